@@ -28,12 +28,10 @@ impl AppAboutDialog {
 
         let file = gio::File::for_path(PathBuf::from("resources/logo.svg"));
 
-        let img = match gdk::Texture::from_file(&file) {
-            Ok(t) => t,
-            Err(err) => panic!("Could not load logo file! {}", err),
-        };
-
-        dialog.set_logo(Some(&img));
+        // The dialog is still useful without the logo.
+        if let Ok(img) = gdk::Texture::from_file(&file) {
+            dialog.set_logo(Some(&img));
+        }
         dialog.set_authors(&authors);
         dialog.set_copyright(Some(&format!("Copyright {} by {}", now.year(), AUTHORS)));
         dialog.set_version(Some(VERSION));
@@ -0,0 +1,74 @@
+//! Checks the `x-widget` and `x-prompt` customizations of schemas without starting the GUI.
+//!
+//! Usage: `schema-lint <schema.json | collection.json>...`
+//! Exits with 1 if any of the schemas has errors, warnings are only printed.
+//!
+//! `schema-lint --x-widget-schema` prints the JSON Schema of the `x-widget` blocks instead.
+
+use std::path::{Path, PathBuf};
+use std::process::exit;
+
+use schematics_gui_reml::error::Result;
+use schematics_gui_reml::schema_lint::SchemaLint;
+use schematics_gui_reml::xwidget_options::XWidgetOptions;
+
+const USAGE: &str =
+    "Usage: schema-lint <schema.json | collection.json>...\n       schema-lint --x-widget-schema";
+
+fn get_schema_paths(path: &Path) -> Result<Vec<PathBuf>> {
+    if path.file_name().unwrap_or_default() == "collection.json" {
+        return SchemaLint::get_schema_paths(path);
+    }
+    Ok(vec![path.to_path_buf()])
+}
+
+fn main() {
+    let args: Vec<PathBuf> = std::env::args().skip(1).map(PathBuf::from).collect();
+
+    if args.is_empty() {
+        eprintln!("{}", USAGE);
+        exit(2);
+    }
+
+    if args[0] == Path::new("--x-widget-schema") {
+        if args.len() > 1 {
+            eprintln!("{}", USAGE);
+            exit(2);
+        }
+        let schema = XWidgetOptions::get_json_schema();
+        println!("{}", serde_json::to_string_pretty(&schema).unwrap());
+        return;
+    }
+
+    let mut failed = false;
+
+    for arg in args {
+        let paths = match get_schema_paths(&arg) {
+            Ok(paths) => paths,
+            Err(err) => {
+                eprintln!("{}", err);
+                failed = true;
+                continue;
+            }
+        };
+
+        for path in paths {
+            match SchemaLint::lint_file(&path) {
+                Ok(diagnostics) => {
+                    for diagnostic in &diagnostics {
+                        println!("{}: {}", path.display(), diagnostic);
+                    }
+                    failed |= SchemaLint::has_errors(&diagnostics);
+                }
+                Err(err) => {
+                    eprintln!("{}", err);
+                    failed = true;
+                }
+            }
+        }
+    }
+
+    if failed {
+        exit(1);
+    }
+}
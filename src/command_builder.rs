@@ -1,4 +1,5 @@
 use convert_case::{Case, Casing};
+use serde_json::{Map, Value};
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct CommandBuilder {
@@ -13,11 +14,45 @@ pub struct Param {
     pub name: String,
     pub value: String,
     pub kind: InputType,
+    /// Set for positional arguments, e.g.: the `name` in `ng g component <name>`.
+    pub position: Option<usize>,
 }
 
 impl Param {
     pub fn new(name: String, value: String, kind: InputType) -> Self {
-        Param { name, value, kind }
+        Param {
+            name,
+            value,
+            kind,
+            position: None,
+        }
+    }
+
+    /// Key/value entries are kept as a JSON object, entries without a key are dropped.
+    pub fn map(name: String, entries: &[(String, String)]) -> Self {
+        let map: Map<String, Value> = entries
+            .iter()
+            .filter(|(key, _)| !key.is_empty())
+            .map(|(key, value)| (key.clone(), Value::String(value.clone())))
+            .collect();
+        let value = match map.is_empty() {
+            true => String::default(),
+            false => Value::Object(map).to_string(),
+        };
+        Param::new(name, value, InputType::Map)
+    }
+
+    pub fn get_map_entries(&self) -> Vec<(String, String)> {
+        match serde_json::from_str::<Value>(&self.value) {
+            Ok(Value::Object(map)) => map
+                .into_iter()
+                .map(|(key, value)| match value {
+                    Value::String(s) => (key, s),
+                    other => (key, other.to_string()),
+                })
+                .collect(),
+            _ => vec![],
+        }
     }
 }
 
@@ -40,9 +75,12 @@ pub enum InputType {
     Multiselect,
     Slider,
     Numeric,
+    Integer,
     Switch,
     Time,
     DateTime,
+    Map,
+    Json,
 }
 
 impl Default for Param {
@@ -51,6 +89,7 @@ impl Default for Param {
             kind: InputType::Text,
             name: String::default(),
             value: String::default(),
+            position: None,
         }
     }
 }
@@ -61,6 +100,7 @@ pub struct CommandBuilderOptions {
     pub quote_paths: bool,
     pub option_case: Case,
     pub pass_boolean: bool,
+    pub pass_map_as_json: bool,
     pub configurable: Option<String>,
 }
 
@@ -70,6 +110,7 @@ impl Default for CommandBuilderOptions {
             escape_multiline_text: false,
             quote_paths: false,
             pass_boolean: true,
+            pass_map_as_json: false,
             configurable: None,
             option_case: Case::Kebab,
         }
@@ -98,6 +139,13 @@ impl CommandBuilder {
         format!("\"{}\"", path.replace(['"'], "\\\""))
     }
 
+    fn is_boolean(param: &Param) -> bool {
+        matches!(
+            param.kind,
+            InputType::Checkbox | InputType::Switch | InputType::Toggle
+        )
+    }
+
     fn get_param_name(&self, param: &Param) -> String {
         if !self.options.pass_boolean {
             if param.value == "true" {
@@ -118,6 +166,7 @@ impl CommandBuilder {
                 param.value.clone()
             }
             InputType::TextArea => self.escape_str(&param.value),
+            InputType::Integer => Self::format_integer(&param.value),
             InputType::File | InputType::Dir => {
                 if self.options.quote_paths {
                     return self.escape_path(&param.value);
@@ -137,6 +186,31 @@ impl CommandBuilder {
         }
     }
 
+    /// Integers without a fractional part, values saved as floats (e.g.: `3.0`) included.
+    fn format_integer(value: &str) -> String {
+        match (value.parse::<i64>(), value.parse::<f64>()) {
+            (Ok(i), _) => i.to_string(),
+            (_, Ok(f)) => (f.round() as i64).to_string(),
+            _ => value.to_owned(),
+        }
+    }
+
+    fn quote_str(value: &str) -> String {
+        format!("'{}'", value.replace('\'', "'\\''"))
+    }
+
+    /// Values of a key/value param, either one JSON string or one `key=value` per entry.
+    fn get_map_values(&self, param: &Param) -> Vec<String> {
+        if self.options.pass_map_as_json {
+            return vec![param.value.clone()];
+        }
+        param
+            .get_map_entries()
+            .into_iter()
+            .map(|(key, value)| format!("{}={}", key, value))
+            .collect()
+    }
+
     pub fn set_configurable(&mut self, value: String) {
         self.options.configurable = Some(value);
     }
@@ -172,6 +246,7 @@ impl CommandBuilder {
             name: self.get_param_name(&param),
             value: self.get_param_value(&param),
             kind: param.kind,
+            position: param.position,
         })
     }
 
@@ -187,27 +262,76 @@ impl CommandBuilder {
                 name: m.name.clone(),
                 value: m.value.clone(),
                 kind: m.kind.clone(),
+                position: m.position,
             })
             .collect::<Vec<Param>>()
     }
 
-    pub fn to_toml(&self) -> String {
-        self.params
-            .clone()
-            .iter()
-            .map(|m| format!("{}='{}'", m.name, m.value.clone()))
-            .collect::<Vec<String>>()
-            .join("\n")
+    /// Values of the positional arguments in order, empty ones are left out. The ones after an
+    /// empty one are passed as `--name=value`, they would take its position otherwise.
+    fn get_positional_values(params: &[Param]) -> Vec<String> {
+        let mut positional: Vec<&Param> = params.iter().filter(|p| p.position.is_some()).collect();
+        positional.sort_by_key(|p| p.position);
+        let mut has_gap = false;
+        let mut result: Vec<String> = vec![];
+
+        for param in positional {
+            if param.value.is_empty() {
+                has_gap = true;
+            } else if has_gap {
+                result.push(format!("--{}={}", param.name, param.value));
+            } else {
+                result.push(param.value.clone());
+            }
+        }
+        result
+    }
+
+    pub fn to_args(&self) -> Vec<String> {
+        let params = self.to_params();
+        let mut args: Vec<String> = Self::get_positional_values(&params);
+
+        for param in params.into_iter().filter(|p| p.position.is_none()) {
+            if param.value.is_empty() {
+                args.push(format!("--{}", param.name));
+            } else if param.kind == InputType::Map {
+                for value in self.get_map_values(&param) {
+                    args.push(format!("--{}", param.name));
+                    args.push(value);
+                }
+            } else if Self::is_boolean(&param) {
+                args.push(format!("--{}={}", param.name, param.value));
+            } else {
+                args.push(format!("--{}", param.name));
+                args.push(param.value);
+            }
+        }
+        args
     }
 
     pub fn to_string(&self, separator: Option<String>) -> String {
         let separator = separator.unwrap_or(String::from(" "));
-        self.to_params()
-            .clone()
-            .iter()
-            .map(|m| format!("--{}{}{}", m.name, separator, m.value))
-            .collect::<Vec<String>>()
-            .join(" ")
+        let params = self.to_params();
+        let mut result = Self::get_positional_values(&params);
+
+        result.extend(params.iter().filter(|m| m.position.is_none()).map(|m| {
+            if Self::is_boolean(m) && !m.value.is_empty() {
+                return format!("--{}={}", m.name, m.value);
+            }
+            if m.kind == InputType::Json && !m.value.is_empty() {
+                return format!("--{}{}{}", m.name, separator, Self::quote_str(&m.value));
+            }
+            if m.kind == InputType::Map && !m.value.is_empty() {
+                return self
+                    .get_map_values(m)
+                    .iter()
+                    .map(|v| format!("--{}{}{}", m.name, separator, Self::quote_str(v)))
+                    .collect::<Vec<String>>()
+                    .join(" ");
+            }
+            format!("--{}{}{}", m.name, separator, m.value)
+        }));
+        result.join(" ")
     }
 }
 
@@ -220,6 +344,7 @@ mod tests {
             name: name.to_string(),
             value: value.unwrap_or_default().to_string(),
             kind: kind.unwrap_or_default(),
+            position: None,
         }
     }
 
@@ -378,36 +503,128 @@ mod tests {
     }
 
     #[test]
-    fn to_toml() {
+    fn to_string_no_separator() {
         let mut builder: CommandBuilder = CommandBuilder::new(None);
         let params = get_params();
 
         builder.set_params(params);
 
-        assert_eq!(builder.to_toml(), "foo='1'\nbar='foo'");
+        assert_eq!(builder.to_string(None), "--foo 1 --bar foo");
     }
 
     #[test]
-    fn to_string_no_separator() {
+    fn to_string_separator() {
         let mut builder: CommandBuilder = CommandBuilder::new(None);
         let params = get_params();
 
         builder.set_params(params);
 
-        assert_eq!(builder.to_string(None), "--foo 1 --bar foo");
+        assert_eq!(
+            builder.to_string(Some(":".to_string())),
+            "--foo:1 --bar:foo"
+        );
     }
 
     #[test]
-    fn to_string_separator() {
+    fn to_string_boolean_value() {
         let mut builder: CommandBuilder = CommandBuilder::new(None);
-        let params = get_params();
+
+        builder.add(get_param("foo", Some("false"), Some(InputType::Switch)));
+
+        assert_eq!(builder.to_string(None), "--foo=false");
+    }
+
+    #[test]
+    fn to_args() {
+        let mut builder: CommandBuilder = CommandBuilder::new(Some(CommandBuilderOptions {
+            pass_boolean: false,
+            ..Default::default()
+        }));
+        let mut params = get_params();
+        params.push(get_param("baz", Some("false"), Some(InputType::Switch)));
 
         builder.set_params(params);
 
         assert_eq!(
-            builder.to_string(Some(":".to_string())),
-            "--foo:1 --bar:foo"
+            builder.to_args(),
+            vec!["--foo", "1", "--bar", "foo", "--no-baz"]
+        );
+    }
+
+    #[test]
+    fn to_args_boolean_value() {
+        let mut builder: CommandBuilder = CommandBuilder::new(None);
+
+        builder.add(get_param("baz", Some("true"), Some(InputType::Checkbox)));
+
+        assert_eq!(builder.to_args(), vec!["--baz=true"]);
+    }
+
+    #[test]
+    fn map_param() {
+        let param = Param::map(
+            String::from("define"),
+            &[
+                (String::from("foo"), String::from("1")),
+                (String::default(), String::from("ignored")),
+            ],
+        );
+
+        assert_eq!(param.kind, InputType::Map);
+        assert_eq!(param.value, "{\"foo\":\"1\"}");
+        assert_eq!(
+            param.get_map_entries(),
+            vec![(String::from("foo"), String::from("1"))]
+        );
+        assert_eq!(Param::map(String::from("define"), &[]).value, "");
+    }
+
+    #[test]
+    fn to_args_map_pairs() {
+        let mut builder: CommandBuilder = CommandBuilder::new(None);
+
+        builder.add(get_param(
+            "define",
+            Some("{\"a\":\"1\",\"b\":\"x y\"}"),
+            Some(InputType::Map),
+        ));
+
+        assert_eq!(
+            builder.to_args(),
+            vec!["--define", "a=1", "--define", "b=x y"]
         );
+        assert_eq!(builder.to_string(None), "--define 'a=1' --define 'b=x y'");
+    }
+
+    #[test]
+    fn to_args_map_json() {
+        let mut builder: CommandBuilder = CommandBuilder::new(Some(CommandBuilderOptions {
+            pass_map_as_json: true,
+            ..Default::default()
+        }));
+
+        builder.add(get_param(
+            "env",
+            Some("{\"a\":\"1\"}"),
+            Some(InputType::Map),
+        ));
+
+        assert_eq!(builder.to_args(), vec!["--env", "{\"a\":\"1\"}"]);
+        assert_eq!(builder.to_string(None), "--env '{\"a\":\"1\"}'");
+    }
+
+    #[test]
+    fn to_string_json() {
+        let mut builder: CommandBuilder = CommandBuilder::new(None);
+
+        builder.add(get_param(
+            "options",
+            Some("[1,\"it's\"]"),
+            Some(InputType::Json),
+        ));
+
+        assert_eq!(builder.to_args(), vec!["--options", "[1,\"it's\"]"]);
+        assert_eq!(builder.to_string(None), "--options '[1,\"it'\\''s\"]'");
     }
 
     #[test]
@@ -439,4 +656,54 @@ mod tests {
         assert_eq!(result.len(), 1);
         assert_eq!(result[0].name, params[2].name);
     }
+
+    #[test]
+    fn to_args_integer() {
+        let mut builder = CommandBuilder::new(None);
+        builder.add(get_param("port", Some("4200.0"), Some(InputType::Integer)));
+        builder.add(get_param("retries", Some("3"), Some(InputType::Integer)));
+        builder.add(get_param("ratio", Some("0.5"), Some(InputType::Numeric)));
+
+        assert_eq!(
+            builder.to_args(),
+            vec!["--port", "4200", "--retries", "3", "--ratio", "0.5"]
+        );
+    }
+
+    #[test]
+    fn to_args_positional() {
+        let mut builder = CommandBuilder::new(None);
+        let mut path = get_param("path", Some("src/app"), None);
+        let mut name = get_param("name", Some("my-comp"), None);
+        let mut empty = get_param("module", None, None);
+        path.position = Some(1);
+        name.position = Some(0);
+        empty.position = Some(2);
+
+        builder.set_params(vec![get_param(
+            "flat",
+            Some("true"),
+            Some(InputType::Switch),
+        )]);
+        builder.set_params(vec![path, name, empty]);
+
+        assert_eq!(builder.to_args(), vec!["my-comp", "src/app", "--flat=true"]);
+        assert_eq!(builder.to_string(None), "my-comp src/app --flat=true");
+    }
+
+    #[test]
+    fn to_args_positional_gap() {
+        let mut builder = CommandBuilder::new(None);
+        let mut name = get_param("name", None, None);
+        let mut path = get_param("path", Some("src/app"), None);
+        let mut module = get_param("module", Some("app"), None);
+        name.position = Some(0);
+        path.position = Some(1);
+        module.position = Some(2);
+
+        builder.set_params(vec![module, path, name]);
+
+        assert_eq!(builder.to_args(), vec!["--path=src/app", "--module=app"]);
+        assert_eq!(builder.to_string(None), "--path=src/app --module=app");
+    }
 }
@@ -3,8 +3,10 @@ use relm4::gtk::prelude::{
 };
 use relm4::{gtk, ComponentParts, ComponentSender, SimpleComponent};
 use serde_json::Value;
-use std::fs::{self, write};
+use std::path::Path;
 
+use crate::error::Result;
+use crate::file_utils::FileUtils;
 use crate::impl_validation;
 use crate::traits::{JsonBuffer, Validator};
 use sourceview5::prelude::ViewExt;
@@ -32,14 +34,8 @@ impl ConfigEditorDialogModel {
         self.json_buf.text(&start, &end, false).to_string()
     }
 
-    fn write(&self) {
-        match write(
-            self.config_file.as_str(),
-            self.get_text_view_content().as_bytes(),
-        ) {
-            Ok(s) => s,
-            Err(err) => panic!("Could not save file! {}", err),
-        }
+    fn write(&self) -> Result<()> {
+        FileUtils::write_str(Path::new(&self.config_file), &self.get_text_view_content())
     }
 
     fn validate(&mut self) -> bool {
@@ -165,11 +161,14 @@ impl SimpleComponent for ConfigEditorDialogModel {
         match message {
             ConfigEditorDialogInput::Show(path) => {
                 self.config_file = path.clone();
-                let config: Value =
-                    serde_json::from_str(fs::read_to_string(path).unwrap().as_str()).unwrap();
+                self.set_error(false);
 
-                self.json_buf
-                    .set_text(&serde_json::to_string_pretty(&config).unwrap());
+                match FileUtils::read_json::<Value>(Path::new(&path)) {
+                    Ok(config) => self
+                        .json_buf
+                        .set_text(&serde_json::to_string_pretty(&config).unwrap_or_default()),
+                    Err(err) => self.print_error(&err.to_string()),
+                }
                 self.hidden = false
             }
             ConfigEditorDialogInput::Apply => {
@@ -178,7 +177,10 @@ impl SimpleComponent for ConfigEditorDialogModel {
                 }
 
                 self.set_error(false);
-                self.write();
+                if let Err(err) = self.write() {
+                    self.print_error(&err.to_string());
+                    return;
+                }
                 self.hidden = true;
             }
             ConfigEditorDialogInput::Cancel => {
@@ -1,31 +1,50 @@
 use relm4::gtk::glib::object::Cast;
 use relm4::gtk::{Box, Orientation, Widget};
+use std::rc::Rc;
 
+use crate::directives::DirectiveRegistry;
 use crate::form_utils::FormUtils;
 use crate::schema_parsing::{
     ChoiceEntry, DateEntry, FsEntry, MenuEntry, NumericEntry, SchemaProp, TextEntry,
 };
 
+/// Menus with more items than this get a search entry.
+const SEARCHABLE_ITEMS: usize = 30;
+
 pub struct DefaultWidgetBuilder {
     prop: SchemaProp,
     utils: FormUtils,
     field: String,
     cwd: Option<String>,
+    /// Directives of the form, built once for all its widgets.
+    registry: Rc<DirectiveRegistry>,
 }
 
 impl DefaultWidgetBuilder {
-    pub fn new(prop: &SchemaProp, field: String, cwd: Option<String>) -> Self {
+    pub fn new(
+        prop: &SchemaProp,
+        field: String,
+        cwd: Option<String>,
+        registry: Rc<DirectiveRegistry>,
+    ) -> Self {
         DefaultWidgetBuilder {
             prop: prop.clone(),
             utils: FormUtils::new(),
             cwd,
             field,
+            registry,
         }
     }
 
     pub fn get_widget(&self) -> Widget {
         let prompt = self.prop.x_prompt.as_ref();
 
+        if let Some(choices) = self.prop.get_choices() {
+            return self.get_labelled_menu(&choices);
+        } else if let Some(variants) = self.prop.get_type_variants() {
+            return self.get_type_switch(&variants);
+        }
+
         if self.prop.r#type == "string" || self.prop.r#type == "array" {
             if self.prop.r#enum.is_some()
                 || (self.prop.x_prompt.is_some()
@@ -34,7 +53,7 @@ impl DefaultWidgetBuilder {
                 if prompt.is_some() && prompt.unwrap().has_multiselect() {
                     return self.get_multiselect(MenuEntry::default()).upcast();
                 }
-                return self.get_menu(MenuEntry::default()).upcast();
+                return self.get_menu().upcast();
             } else if self.prop.format.is_some() {
                 let format = self.prop.format.as_deref().unwrap();
                 if format == "path" {
@@ -51,29 +70,72 @@ impl DefaultWidgetBuilder {
             self.get_text_input(TextEntry::default()).upcast()
         } else if self.prop.r#type == "boolean" {
             return self.get_switch_input(ChoiceEntry::default()).upcast();
-        } else if self.prop.r#type == "number" {
-            return self.get_numeric_input(NumericEntry::default()).upcast();
+        } else if self.prop.r#type == "number" || self.prop.is_integer() {
+            return self
+                .get_numeric_input(self.prop.get_numeric_entry())
+                .upcast();
+        } else if self.prop.is_map() {
+            return self.get_map_input().upcast();
         } else {
             return Box::new(Orientation::Horizontal, 0).upcast();
         }
     }
 
-    fn get_menu(&self, options: MenuEntry) -> Widget {
+    fn get_menu(&self) -> Widget {
+        let items = self.get_items();
+        if items.len() > SEARCHABLE_ITEMS {
+            return self
+                .utils
+                .searchable_dropdown(&self.field, &items, self.prop.default.clone())
+                .upcast();
+        }
         self.utils
-            .dropdown(
-                &self.field,
-                &self.get_items(),
-                Some(options),
-                self.prop.default.clone(),
-            )
+            .dropdown(&self.field, &items, self.prop.default.clone())
+            .upcast()
+    }
+
+    fn get_labelled_menu(&self, choices: &[(String, String)]) -> Widget {
+        self.utils
+            .labelled_dropdown(&self.field, choices, self.prop.default.clone())
+            .upcast()
+    }
+
+    fn get_type_switch(&self, variants: &[SchemaProp]) -> Widget {
+        let widgets: Vec<(String, String, Widget)> = variants
+            .iter()
+            .map(|variant| {
+                let builder = DefaultWidgetBuilder::new(
+                    variant,
+                    self.field.clone(),
+                    self.cwd.clone(),
+                    self.registry.clone(),
+                );
+                let title = variant.title.clone().unwrap_or(variant.r#type.clone());
+                (title, variant.r#type.clone(), builder.get_widget())
+            })
+            .collect();
+        let selected = variants
+            .iter()
+            .position(|v| v.default.is_some() && v.default == self.prop.default)
+            .unwrap_or_default();
+
+        self.utils
+            .type_switch(&self.field, &widgets, selected as u32)
             .upcast()
     }
 
     fn get_multiselect(&self, options: MenuEntry) -> Widget {
+        let items = self.get_items();
+        if items.len() > SEARCHABLE_ITEMS {
+            return self
+                .utils
+                .searchable_multiselect(&self.field, &items, self.prop.default.clone())
+                .upcast();
+        }
         self.utils
             .multiselect_input(
                 &self.field,
-                &self.get_items(),
+                &items,
                 Some(options),
                 self.prop.default.clone(),
             )
@@ -81,33 +143,7 @@ impl DefaultWidgetBuilder {
     }
 
     fn get_items(&self) -> Vec<String> {
-        let empty: Vec<String> = vec![];
-        if self.prop.r#type == "string" && self.prop.r#enum.is_some() {
-            return self.prop.r#enum.as_ref().unwrap().clone();
-        }
-        if (self.prop.r#type == "array" || self.prop.r#type == "string")
-            && self.prop.x_prompt.is_some()
-            && self.prop.x_prompt.as_ref().unwrap().has_items()
-        {
-            let prompt = self.prop.x_prompt.as_ref().unwrap();
-            if self.cwd.is_some() {
-                if prompt.has_modules() {
-                    return prompt.get_modules(self.cwd.as_ref().unwrap());
-                } else if prompt.has_models() {
-                    return prompt.get_models(self.cwd.as_ref().unwrap());
-                } else if prompt.has_dirs() {
-                    return prompt.get_dirs_or_files(true, self.cwd.as_ref().unwrap());
-                } else if prompt.has_files() {
-                    return prompt.get_dirs_or_files(false, self.cwd.as_ref().unwrap());
-                }
-            }
-            if prompt.has_modules() || prompt.has_models() || prompt.has_dirs() {
-                return prompt.get_items_placeholder();
-            }
-            return prompt.get_items();
-        }
-
-        empty
+        self.prop.get_items(self.cwd.as_deref(), &self.registry)
     }
 
     fn get_file_input(&self, options: FsEntry) -> Widget {
@@ -134,6 +170,12 @@ impl DefaultWidgetBuilder {
             .upcast()
     }
 
+    fn get_map_input(&self) -> Widget {
+        self.utils
+            .map_input(&self.field, self.prop.default.clone())
+            .upcast()
+    }
+
     fn get_text_input(&self, options: TextEntry) -> Widget {
         self.utils
             .text_input(&self.field, Some(options), self.prop.default.clone())
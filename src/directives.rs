@@ -0,0 +1,508 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::Path;
+
+use crate::file_utils::FileUtils;
+use crate::settings_utils::SettingsUtils;
+
+/// Where the built-in directives look for items, relative to the cwd. Can be changed in the
+/// `[directives]` table of `settings.toml`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct DirectiveOptions {
+    /// Directory whose subdirectories `$modules` lists.
+    pub modules_dir: String,
+    /// Directory `$models` searches.
+    pub models_dir: String,
+    /// Name of the directories holding the models.
+    pub models_parent: String,
+    /// File name suffix of the models.
+    pub models_suffix: String,
+    /// Names of the entries the glob directives never search, besides the `.gitignore`d ones.
+    pub exclude: Vec<String>,
+}
+
+impl Default for DirectiveOptions {
+    fn default() -> Self {
+        DirectiveOptions {
+            modules_dir: String::from("src"),
+            models_dir: String::from("src"),
+            models_parent: String::from("models"),
+            models_suffix: String::from("model.ts"),
+            exclude: vec![String::from("node_modules"), String::from(".git")],
+        }
+    }
+}
+
+/// Computes the items of a `x-prompt.items` directive like `$modules` or `$dir:src/app`.
+pub trait DirectiveProvider {
+    /// Name of the directive without the `$`.
+    fn name(&self) -> &str;
+
+    /// Whether the argument after the `:` is acceptable, e.g.: it's mandatory for `$glob`.
+    fn accepts(&self, _argument: Option<&str>) -> bool {
+        true
+    }
+
+    fn get_items(&self, argument: Option<&str>, cwd: &Path) -> Vec<String>;
+}
+
+/// `$modules[:dir]`, the subdirectories of the modules directory.
+pub struct ModulesProvider {
+    dir: String,
+}
+
+impl DirectiveProvider for ModulesProvider {
+    fn name(&self) -> &str {
+        "modules"
+    }
+
+    fn get_items(&self, argument: Option<&str>, cwd: &Path) -> Vec<String> {
+        let path = cwd.join(argument.unwrap_or(&self.dir));
+        let mut result: Vec<String> = FileUtils::read_fs_entries(&path, true)
+            .unwrap_or_default()
+            .iter()
+            .map(|p| p.file_name().unwrap().to_string_lossy().to_string())
+            .collect();
+
+        result.sort();
+        result
+    }
+}
+
+/// `$models[:dir]`, the model files in the `models` directories of the models directory.
+pub struct ModelsProvider {
+    dir: String,
+    parent: String,
+    suffix: String,
+}
+
+impl DirectiveProvider for ModelsProvider {
+    fn name(&self) -> &str {
+        "models"
+    }
+
+    fn get_items(&self, argument: Option<&str>, cwd: &Path) -> Vec<String> {
+        let path = cwd.join(argument.unwrap_or(&self.dir));
+        let prefix = path.to_string_lossy().to_string();
+        // `user.model.ts` is listed as `user.model`
+        let stem = Path::new(&self.suffix)
+            .file_stem()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
+        let mut result: Vec<String> =
+            FileUtils::read_fs_entries_recursive(&path, &Some(vec![self.parent.as_str()]))
+                .unwrap_or_default()
+                .iter()
+                .map(|p| p.to_string_lossy().to_string())
+                .filter(|p| p.ends_with(&self.suffix))
+                .map(|p| p.replace(&prefix, "").replace(&self.suffix, &stem))
+                .collect();
+
+        result.sort();
+        result
+    }
+}
+
+/// Whether a directive argument holds glob patterns rather than a path.
+fn is_glob(argument: &str) -> bool {
+    argument.contains(['*', '?', '{', ' ', '!'])
+}
+
+/// Include and `!`exclude patterns of a glob directive, separated by spaces.
+fn get_patterns(argument: &str) -> Vec<&str> {
+    argument.split_whitespace().collect()
+}
+
+fn accepts_patterns(argument: &str) -> bool {
+    let patterns = get_patterns(argument);
+    patterns.iter().any(|p| !p.starts_with('!'))
+        && patterns
+            .iter()
+            .all(|p| FileUtils::glob_to_regex(p.trim_start_matches('!')).is_some())
+}
+
+/// `$dir:<path>` and `$files:<path>`, the subdirectories or files of a directory. With glob
+/// patterns, e.g.: `$files:src/**/*.service.ts !**/*.spec.ts`, the matching paths at any depth.
+pub struct FsEntriesProvider {
+    dirs: bool,
+    exclude: Vec<String>,
+}
+
+impl DirectiveProvider for FsEntriesProvider {
+    fn name(&self) -> &str {
+        match self.dirs {
+            true => "dir",
+            false => "files",
+        }
+    }
+
+    fn accepts(&self, argument: Option<&str>) -> bool {
+        match argument {
+            Some(argument) if is_glob(argument) => accepts_patterns(argument),
+            _ => true,
+        }
+    }
+
+    fn get_items(&self, argument: Option<&str>, cwd: &Path) -> Vec<String> {
+        if let Some(argument) = argument.filter(|a| is_glob(a)) {
+            return FileUtils::find_glob(cwd, &get_patterns(argument), self.dirs, &self.exclude);
+        }
+        // Absolute paths are taken as relative to the cwd, they would replace it otherwise.
+        let mut path = cwd.join(argument.unwrap_or_default().trim_start_matches('/'));
+
+        if !path.exists() {
+            path = cwd.to_path_buf();
+        }
+        let mut result: Vec<String> = FileUtils::read_fs_entries(&path, self.dirs)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|p| p.file_name())
+            .map(|name| name.to_string_lossy().to_string())
+            .collect();
+
+        result.sort();
+        result
+    }
+}
+
+/// `$glob:<patterns>`, the files matching the glob patterns relative to the cwd, e.g.:
+/// `src/**/*.pipe.ts`.
+pub struct GlobProvider {
+    exclude: Vec<String>,
+}
+
+impl DirectiveProvider for GlobProvider {
+    fn name(&self) -> &str {
+        "glob"
+    }
+
+    fn accepts(&self, argument: Option<&str>) -> bool {
+        argument.is_some_and(accepts_patterns)
+    }
+
+    fn get_items(&self, argument: Option<&str>, cwd: &Path) -> Vec<String> {
+        let patterns = get_patterns(argument.unwrap_or_default());
+        FileUtils::find_glob(cwd, &patterns, false, &self.exclude)
+    }
+}
+
+/// `$json:<file>#<pointer>`, the items of an array or the keys of an object in a JSON file,
+/// e.g.: `$json:angular.json#/projects`.
+pub struct JsonProvider {}
+
+impl DirectiveProvider for JsonProvider {
+    fn name(&self) -> &str {
+        "json"
+    }
+
+    fn accepts(&self, argument: Option<&str>) -> bool {
+        argument.is_some_and(|a| !a.is_empty())
+    }
+
+    fn get_items(&self, argument: Option<&str>, cwd: &Path) -> Vec<String> {
+        let (file, pointer) = argument
+            .unwrap_or_default()
+            .split_once('#')
+            .unwrap_or((argument.unwrap_or_default(), ""));
+        let json: Value = FileUtils::read_json(&cwd.join(file)).unwrap_or_default();
+
+        match json.pointer(pointer) {
+            Some(Value::Array(items)) => items
+                .iter()
+                .map(|i| match i {
+                    Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                })
+                .collect(),
+            Some(Value::Object(map)) => map.keys().cloned().collect(),
+            _ => vec![],
+        }
+    }
+}
+
+/// `$packages[:section]`, the dependencies in the `package.json` of the cwd. All of
+/// `dependencies`, `devDependencies` and `peerDependencies` unless a section is given.
+pub struct PackagesProvider {}
+
+impl DirectiveProvider for PackagesProvider {
+    fn name(&self) -> &str {
+        "packages"
+    }
+
+    fn get_items(&self, argument: Option<&str>, cwd: &Path) -> Vec<String> {
+        let json: Value = FileUtils::read_json(&cwd.join("package.json")).unwrap_or_default();
+        let sections = match argument {
+            Some(section) => vec![section],
+            None => vec!["dependencies", "devDependencies", "peerDependencies"],
+        };
+        let mut result: Vec<String> = sections
+            .iter()
+            .filter_map(|s| json[s].as_object())
+            .flat_map(|deps| deps.keys().cloned())
+            .collect();
+
+        result.sort();
+        result.dedup();
+        result
+    }
+}
+
+/// The directive providers by name, providers registered later override the earlier ones.
+pub struct DirectiveRegistry {
+    providers: Vec<Box<dyn DirectiveProvider>>,
+}
+
+impl Default for DirectiveRegistry {
+    fn default() -> Self {
+        Self::new(DirectiveOptions::default())
+    }
+}
+
+impl DirectiveRegistry {
+    /// Registry of the built-in directives.
+    pub fn new(options: DirectiveOptions) -> Self {
+        let mut registry = DirectiveRegistry { providers: vec![] };
+
+        registry.register(Box::new(ModulesProvider {
+            dir: options.modules_dir,
+        }));
+        registry.register(Box::new(ModelsProvider {
+            dir: options.models_dir,
+            parent: options.models_parent,
+            suffix: options.models_suffix,
+        }));
+        registry.register(Box::new(FsEntriesProvider {
+            dirs: true,
+            exclude: options.exclude.clone(),
+        }));
+        registry.register(Box::new(FsEntriesProvider {
+            dirs: false,
+            exclude: options.exclude.clone(),
+        }));
+        registry.register(Box::new(GlobProvider {
+            exclude: options.exclude,
+        }));
+        registry.register(Box::new(JsonProvider {}));
+        registry.register(Box::new(PackagesProvider {}));
+        registry
+    }
+
+    /// Built-in directives configured with the options of the settings file.
+    pub fn from_settings() -> Self {
+        let options = SettingsUtils::new()
+            .read()
+            .map(|s| s.directives)
+            .unwrap_or_default();
+        Self::new(options)
+    }
+
+    pub fn register(&mut self, provider: Box<dyn DirectiveProvider>) {
+        self.providers.push(provider);
+    }
+
+    /// Splits `$name:argument` into its parts, `None` if `items` isn't a directive.
+    pub fn parse(items: &str) -> Option<(&str, Option<&str>)> {
+        let directive = items.strip_prefix('$')?;
+        match directive.split_once(':') {
+            Some((name, argument)) => Some((name, Some(argument))),
+            None => Some((directive, None)),
+        }
+    }
+
+    fn get_provider(&self, name: &str) -> Option<&dyn DirectiveProvider> {
+        self.providers
+            .iter()
+            .rev()
+            .find(|p| p.name() == name)
+            .map(|p| p.as_ref())
+    }
+
+    /// Whether `items` is a directive with a provider accepting its argument.
+    pub fn is_known(&self, items: &str) -> bool {
+        Self::parse(items).is_some_and(|(name, argument)| {
+            self.get_provider(name).is_some_and(|p| p.accepts(argument))
+        })
+    }
+
+    /// Items of a directive, `None` if `items` isn't a known directive.
+    pub fn get_items(&self, items: &str, cwd: &Path) -> Option<Vec<String>> {
+        if !self.is_known(items) {
+            return None;
+        }
+        let (name, argument) = Self::parse(items)?;
+        Some(self.get_provider(name)?.get_items(argument, cwd))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::write_tree;
+
+    struct EnvProvider {}
+
+    impl DirectiveProvider for EnvProvider {
+        fn name(&self) -> &str {
+            "modules"
+        }
+
+        fn get_items(&self, _argument: Option<&str>, _cwd: &Path) -> Vec<String> {
+            vec![String::from("dev"), String::from("prod")]
+        }
+    }
+
+    #[test]
+    fn parse_success() {
+        assert_eq!(
+            DirectiveRegistry::parse("$dir:src/app"),
+            Some(("dir", Some("src/app")))
+        );
+        assert_eq!(
+            DirectiveRegistry::parse("$modules"),
+            Some(("modules", None))
+        );
+        assert_eq!(DirectiveRegistry::parse("modules"), None);
+    }
+
+    #[test]
+    fn is_known_success() {
+        let registry = DirectiveRegistry::default();
+
+        assert!(registry.is_known("$models"));
+        assert!(registry.is_known("$files:/src"));
+        assert!(registry.is_known("$packages"));
+        assert!(!registry.is_known("$modles"));
+        assert!(!registry.is_known("$glob"));
+        assert!(!registry.is_known("$json:"));
+        assert!(registry.is_known("$files:src/**/*.ts !**/*.spec.ts"));
+        assert!(!registry.is_known("$files:!**/*.spec.ts"));
+        assert!(!registry.is_known("$dir:src/{app"));
+    }
+
+    #[test]
+    fn get_items_globs() {
+        let root = write_tree(
+            "directives/globs",
+            &[
+                (".gitignore", "dist\n"),
+                ("src/app/users/users.service.ts", ""),
+                ("src/app/users/users.service.spec.ts", ""),
+                ("src/app/shared_ui.v2/button.ts", ""),
+                ("dist/users.service.ts", ""),
+                ("node_modules/foo/foo.service.ts", ""),
+            ],
+        );
+        let registry = DirectiveRegistry::default();
+
+        assert_eq!(
+            registry.get_items("$files:**/*.service.ts", &root),
+            Some(vec![String::from("src/app/users/users.service.ts")])
+        );
+        assert_eq!(
+            registry.get_items("$glob:src/** !**/*.spec.ts !**/button.ts", &root),
+            Some(vec![String::from("src/app/users/users.service.ts")])
+        );
+        assert_eq!(
+            registry.get_items("$dir:src/*/*", &root),
+            Some(vec![
+                String::from("src/app/shared_ui.v2"),
+                String::from("src/app/users")
+            ])
+        );
+        assert_eq!(
+            registry.get_items("$dir:src/app", &root),
+            Some(vec![String::from("shared_ui.v2"), String::from("users")])
+        );
+    }
+
+    #[test]
+    fn get_items_dir_in_cwd() {
+        let root = write_tree("directives/cwd", &[("src/app/main.ts", "")]);
+        let registry = DirectiveRegistry::default();
+        let src = Some(vec![String::from("src")]);
+
+        assert_eq!(registry.get_items("$dir", &root), src);
+        assert_eq!(registry.get_items("$dir:/etc", &root), src);
+        assert_eq!(
+            registry.get_items("$dir:/src", &root),
+            Some(vec![String::from("app")])
+        );
+    }
+
+    #[test]
+    fn get_items_configured_dirs() {
+        let root = write_tree(
+            "directives/configured",
+            &[
+                ("apps/admin/main.ts", ""),
+                ("apps/shop/main.ts", ""),
+                ("apps/shop/models/user.entity.ts", ""),
+                ("apps/shop/models/user.ts", ""),
+            ],
+        );
+        let registry = DirectiveRegistry::new(DirectiveOptions {
+            modules_dir: String::from("apps"),
+            models_dir: String::from("apps"),
+            models_suffix: String::from("entity.ts"),
+            ..Default::default()
+        });
+
+        assert_eq!(
+            registry.get_items("$modules", &root),
+            Some(vec![String::from("admin"), String::from("shop")])
+        );
+        assert_eq!(
+            registry.get_items("$models", &root),
+            Some(vec![String::from("/shop/models/user.entity")])
+        );
+    }
+
+    #[test]
+    fn get_items_json_and_packages() {
+        let root = write_tree(
+            "directives/json",
+            &[
+                (
+                    "angular.json",
+                    r#"{ "projects": { "app": {}, "lib": {} } }"#,
+                ),
+                ("envs.json", r#"{ "names": ["dev", 1] }"#),
+                (
+                    "package.json",
+                    r#"{ "dependencies": { "rxjs": "*" }, "devDependencies": { "jest": "*", "rxjs": "*" } }"#,
+                ),
+            ],
+        );
+        let registry = DirectiveRegistry::default();
+
+        assert_eq!(
+            registry.get_items("$json:angular.json#/projects", &root),
+            Some(vec![String::from("app"), String::from("lib")])
+        );
+        assert_eq!(
+            registry.get_items("$json:envs.json#/names", &root),
+            Some(vec![String::from("dev"), String::from("1")])
+        );
+        assert_eq!(
+            registry.get_items("$packages", &root),
+            Some(vec![String::from("jest"), String::from("rxjs")])
+        );
+        assert_eq!(
+            registry.get_items("$packages:dependencies", &root),
+            Some(vec![String::from("rxjs")])
+        );
+    }
+
+    #[test]
+    fn register_overrides_built_in() {
+        let mut registry = DirectiveRegistry::default();
+        registry.register(Box::new(EnvProvider {}));
+
+        assert_eq!(
+            registry.get_items("$modules", Path::new("/")),
+            Some(vec![String::from("dev"), String::from("prod")])
+        );
+    }
+}
@@ -0,0 +1,324 @@
+use is_executable::IsExecutable;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::fs::{read_dir, read_to_string};
+use std::path::{Path, PathBuf};
+
+use crate::runner_utils::RunnerUtils;
+use crate::schematics::Collection;
+
+pub struct DiscoveryUtils {
+    home: PathBuf,
+    cwd: PathBuf,
+    vars: HashMap<String, OsString>,
+    runner_executables: Vec<String>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct DiscoveredCollection {
+    pub name: String,
+    pub package: String,
+    pub collection: String,
+}
+
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct DiscoveryResult {
+    pub node_binaries: Vec<String>,
+    pub runners: Vec<String>,
+    pub collections: Vec<DiscoveredCollection>,
+}
+
+impl Default for DiscoveryUtils {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DiscoveryUtils {
+    pub fn new() -> Self {
+        let cwd = std::env::current_dir().unwrap_or_default();
+        let home = std::env::var_os("HOME")
+            .map(PathBuf::from)
+            .unwrap_or(cwd.clone());
+        let vars = ["PATH", "NVM_DIR", "FNM_DIR", "VOLTA_HOME", "ASDF_DATA_DIR"]
+            .iter()
+            .filter_map(|name| std::env::var_os(name).map(|v| (name.to_string(), v)))
+            .collect();
+        let runner_executables = RunnerUtils::new()
+            .list()
+            .0
+            .into_iter()
+            .map(|r| r.executable)
+            .filter(|e| !e.is_empty())
+            .collect();
+
+        DiscoveryUtils {
+            home,
+            cwd,
+            vars,
+            runner_executables,
+        }
+    }
+
+    fn get_var_dir(&self, name: &str, default: PathBuf) -> PathBuf {
+        self.vars.get(name).map(PathBuf::from).unwrap_or(default)
+    }
+
+    fn get_sub_dirs(path: &Path) -> Vec<PathBuf> {
+        match read_dir(path) {
+            Ok(entries) => {
+                let mut result: Vec<PathBuf> = entries
+                    .filter_map(|e| e.ok())
+                    .map(|e| e.path())
+                    .filter(|p| p.is_dir())
+                    .collect();
+                result.sort();
+                result
+            }
+            Err(_) => vec![],
+        }
+    }
+
+    fn get_version_manager_bin_dirs(&self) -> Vec<PathBuf> {
+        let mut result: Vec<PathBuf> = vec![];
+
+        let nvm = self.get_var_dir("NVM_DIR", self.home.join(".nvm"));
+        for version in Self::get_sub_dirs(&nvm.join("versions").join("node")) {
+            result.push(version.join("bin"));
+        }
+
+        let fnm_roots = match self.vars.get("FNM_DIR") {
+            Some(dir) => vec![PathBuf::from(dir)],
+            None => vec![
+                self.home.join(".local").join("share").join("fnm"),
+                self.home.join(".fnm"),
+                self.home
+                    .join("Library")
+                    .join("Application Support")
+                    .join("fnm"),
+            ],
+        };
+        for root in fnm_roots {
+            for version in Self::get_sub_dirs(&root.join("node-versions")) {
+                result.push(version.join("installation").join("bin"));
+            }
+        }
+
+        let volta = self.get_var_dir("VOLTA_HOME", self.home.join(".volta"));
+        result.push(volta.join("bin"));
+        for version in Self::get_sub_dirs(&volta.join("tools").join("image").join("node")) {
+            result.push(version.join("bin"));
+        }
+
+        let asdf = self.get_var_dir("ASDF_DATA_DIR", self.home.join(".asdf"));
+        for version in Self::get_sub_dirs(&asdf.join("installs").join("nodejs")) {
+            result.push(version.join("bin"));
+        }
+
+        result
+    }
+
+    pub fn get_bin_dirs(&self) -> Vec<PathBuf> {
+        let mut result: Vec<PathBuf> = match self.vars.get("PATH") {
+            Some(path) => std::env::split_paths(path).collect(),
+            None => vec![],
+        };
+
+        result.extend(self.get_version_manager_bin_dirs());
+        result.push(self.cwd.join("node_modules").join(".bin"));
+        Self::dedup(result.into_iter().filter(|p| p.is_dir()).collect())
+    }
+
+    pub fn get_node_modules_dirs(&self, bin_dirs: &[PathBuf]) -> Vec<PathBuf> {
+        let mut result: Vec<PathBuf> = vec![self.cwd.join("node_modules")];
+
+        for bin in bin_dirs {
+            if let Some(prefix) = bin.parent() {
+                result.push(prefix.join("lib").join("node_modules"));
+                result.push(prefix.join("node_modules"));
+            }
+        }
+
+        let volta = self.get_var_dir("VOLTA_HOME", self.home.join(".volta"));
+        for package in Self::get_sub_dirs(&volta.join("tools").join("image").join("packages")) {
+            result.push(package.join("lib").join("node_modules"));
+        }
+
+        Self::dedup(result.into_iter().filter(|p| p.is_dir()).collect())
+    }
+
+    fn find_executables(&self, bin_dirs: &[PathBuf], names: &[String]) -> Vec<String> {
+        let mut result: Vec<PathBuf> = vec![];
+
+        for dir in bin_dirs {
+            for name in names {
+                let path = dir.join(name);
+                if path.is_file() && path.is_executable() {
+                    result.push(path);
+                }
+            }
+        }
+
+        Self::dedup(result)
+            .iter()
+            .map(|p| p.to_str().unwrap_or_default().to_string())
+            .collect()
+    }
+
+    pub fn find_node_binaries(&self, bin_dirs: &[PathBuf]) -> Vec<String> {
+        self.find_executables(bin_dirs, &[String::from("node"), String::from("node.exe")])
+    }
+
+    pub fn find_runners(&self, bin_dirs: &[PathBuf]) -> Vec<String> {
+        self.find_executables(bin_dirs, &self.runner_executables)
+    }
+
+    fn read_collection(package_dir: &Path) -> Option<DiscoveredCollection> {
+        let package = package_dir.join("package.json");
+        let collection_path = Collection::get_collection_path(&package)?;
+        let json: Value = serde_json::from_str(&read_to_string(&package).ok()?).ok()?;
+
+        Some(DiscoveredCollection {
+            name: json["name"].as_str().unwrap_or_default().to_string(),
+            package: package.to_str().unwrap_or_default().to_string(),
+            collection: collection_path.to_str().unwrap_or_default().to_string(),
+        })
+    }
+
+    pub fn find_collections(&self, node_modules_dirs: &[PathBuf]) -> Vec<DiscoveredCollection> {
+        let mut result: Vec<DiscoveredCollection> = vec![];
+
+        for dir in node_modules_dirs {
+            for package_dir in Self::get_sub_dirs(dir) {
+                let is_scope = package_dir
+                    .file_name()
+                    .unwrap_or_default()
+                    .to_str()
+                    .unwrap_or_default()
+                    .starts_with('@');
+
+                let package_dirs = if is_scope {
+                    Self::get_sub_dirs(&package_dir)
+                } else {
+                    vec![package_dir]
+                };
+
+                result.extend(package_dirs.iter().filter_map(|p| Self::read_collection(p)));
+            }
+        }
+
+        result.sort_by_cached_key(|c| c.name.clone());
+        result.dedup_by(|a, b| a.collection == b.collection);
+        result
+    }
+
+    pub fn discover(&self) -> DiscoveryResult {
+        let bin_dirs = self.get_bin_dirs();
+        let node_modules_dirs = self.get_node_modules_dirs(&bin_dirs);
+
+        DiscoveryResult {
+            node_binaries: self.find_node_binaries(&bin_dirs),
+            runners: self.find_runners(&bin_dirs),
+            collections: self.find_collections(&node_modules_dirs),
+        }
+    }
+
+    fn dedup(paths: Vec<PathBuf>) -> Vec<PathBuf> {
+        let mut result: Vec<PathBuf> = vec![];
+
+        for path in paths {
+            let canonical = path.canonicalize().unwrap_or(path.clone());
+            if !result
+                .iter()
+                .any(|p| p.canonicalize().unwrap_or(p.clone()) == canonical)
+            {
+                result.push(path);
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::write_tree;
+    use std::fs::{create_dir_all, write};
+
+    fn get_discovery(name: &str) -> (DiscoveryUtils, PathBuf) {
+        let root = write_tree(&format!("discovery/{}", name), &[]);
+
+        let discovery = DiscoveryUtils {
+            home: root.join("home"),
+            cwd: root.join("project"),
+            vars: HashMap::new(),
+            runner_executables: vec![String::from("schematics")],
+        };
+        (discovery, root)
+    }
+
+    fn add_package(node_modules: &Path, name: &str, key: &str) {
+        let dir = node_modules.join(name);
+        create_dir_all(&dir).unwrap();
+        write(
+            dir.join("package.json"),
+            format!(
+                "{{\"name\": \"{}\", \"{}\": \"./collection.json\"}}",
+                name, key
+            ),
+        )
+        .unwrap();
+        write(dir.join("collection.json"), "{}").unwrap();
+    }
+
+    #[test]
+    fn get_bin_dirs_version_managers() {
+        let (discovery, root) = get_discovery("bin_dirs");
+        let nvm = root.join("home/.nvm/versions/node/v20.0.0/bin");
+        let asdf = root.join("home/.asdf/installs/nodejs/18.0.0/bin");
+        create_dir_all(&nvm).unwrap();
+        create_dir_all(&asdf).unwrap();
+
+        let result = discovery.get_bin_dirs();
+
+        assert_eq!(result, vec![nvm, asdf]);
+    }
+
+    #[test]
+    fn get_node_modules_dirs_global_and_project() {
+        let (discovery, root) = get_discovery("node_modules_dirs");
+        let bin = root.join("home/.nvm/versions/node/v20.0.0/bin");
+        let global = root.join("home/.nvm/versions/node/v20.0.0/lib/node_modules");
+        let project = root.join("project/node_modules");
+        create_dir_all(&bin).unwrap();
+        create_dir_all(&global).unwrap();
+        create_dir_all(&project).unwrap();
+
+        let result = discovery.get_node_modules_dirs(&[bin]);
+
+        assert_eq!(result, vec![project, global]);
+    }
+
+    #[test]
+    fn find_collections_success() {
+        let (discovery, root) = get_discovery("collections");
+        let node_modules = root.join("project/node_modules");
+        add_package(&node_modules, "@schematics/angular", "schematics");
+        add_package(&node_modules, "nx-plugin", "generators");
+        add_package(&node_modules, "lodash", "main");
+
+        let result = discovery.find_collections(std::slice::from_ref(&node_modules));
+
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].name, "@schematics/angular");
+        assert_eq!(
+            result[0].collection,
+            node_modules
+                .join("@schematics/angular/collection.json")
+                .to_str()
+                .unwrap()
+        );
+        assert_eq!(result[1].name, "nx-plugin");
+    }
+}
@@ -0,0 +1,94 @@
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug)]
+pub enum AppError {
+    /// A file or directory could not be read or written.
+    Io(PathBuf, std::io::Error),
+    /// A JSON document is malformed or doesn't have the expected shape.
+    Json(PathBuf, serde_json::Error),
+    /// A TOML document is malformed or doesn't have the expected shape.
+    Toml(PathBuf, toml::de::Error),
+    /// Data could not be serialized to TOML.
+    TomlSerialize(toml::ser::Error),
+    /// A package, collection or schematic could not be resolved.
+    NotFound(String),
+    /// An external command failed, timed out or printed something unexpected.
+    Command(String, String),
+    /// A profile can't be loaded, e.g.: it extends itself.
+    Profile(PathBuf, String),
+}
+
+pub type Result<T> = std::result::Result<T, AppError>;
+
+impl AppError {
+    pub fn io(path: &Path, err: std::io::Error) -> Self {
+        AppError::Io(path.to_path_buf(), err)
+    }
+
+    pub fn json(path: &Path, err: serde_json::Error) -> Self {
+        AppError::Json(path.to_path_buf(), err)
+    }
+
+    pub fn toml(path: &Path, err: toml::de::Error) -> Self {
+        AppError::Toml(path.to_path_buf(), err)
+    }
+}
+
+impl Display for AppError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AppError::Io(path, err) => write!(f, "Could not access {}! {}", path.display(), err),
+            AppError::Json(path, err) => write!(f, "Invalid JSON in {}! {}", path.display(), err),
+            AppError::Toml(path, err) => write!(f, "Invalid TOML in {}! {}", path.display(), err),
+            AppError::TomlSerialize(err) => write!(f, "Could not serialize TOML! {}", err),
+            AppError::NotFound(what) => write!(f, "Could not resolve {}!", what),
+            AppError::Command(command, reason) => {
+                write!(f, "Command `{}` failed! {}", command, reason)
+            }
+            AppError::Profile(path, reason) => {
+                write!(f, "Invalid profile {}! {}", path.display(), reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for AppError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            AppError::Io(_, err) => Some(err),
+            AppError::Json(_, err) => Some(err),
+            AppError::Toml(_, err) => Some(err),
+            AppError::TomlSerialize(err) => Some(err),
+            AppError::NotFound(_) | AppError::Command(_, _) | AppError::Profile(_, _) => None,
+        }
+    }
+}
+
+impl From<toml::ser::Error> for AppError {
+    fn from(err: toml::ser::Error) -> Self {
+        AppError::TomlSerialize(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_io() {
+        let err = AppError::io(
+            Path::new("/foo/bar.json"),
+            std::io::Error::new(std::io::ErrorKind::NotFound, "missing"),
+        );
+
+        assert_eq!(err.to_string(), "Could not access /foo/bar.json! missing");
+    }
+
+    #[test]
+    fn display_not_found() {
+        let err = AppError::NotFound(String::from("package foo"));
+
+        assert_eq!(err.to_string(), "Could not resolve package foo!");
+    }
+}
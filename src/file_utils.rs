@@ -1,28 +1,55 @@
 use std::{
+    collections::HashMap,
     fs::{self, read_dir},
     io,
-    path::PathBuf,
+    path::{Path, PathBuf},
+    time::SystemTime,
 };
 
+use regex::Regex;
+use serde::de::DeserializeOwned;
+
+use crate::error::{AppError, Result};
+
 pub struct FileUtils {}
 
+/// Polls the modification time of a set of files.
+#[derive(Default, Debug)]
+pub struct FileWatcher {
+    files: HashMap<PathBuf, Option<SystemTime>>,
+}
+
 impl FileUtils {
-    pub fn read_fs_entries(path: &PathBuf, dirs: bool) -> Vec<PathBuf> {
-        read_dir(path)
-            .unwrap()
-            .filter_map(|f| {
-                let entry = f.unwrap();
-                if dirs {
-                    if !entry.file_type().unwrap().is_dir() {
-                        return None;
-                    }
-                } else if !entry.file_type().unwrap().is_file() {
-                    return None;
-                }
+    pub fn read_fs_entries(path: &PathBuf, dirs: bool) -> Result<Vec<PathBuf>> {
+        let mut result: Vec<PathBuf> = vec![];
 
-                Some(entry.path())
-            })
-            .collect()
+        for entry in read_dir(path).map_err(|e| AppError::io(path, e))? {
+            let entry = entry.map_err(|e| AppError::io(path, e))?;
+            let file_type = entry
+                .file_type()
+                .map_err(|e| AppError::io(&entry.path(), e))?;
+
+            if (dirs && file_type.is_dir()) || (!dirs && file_type.is_file()) {
+                result.push(entry.path());
+            }
+        }
+        Ok(result)
+    }
+
+    pub fn read_str(path: &Path) -> Result<String> {
+        fs::read_to_string(path).map_err(|e| AppError::io(path, e))
+    }
+
+    pub fn read_json<T: DeserializeOwned>(path: &Path) -> Result<T> {
+        serde_json::from_str(&Self::read_str(path)?).map_err(|e| AppError::json(path, e))
+    }
+
+    pub fn read_toml<T: DeserializeOwned>(path: &Path) -> Result<T> {
+        toml::from_str(&Self::read_str(path)?).map_err(|e| AppError::toml(path, e))
+    }
+
+    pub fn write_str(path: &Path, contents: &str) -> Result<()> {
+        fs::write(path, contents).map_err(|e| AppError::io(path, e))
     }
 
     pub fn read_fs_entries_recursive(
@@ -48,4 +75,396 @@ impl FileUtils {
         }
         Ok(result)
     }
+
+    /// Regex for a glob like `src/**/*.{ts,js}`: `**/` spans any number of directories, `*`
+    /// and `?` stay within one. `None` if the braces are unbalanced.
+    pub fn glob_to_regex(pattern: &str) -> Option<Regex> {
+        let mut result = String::from("^");
+        let mut chars = pattern.trim_start_matches("./").chars().peekable();
+        let mut in_braces = false;
+
+        while let Some(c) = chars.next() {
+            match c {
+                '*' if chars.peek() == Some(&'*') => {
+                    chars.next();
+                    if chars.next_if_eq(&'/').is_some() {
+                        result.push_str("(?:.*/)?");
+                    } else {
+                        result.push_str(".*");
+                    }
+                }
+                '*' => result.push_str("[^/]*"),
+                '?' => result.push_str("[^/]"),
+                '{' => {
+                    in_braces = true;
+                    result.push_str("(?:");
+                }
+                '}' => {
+                    in_braces = false;
+                    result.push(')');
+                }
+                ',' if in_braces => result.push('|'),
+                c => result.push_str(&regex::escape(&c.to_string())),
+            }
+        }
+        result.push('$');
+        Regex::new(&result).ok()
+    }
+
+    /// Directory the matches of a glob are under, e.g.: `src/app` for `src/app/**/*.ts`.
+    fn get_glob_base(pattern: &str) -> PathBuf {
+        let parts: Vec<&str> = pattern.trim_start_matches("./").split('/').collect();
+
+        parts[..parts.len() - 1]
+            .iter()
+            .take_while(|part| !part.contains(['*', '?', '{']))
+            .collect()
+    }
+
+    /// Paths under `root` matching any of the glob `patterns` and none of the ones prefixed by
+    /// `!`, relative to `root` and sorted. Entries named in `skip` and the ones ignored by the
+    /// `.gitignore` files aren't searched. The walk starts from the literal directories the
+    /// patterns begin with and doesn't follow symlinks.
+    pub fn find_glob(root: &Path, patterns: &[&str], dirs: bool, skip: &[String]) -> Vec<String> {
+        let (excludes, includes): (Vec<&str>, Vec<&str>) =
+            patterns.iter().partition(|p| p.starts_with('!'));
+        let include_res: Option<Vec<Regex>> =
+            includes.iter().map(|p| Self::glob_to_regex(p)).collect();
+        let exclude_res: Option<Vec<Regex>> = excludes
+            .iter()
+            .map(|p| Self::glob_to_regex(&p[1..]))
+            .collect();
+        let (include_res, exclude_res) = match (include_res, exclude_res) {
+            (Some(include_res), Some(exclude_res)) => (include_res, exclude_res),
+            _ => return vec![],
+        };
+
+        let mut bases: Vec<PathBuf> = includes.iter().map(|p| Self::get_glob_base(p)).collect();
+        bases.sort();
+        bases.dedup();
+        let bases: Vec<&PathBuf> = bases
+            .iter()
+            .filter(|base| {
+                !bases
+                    .iter()
+                    .any(|other| other != *base && base.starts_with(other))
+            })
+            .collect();
+
+        let mut result: Vec<String> = vec![];
+        let mut pending: Vec<(PathBuf, GitIgnore)> = vec![];
+
+        for base in bases {
+            let mut dir = root.to_path_buf();
+            let mut ignore = GitIgnore::default().read(root, "");
+
+            for part in base.iter() {
+                dir.push(part);
+                let relative = dir.strip_prefix(root).unwrap_or(&dir).to_string_lossy();
+                ignore = ignore.read(&dir, &relative.replace('\\', "/"));
+            }
+            pending.push((dir, ignore));
+        }
+
+        while let Some((dir, ignore)) = pending.pop() {
+            let entries = match read_dir(&dir) {
+                Ok(entries) => entries,
+                Err(_) => continue,
+            };
+            for entry in entries.flatten() {
+                let path = entry.path();
+                let is_symlink = entry.file_type().is_ok_and(|t| t.is_symlink());
+                let is_walked = entry.file_type().is_ok_and(|t| t.is_dir());
+                let is_dir = is_walked || (is_symlink && path.is_dir());
+                if skip.iter().any(|s| entry.file_name() == s.as_str()) {
+                    continue;
+                }
+                let relative = path
+                    .strip_prefix(root)
+                    .unwrap_or(&path)
+                    .to_string_lossy()
+                    .replace('\\', "/");
+                if ignore.is_ignored(&relative, is_dir) {
+                    continue;
+                }
+
+                if is_dir == dirs
+                    && include_res.iter().any(|re| re.is_match(&relative))
+                    && !exclude_res.iter().any(|re| re.is_match(&relative))
+                {
+                    result.push(relative.clone());
+                }
+                if is_walked {
+                    let ignore = ignore.read(&path, &relative);
+                    pending.push((path, ignore));
+                }
+            }
+        }
+        result.sort();
+        result
+    }
+}
+
+/// Rules of the `.gitignore` files met while walking a directory tree, they are matched
+/// against paths relative to its root.
+#[derive(Default, Debug, Clone)]
+pub struct GitIgnore {
+    rules: Vec<IgnoreRule>,
+}
+
+#[derive(Debug, Clone)]
+struct IgnoreRule {
+    pattern: Regex,
+    negated: bool,
+    dirs_only: bool,
+}
+
+impl GitIgnore {
+    /// These rules and the ones of the `.gitignore` in `dir`, `base` is its relative path.
+    pub fn read(&self, dir: &Path, base: &str) -> GitIgnore {
+        let mut result = self.clone();
+        if let Ok(text) = fs::read_to_string(dir.join(".gitignore")) {
+            result.add(&text, base);
+        }
+        result
+    }
+
+    pub fn add(&mut self, text: &str, base: &str) {
+        for line in text.lines().map(str::trim_end) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (negated, line) = match line.strip_prefix('!') {
+                Some(line) => (true, line),
+                None => (false, line),
+            };
+            let (dirs_only, line) = match line.strip_suffix('/') {
+                Some(line) => (true, line),
+                None => (false, line),
+            };
+            // Patterns with a slash are relative to the `.gitignore`, the others match at any depth.
+            let glob = match line.strip_prefix('/') {
+                Some(line) => line.to_string(),
+                None if line.contains('/') => line.to_string(),
+                None => format!("**/{}", line),
+            };
+            let glob = match base.is_empty() {
+                true => glob,
+                false => format!("{}/{}", base, glob),
+            };
+
+            if let Some(pattern) = FileUtils::glob_to_regex(&glob) {
+                self.rules.push(IgnoreRule {
+                    pattern,
+                    negated,
+                    dirs_only,
+                });
+            }
+        }
+    }
+
+    /// Whether a relative path is ignored, the last matching rule wins.
+    pub fn is_ignored(&self, path: &str, is_dir: bool) -> bool {
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| (is_dir || !rule.dirs_only) && rule.pattern.is_match(path))
+            .is_some_and(|rule| !rule.negated)
+    }
+}
+
+impl FileWatcher {
+    pub fn new() -> Self {
+        FileWatcher {
+            files: HashMap::new(),
+        }
+    }
+
+    fn modified(path: &Path) -> Option<SystemTime> {
+        fs::metadata(path).and_then(|m| m.modified()).ok()
+    }
+
+    pub fn watch(&mut self, path: &Path) {
+        self.files.insert(path.to_path_buf(), Self::modified(path));
+    }
+
+    pub fn clear(&mut self) {
+        self.files.clear();
+    }
+
+    pub fn is_watched(&self, path: &Path) -> bool {
+        self.files.contains_key(path)
+    }
+
+    /// Returns the files modified since the last call.
+    pub fn changed(&mut self) -> Vec<PathBuf> {
+        let mut result: Vec<PathBuf> = vec![];
+
+        for (path, last_modified) in self.files.iter_mut() {
+            let modified = Self::modified(path);
+            if modified != *last_modified {
+                *last_modified = modified;
+                result.push(path.clone());
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::write_tree;
+    use std::time::Duration;
+
+    fn get_file(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join("schematics-gui-watcher");
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        fs::write(&path, "{}").unwrap();
+        path
+    }
+
+    #[test]
+    fn read_fs_entries_missing_dir() {
+        let result = FileUtils::read_fs_entries(&PathBuf::from("/no/such/dir"), true);
+
+        assert!(matches!(result, Err(AppError::Io(_, _))));
+    }
+
+    #[test]
+    fn read_json_invalid() {
+        let path = get_file("invalid.json");
+        fs::write(&path, "{").unwrap();
+
+        let result = FileUtils::read_json::<serde_json::Value>(&path);
+
+        assert!(matches!(result, Err(AppError::Json(_, _))));
+    }
+
+    #[test]
+    fn glob_to_regex_success() {
+        let re = FileUtils::glob_to_regex("src/**/*.{service,model}.ts").unwrap();
+
+        assert!(re.is_match("src/foo.service.ts"));
+        assert!(re.is_match("src/app/users/user.model.ts"));
+        assert!(!re.is_match("src/app/user.component.ts"));
+        assert!(!re.is_match("lib/foo.service.ts"));
+        assert!(FileUtils::glob_to_regex("src/*.ts")
+            .unwrap()
+            .is_match("src/main.ts"));
+        assert!(!FileUtils::glob_to_regex("src/*.ts")
+            .unwrap()
+            .is_match("src/app/main.ts"));
+        assert!(FileUtils::glob_to_regex("src/[id]/*.ts")
+            .unwrap()
+            .is_match("src/[id]/page.ts"));
+    }
+
+    #[test]
+    fn find_glob_success() {
+        let root = write_tree(
+            "glob",
+            &[
+                ("src/main.ts", ""),
+                ("src/app/users/users.service.ts", ""),
+                ("src/app/users/users.service.spec.ts", ""),
+                ("src/dist/users.service.ts", ""),
+                ("node_modules/foo/foo.service.ts", ""),
+                ("src/.gitignore", "dist/\n"),
+            ],
+        );
+        let skip = vec![String::from("node_modules")];
+
+        assert_eq!(
+            FileUtils::find_glob(&root, &["**/*.service.ts"], false, &skip),
+            vec!["src/app/users/users.service.ts"]
+        );
+        assert_eq!(
+            FileUtils::find_glob(&root, &["src/**/*.ts", "!**/*.spec.ts"], false, &skip),
+            vec!["src/app/users/users.service.ts", "src/main.ts"]
+        );
+        assert_eq!(
+            FileUtils::find_glob(&root, &["src/*"], true, &skip),
+            vec!["src/app"]
+        );
+        assert_eq!(
+            FileUtils::find_glob(&root, &["**/foo.service.ts"], false, &[]),
+            vec!["node_modules/foo/foo.service.ts"]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn find_glob_symlink_loop() {
+        let root = write_tree("glob-loop", &[("src/app/main.ts", "")]);
+        std::os::unix::fs::symlink(root.join("src"), root.join("src/app/loop")).unwrap();
+
+        assert_eq!(
+            FileUtils::find_glob(&root, &["src/**/*.ts"], false, &[]),
+            vec!["src/app/main.ts"]
+        );
+        assert_eq!(
+            FileUtils::find_glob(&root, &["src/**"], true, &[]),
+            vec!["src/app", "src/app/loop"]
+        );
+    }
+
+    #[test]
+    fn get_glob_base_success() {
+        assert_eq!(
+            FileUtils::get_glob_base("./src/app/**/*.ts"),
+            PathBuf::from("src/app")
+        );
+        assert_eq!(
+            FileUtils::get_glob_base("src/main.ts"),
+            PathBuf::from("src")
+        );
+        assert_eq!(FileUtils::get_glob_base("**/*.ts"), PathBuf::new());
+        assert_eq!(FileUtils::get_glob_base("{src,lib}/*.ts"), PathBuf::new());
+        assert_eq!(
+            FileUtils::get_glob_base("src/[id]/*.ts"),
+            PathBuf::from("src/[id]")
+        );
+    }
+
+    #[test]
+    fn git_ignore_rules() {
+        let mut ignore = GitIgnore::default();
+        ignore.add("# build output\n*.log\n/dist\nbuild/\n!keep.log\n", "");
+        ignore.add("tmp", "libs/ui");
+
+        assert!(ignore.is_ignored("a/b/error.log", false));
+        assert!(!ignore.is_ignored("keep.log", false));
+        assert!(ignore.is_ignored("dist", true));
+        assert!(!ignore.is_ignored("src/dist", true));
+        assert!(ignore.is_ignored("src/build", true));
+        assert!(!ignore.is_ignored("src/build", false));
+        assert!(ignore.is_ignored("libs/ui/src/tmp", true));
+        assert!(!ignore.is_ignored("libs/api/tmp", true));
+    }
+
+    #[test]
+    fn changed_none() {
+        let path = get_file("unchanged.json");
+        let mut watcher = FileWatcher::new();
+
+        watcher.watch(&path);
+
+        assert!(watcher.changed().is_empty());
+    }
+
+    #[test]
+    fn changed_modified_once() {
+        let path = get_file("modified.json");
+        let mut watcher = FileWatcher::new();
+
+        watcher.watch(&path);
+        let file = fs::File::options().write(true).open(&path).unwrap();
+        file.set_modified(SystemTime::now() + Duration::from_secs(10))
+            .unwrap();
+
+        assert_eq!(watcher.changed(), vec![path]);
+        assert!(watcher.changed().is_empty());
+    }
 }
@@ -1,8 +1,9 @@
 use colors_transform::{AlphaColor, Color, Hsl, Rgb};
 use relm4::gtk::gdk::RGBA;
-use relm4::gtk::glib::{DateTime, GString, TimeZone};
+use relm4::gtk::glib::{self, DateTime, GString, TimeZone};
 use relm4::gtk::prelude::{
-    Cast, ComboBoxExtManual, EntryBufferExtManual, FileChooserExtManual, FileExt, GtkWindowExt, IsA,
+    Cast, ComboBoxExtManual, EntryBufferExtManual, FileChooserExtManual, FileExt, FilterExt,
+    GtkApplicationExt, GtkWindowExt, IsA, ListModelExt, PopoverExt, SorterExt,
 };
 use relm4::gtk::{
     traits::{
@@ -10,29 +11,52 @@ use relm4::gtk::{
         FileChooserExt, OrientableExt, RangeExt, ScaleExt, SelectionModelExt, TextBufferExt,
         TextViewExt, ToggleButtonExt, WidgetExt,
     },
-    Align, Box, Button, Dialog, Entry, EntryBuffer, FileChooserAction, FileChooserDialog,
-    FileFilter, Label, ResponseType, SpinButtonUpdatePolicy,
+    Align, Box, Button, ButtonsType, Dialog, DialogFlags, Entry, EntryBuffer, FileChooserAction,
+    FileChooserDialog, FileFilter, Label, MessageDialog, MessageType, ResponseType,
+    SpinButtonUpdatePolicy,
 };
 use relm4::gtk::{
-    Adjustment, ApplicationWindow, Calendar, CheckButton, ColorButton, ColorChooserDialog,
-    ComboBoxText, DropDown, EntryIconPosition, Justification, LinkButton, ListView, MultiSelection,
-    Orientation, Scale, SpinButton, Switch, TextBuffer, TextView, ToggleButton, Window, WrapMode,
+    Adjustment, ApplicationWindow, Calendar, CheckButton, ClosureExpression, ColorButton,
+    ColorChooserDialog, ComboBoxText, CustomFilter, CustomSorter, DropDown, EntryIconPosition,
+    Expression, FilterChange, FilterListModel, Justification, LinkButton, ListItem, ListView,
+    MenuButton, MultiSelection, Orientation, Popover, Scale, ScrolledWindow, SearchEntry,
+    SelectionModel, SignalListItemFactory, SingleSelection, SortListModel, SorterChange,
+    SpinButton, Stack, StringList, StringObject, Switch, TextBuffer, TextView, ToggleButton,
+    Widget, Window, WrapMode,
 };
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::rc::Rc;
+use std::sync::mpsc::{self, TryRecvError};
+use std::thread;
+use std::time::Duration;
 
 use relm4::gtk::gio::File;
 use relm4::typed_list_view::TypedListView;
+use sourceview5::prelude::ViewExt;
 
+use crate::command_builder::{InputType, Param};
+use crate::error::AppError;
+use crate::json_validator::JsonValidator;
+use crate::profile_utils::ProfileDrift;
 use crate::schema_parsing::{
-    ChoiceEntry, ColorEntry, ColorEntryFormat, CurrentValuePosType, DateEntry, DateEntryType,
-    FsEntry, IconPositionType, IntOrFloat, JustificationType, MenuEntry, NumericEntry,
-    NumericValueType, OrientationType, Primitive, TextEntry, TimeInput,
+    ChoiceEntry, ColorEntry, ColorEntryFormat, CommandSource, CurrentValuePosType, DateEntry,
+    DateEntryType, FsEntry, IconPositionType, IntOrFloat, JustificationType, MenuEntry,
+    NumericEntry, NumericValueType, OrientationType, Primitive, TextEntry, TimeInput,
 };
+use crate::search_utils::SearchUtils;
+use crate::source_utils::SourceUtils;
 use crate::string_list_item::StringListItem;
-use crate::traits::WidgetUtils;
+use crate::traits::{JsonBuffer, WidgetUtils};
+use crate::value_extractor::ValueExtractor;
+use crate::value_loader::ValueLoader;
 
 #[derive(Debug)]
 pub struct FormUtils;
 
+impl JsonBuffer for FormUtils {}
+
 #[derive(Debug)]
 pub struct FormValue<'l> {
     pub name: &'l str,
@@ -157,20 +181,7 @@ impl FormUtils {
     }
 
     fn get_digits(options: &NumericEntry, default: &Option<Primitive>) -> i32 {
-        if options.value_type == NumericValueType::Float {
-            if default.is_some() {
-                let dstr: String = default.clone().unwrap().into();
-                let has_decimals = dstr.contains('.');
-                if has_decimals {
-                    return dstr.split('.').last().unwrap().len() as i32;
-                } else {
-                    return 0;
-                }
-            }
-            options.clone().precision.into()
-        } else {
-            0
-        }
+        options.get_digits(default)
     }
 
     pub fn browse_button_with_entry(&self, input: &Entry, button: &Button) -> Box {
@@ -240,6 +251,9 @@ impl FormUtils {
 
         let slider = Scale::new(orientation, Some(&adjustment));
         slider.set_css_classes(&["slider"]);
+        if opts.value_type == NumericValueType::Int {
+            slider.add_css_class("integer_input");
+        }
         slider.set_widget_name(name);
         let pos_type = opts.show_current.clone().into();
 
@@ -534,12 +548,14 @@ impl FormUtils {
         let dialog = FileChooserDialog::new(title, Some(parent), action, &[]);
         let filter = FileFilter::new();
 
-        if opts.current_folder.is_some() {
-            let folder = File::for_path(opts.current_folder.unwrap());
-            match dialog.set_current_folder(Some(&folder)) {
-                Ok(b) => b,
-                Err(e) => panic!("{}", e),
-            };
+        if let Some(current_folder) = opts.current_folder {
+            let folder = File::for_path(&current_folder);
+            if let Err(err) = dialog.set_current_folder(Some(&folder)) {
+                self.error_dialog(&AppError::io(
+                    Path::new(&current_folder),
+                    std::io::Error::new(std::io::ErrorKind::Other, err.to_string()),
+                ));
+            }
         }
 
         if opts.default_name.is_some() {
@@ -562,6 +578,159 @@ impl FormUtils {
         dialog
     }
 
+    pub fn error_dialog(&self, error: &AppError) {
+        let window = relm4::main_application().active_window();
+        let dialog = MessageDialog::new(
+            window.as_ref(),
+            DialogFlags::all(),
+            MessageType::Error,
+            ButtonsType::Close,
+            "Oops.. an error has occured!",
+        );
+        dialog.set_secondary_text(Some(&error.to_string()));
+        dialog.connect_response(|dialog, _| dialog.close());
+        dialog.show();
+    }
+
+    /// Report of a profile that doesn't match the schema anymore. Each unknown option can be
+    /// kept, removed or moved to one of the `options`, `on_migrate` gets the ones to change.
+    pub fn profile_drift_dialog(
+        &self,
+        drift: &ProfileDrift,
+        options: &[String],
+        on_migrate: impl Fn(Vec<(String, Option<String>)>) + 'static,
+    ) {
+        let window = relm4::main_application().active_window();
+        let dialog = Dialog::new();
+        dialog.set_title(Some("The schema has changed"));
+        dialog.set_modal(true);
+        dialog.set_transient_for(window.as_ref());
+        dialog.set_destroy_with_parent(true);
+        dialog.set_css_classes(&["settings_dialog", "drift_dialog"]);
+
+        for (label, response) in [
+            ("Migrate", ResponseType::Apply),
+            ("Cancel", ResponseType::Cancel),
+        ] {
+            let button = dialog.add_button(label, response);
+            button.add_css_class("button");
+            button.add_css_class("action");
+        }
+
+        let content = dialog.content_area();
+        content.add_css_class("dialog_container");
+        let report = self.label(&drift.to_string(), "drift_report", None, None);
+        report.set_wrap(true);
+        content.append(&report);
+
+        let mut choices = vec![String::from("(keep)"), String::from("(remove)")];
+        choices.extend(options.iter().cloned());
+        let mut rows: Vec<(String, DropDown)> = vec![];
+
+        for (key, suggestion) in &drift.unknown {
+            let row = Box::new(Orientation::Horizontal, 5);
+            row.set_css_classes(&["drift_row"]);
+            let label = Label::new(Some(key));
+            label.set_hexpand(true);
+            label.set_xalign(0.0);
+            let target = self.dropdown(key, &choices, None);
+            let selected = suggestion
+                .as_ref()
+                .and_then(|s| choices.iter().position(|c| c == s))
+                .unwrap_or_default();
+            target.set_selected(selected as u32);
+
+            row.append(&label);
+            row.append(&target);
+            content.append(&row);
+            rows.push((key.clone(), target));
+        }
+
+        dialog.connect_response(move |dialog, response| {
+            if response == ResponseType::Apply {
+                let mapping = rows
+                    .iter()
+                    .filter_map(|(key, target)| match target.selected() {
+                        0 => None,
+                        1 => Some((key.clone(), None)),
+                        i => Some((key.clone(), choices.get(i as usize).cloned())),
+                    })
+                    .collect();
+                on_migrate(mapping);
+            }
+            dialog.close();
+        });
+        dialog.show();
+    }
+
+    /// Asks the values of the `${prompt:<label>}` placeholders of a profile, `None` if the
+    /// dialog is cancelled.
+    pub fn prompt_dialog(
+        &self,
+        prompts: &[String],
+        on_answer: impl Fn(Option<HashMap<String, String>>) + 'static,
+    ) {
+        let window = relm4::main_application().active_window();
+        let dialog = Dialog::new();
+        dialog.set_title(Some("Profile values"));
+        dialog.set_modal(true);
+        dialog.set_transient_for(window.as_ref());
+        dialog.set_destroy_with_parent(true);
+        dialog.set_css_classes(&["settings_dialog", "prompt_dialog"]);
+
+        for (label, response) in [("OK", ResponseType::Ok), ("Cancel", ResponseType::Cancel)] {
+            let button = dialog.add_button(label, response);
+            button.add_css_class("button");
+            button.add_css_class("action");
+        }
+
+        let content = dialog.content_area();
+        content.add_css_class("dialog_container");
+        let mut rows: Vec<(String, Entry)> = vec![];
+
+        for prompt in prompts {
+            let row = Box::new(Orientation::Horizontal, 5);
+            row.set_css_classes(&["prompt_row"]);
+            let label = Label::new(Some(prompt));
+            label.set_hexpand(true);
+            label.set_xalign(0.0);
+            let entry = Entry::new();
+            entry.set_hexpand(true);
+            entry.set_activates_default(true);
+
+            row.append(&label);
+            row.append(&entry);
+            content.append(&row);
+            rows.push((prompt.clone(), entry));
+        }
+        dialog.set_default_response(ResponseType::Ok);
+
+        dialog.connect_response(move |dialog, response| {
+            let answers = (response == ResponseType::Ok).then(|| {
+                rows.iter()
+                    .map(|(prompt, entry)| (prompt.clone(), entry.text().to_string()))
+                    .collect()
+            });
+            on_answer(answers);
+            dialog.close();
+        });
+        dialog.show();
+    }
+
+    /// Note under an option of a profile extending others, with an optional action.
+    pub fn inheritance_bar(&self, text: &str, action: Option<&Button>) -> Box {
+        let bar = Box::new(Orientation::Horizontal, 5);
+        bar.set_css_classes(&["inheritance_bar"]);
+        let label = self.label(text, "", None, None);
+        label.set_hexpand(true);
+        bar.append(&label);
+
+        if let Some(action) = action {
+            bar.append(action);
+        }
+        bar
+    }
+
     pub fn browse_button(&self, name: &str) -> Button {
         let button = Button::default();
         button.set_css_classes(&["button", "browse_button"]);
@@ -902,7 +1071,7 @@ impl FormUtils {
         let hour = self.numeric_input(
             name,
             Some(num_opts.clone()),
-            Some(Primitive::Int(default.0)),
+            Some(Primitive::Int(default.0.into())),
         );
         let minute = self.numeric_input(
             name,
@@ -910,7 +1079,7 @@ impl FormUtils {
                 max: IntOrFloat::Int(60),
                 ..num_opts.clone()
             }),
-            Some(Primitive::Int(default.1)),
+            Some(Primitive::Int(default.1.into())),
         );
         let seconds = self.numeric_input(
             name,
@@ -918,7 +1087,7 @@ impl FormUtils {
                 max: IntOrFloat::Int(60),
                 ..num_opts
             }),
-            Some(Primitive::Int(default.2)),
+            Some(Primitive::Int(default.2.into())),
         );
 
         container.append(&hour);
@@ -945,6 +1114,9 @@ impl FormUtils {
         number_input.set_max_width_chars(10);
         number_input.set_widget_name(name);
         number_input.set_css_classes(&["numeric_input"]);
+        if opts.value_type == NumericValueType::Int {
+            number_input.add_css_class("integer_input");
+        }
         number_input.set_numeric(true);
         number_input.set_wrap(opts.wrap);
         number_input.set_snap_to_ticks(true);
@@ -988,14 +1160,7 @@ impl FormUtils {
         list.view
     }
 
-    pub fn dropdown(
-        &self,
-        name: &str,
-        items: &[String],
-        options: Option<MenuEntry>,
-        default: Option<Primitive>,
-    ) -> DropDown {
-        let opts = options.unwrap_or_default();
+    pub fn dropdown(&self, name: &str, items: &[String], default: Option<Primitive>) -> DropDown {
         let dropdown = DropDown::from_strings(
             items
                 .iter()
@@ -1006,11 +1171,6 @@ impl FormUtils {
 
         dropdown.set_widget_name(name);
         dropdown.set_css_classes(&["dropdown"]);
-
-        if opts.searchable {
-            dropdown.set_enable_search(true);
-        }
-
         dropdown.set_selected(Self::get_menu_default(items, default));
 
         dropdown
@@ -1020,7 +1180,7 @@ impl FormUtils {
         &self,
         name: &str,
         items: &[String],
-        // TODO: Implement searchable and orientation options if it makes sense.
+        // TODO: Implement orientation option if it makes sense.
         _options: Option<MenuEntry>,
         default: Option<Primitive>,
     ) -> ComboBoxText {
@@ -1036,4 +1196,582 @@ impl FormUtils {
         combo.set_css_classes(&["combo"]);
         combo
     }
+
+    /// Dropdown whose button opens a fuzzy searchable list of the items.
+    pub fn searchable_dropdown(
+        &self,
+        name: &str,
+        items: &[String],
+        default: Option<Primitive>,
+    ) -> Box {
+        let container = Box::new(Orientation::Horizontal, 0);
+        container.set_widget_name(name);
+        container.set_css_classes(&["searchable_dropdown_container"]);
+
+        let selected = items
+            .get(Self::get_menu_default(items, default) as usize)
+            .cloned()
+            .unwrap_or_default();
+        let button = self.search_button(items, |button, value| button.set_label(value));
+        button.set_label(&selected);
+        button.set_hexpand(true);
+        button.set_css_classes(&["dropdown"]);
+
+        container.append(&button);
+        container
+    }
+
+    /// Editable combobox, its button opens a fuzzy searchable list of the items.
+    pub fn searchable_combobox(
+        &self,
+        name: &str,
+        items: &[String],
+        default: Option<Primitive>,
+    ) -> Box {
+        let container = Box::new(Orientation::Horizontal, 0);
+        container.set_widget_name(name);
+        container.set_css_classes(&["searchable_combo_container"]);
+
+        let entry = Entry::new();
+        entry.set_text(&Self::get_string_default(default));
+        entry.set_hexpand(true);
+        entry.set_css_classes(&["text_input"]);
+
+        let target = entry.clone();
+        let button = self.search_button(items, move |_, value| target.set_text(value));
+        button.set_css_classes(&["combo"]);
+
+        container.append(&entry);
+        container.append(&button);
+        container
+    }
+
+    /// Multiselect filtered by the fuzzy query typed above it, the selected items stay visible.
+    pub fn searchable_multiselect(
+        &self,
+        name: &str,
+        items: &[String],
+        default: Option<Primitive>,
+    ) -> Box {
+        let defaults: Vec<String> = default.map(|d| d.into()).unwrap_or_default();
+        let selected: HashSet<String> = defaults
+            .into_iter()
+            .filter(|value| items.contains(value))
+            .collect();
+        let selected = Rc::new(RefCell::new(selected));
+        let (model, set_query) = Self::search_model(items, selected.clone());
+        let selection = MultiSelection::new(Some(model));
+
+        for index in 0..selection.n_items() {
+            let value = selection
+                .item(index)
+                .map(|item| Self::get_list_item_value(&item));
+            if value.is_some_and(|value| selected.borrow().contains(&value)) {
+                selection.select_item(index, false);
+            }
+        }
+        selection.connect_selection_changed(move |selection, position, count| {
+            for index in position..position + count {
+                if let Some(item) = selection.item(index) {
+                    let value = Self::get_list_item_value(&item);
+                    match selection.is_selected(index) {
+                        true => selected.borrow_mut().insert(value),
+                        false => selected.borrow_mut().remove(&value),
+                    };
+                }
+            }
+        });
+
+        let list = Self::search_list_view(&selection);
+        list.set_widget_name(name);
+        list.set_css_classes(&["dropdown"]);
+        list.enables_rubberband();
+
+        let container = self.search_list(&list, set_query);
+        container.set_widget_name(name);
+        container
+    }
+
+    /// Search entry above a scrollable list, typing into it changes the query of the list.
+    fn search_list(&self, list: &ListView, set_query: Rc<dyn Fn(&str)>) -> Box {
+        let container = Box::new(Orientation::Vertical, 5);
+        container.set_css_classes(&["searchable_list_container"]);
+
+        let search = SearchEntry::new();
+        search.set_css_classes(&["search", "text_input"]);
+        search.connect_changed(move |search| set_query(&search.text()));
+
+        let scroll = ScrolledWindow::new();
+        scroll.set_child(Some(list));
+        scroll.set_min_content_height(150);
+        scroll.set_max_content_height(300);
+        scroll.set_propagate_natural_height(true);
+
+        container.append(&search);
+        container.append(&scroll);
+        container
+    }
+
+    /// Button opening a popover with the searchable list of the items, `on_pick` gets the
+    /// activated item.
+    fn search_button(
+        &self,
+        items: &[String],
+        on_pick: impl Fn(&MenuButton, &str) + 'static,
+    ) -> MenuButton {
+        let button = MenuButton::new();
+        let (model, set_query) = Self::search_model(items, Rc::new(RefCell::new(HashSet::new())));
+        let selection = SingleSelection::new(Some(model));
+        selection.set_autoselect(false);
+
+        let list = Self::search_list_view(&selection);
+        list.set_single_click_activate(true);
+        let content = self.search_list(&list, set_query);
+        content.set_width_request(400);
+
+        let popover = Popover::new();
+        popover.set_child(Some(&content));
+        button.set_popover(Some(&popover));
+
+        let search = content.first_child().unwrap();
+        popover.connect_show(move |_| {
+            if let Ok(search) = search.clone().downcast::<SearchEntry>() {
+                search.set_text("");
+                search.grab_focus();
+            }
+        });
+
+        let target = button.clone();
+        list.connect_activate(move |list, position| {
+            let item = list.model().and_then(|model| model.item(position));
+            if let Some(item) = item {
+                on_pick(&target, &Self::get_list_item_value(&item));
+                target.popdown();
+            }
+        });
+        button
+    }
+
+    /// The items matching the query of `set_query`, best first. The `kept` ones are listed
+    /// whatever the query is.
+    fn search_model(
+        items: &[String],
+        kept: Rc<RefCell<HashSet<String>>>,
+    ) -> (SortListModel, Rc<dyn Fn(&str)>) {
+        let strings = StringList::new(&items.iter().map(|i| i.as_str()).collect::<Vec<&str>>());
+        let query = Rc::new(RefCell::new(String::new()));
+
+        let filter_query = query.clone();
+        let filter = CustomFilter::new(move |item| {
+            let value = Self::get_list_item_value(item);
+            kept.borrow().contains(&value)
+                || SearchUtils::fuzzy_score(&value, &filter_query.borrow()).is_some()
+        });
+        let sorter_query = query.clone();
+        let sorter = CustomSorter::new(move |a, b| {
+            let (a, b) = (Self::get_list_item_value(a), Self::get_list_item_value(b));
+            SearchUtils::compare(&a, &b, &sorter_query.borrow()).into()
+        });
+        let model = SortListModel::new(
+            Some(FilterListModel::new(Some(strings), Some(filter.clone()))),
+            Some(sorter.clone()),
+        );
+
+        let set_query = move |text: &str| {
+            query.replace(text.to_string());
+            filter.changed(FilterChange::Different);
+            sorter.changed(SorterChange::Different);
+        };
+        (model, Rc::new(set_query))
+    }
+
+    /// List view creating rows only for the visible items, so long lists stay fast.
+    fn search_list_view(selection: &impl IsA<SelectionModel>) -> ListView {
+        let factory = SignalListItemFactory::new();
+        factory.connect_setup(|_, item| {
+            if let Some(item) = item.downcast_ref::<ListItem>() {
+                let label = Label::new(None);
+                label.set_xalign(0.0);
+                label.set_css_classes(&["selector_item"]);
+                item.set_child(Some(&label));
+            }
+        });
+        factory.connect_bind(|_, item| {
+            if let Some(item) = item.downcast_ref::<ListItem>() {
+                let label = item.child().and_then(|c| c.downcast::<Label>().ok());
+                if let (Some(label), Some(value)) = (label, item.item()) {
+                    label.set_label(&Self::get_list_item_value(&value));
+                }
+            }
+        });
+        ListView::new(Some(selection.clone()), Some(factory))
+    }
+
+    /// Search entry and list view of a searchable list.
+    pub fn get_search_list_parts(container: &Box) -> Option<(SearchEntry, ListView)> {
+        let search = container.first_child()?.downcast::<SearchEntry>().ok()?;
+        let scroll = container.last_child()?.downcast::<ScrolledWindow>().ok()?;
+        let list = scroll.child()?.downcast::<ListView>().ok()?;
+        Some((search, list))
+    }
+
+    /// Searchable list in the popover of a searchable dropdown or combobox.
+    pub fn get_search_button_list(button: &MenuButton) -> Option<Box> {
+        button.popover()?.child()?.downcast::<Box>().ok()
+    }
+
+    /// Menu of the items printed by a command in the cwd, `build` creates the menu of the items.
+    /// The refresh button reruns the command, errors are shown below the menu.
+    pub fn sourced_menu(
+        &self,
+        name: &str,
+        source: &CommandSource,
+        cwd: Option<String>,
+        build: impl Fn(&[String]) -> Widget + 'static,
+    ) -> Box {
+        let container = Box::new(Orientation::Vertical, 5);
+        container.set_widget_name(name);
+        container.set_css_classes(&["source_menu_container"]);
+
+        let row = Box::new(Orientation::Horizontal, 5);
+        let refresh = Button::from_icon_name("view-refresh");
+        refresh.set_css_classes(&["button", "refresh_button"]);
+        refresh.set_tooltip_text(Some(&source.to_string()));
+        row.append(&refresh);
+
+        let error = self.label("", "error", None, Some(vec!["error"]));
+        error.set_wrap(true);
+        error.set_visible(false);
+
+        let hint = self.label(
+            "Click refresh to run the command and load the items.",
+            "hint",
+            None,
+            Some(vec!["source_hint"]),
+        );
+        hint.set_wrap(true);
+        hint.set_visible(false);
+
+        let pending = self.label("", "pending", None, Some(vec!["pending_value"]));
+        pending.set_visible(false);
+
+        container.append(&row);
+        container.append(&error);
+        container.append(&hint);
+        container.append(&pending);
+
+        let cwd = match cwd {
+            Some(cwd) => cwd,
+            None => {
+                Self::set_source_menu(
+                    &row,
+                    build(&[String::from("Select current working directory!")]),
+                );
+                refresh.set_sensitive(false);
+                return container;
+            }
+        };
+        // Schemas come from installed packages, their commands only run when asked to.
+        match SourceUtils::get_cached(source, Path::new(&cwd)) {
+            Some(items) => Self::set_source_menu(&row, build(&items)),
+            None => {
+                Self::set_source_menu(&row, build(&[]));
+                hint.set_visible(true);
+            }
+        }
+
+        let build: Rc<dyn Fn(&[String]) -> Widget> = Rc::new(build);
+        let source = source.clone();
+        refresh.connect_clicked(move |button| {
+            hint.set_visible(false);
+            Self::load_source_items(
+                button,
+                &row,
+                &error,
+                (source.clone(), cwd.clone()),
+                build.clone(),
+            );
+        });
+
+        container
+    }
+
+    /// Runs the command of a sourced menu in the background, polling for its items.
+    fn load_source_items(
+        button: &Button,
+        row: &Box,
+        error: &Label,
+        (source, cwd): (CommandSource, String),
+        build: Rc<dyn Fn(&[String]) -> Widget>,
+    ) {
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let _ = sender.send(SourceUtils::get_items(&source, Path::new(&cwd), true));
+        });
+        button.set_sensitive(false);
+
+        let (button, row, error) = (button.clone(), row.clone(), error.clone());
+        glib::timeout_add_local(Duration::from_millis(100), move || {
+            let result = match receiver.try_recv() {
+                Ok(result) => result,
+                Err(TryRecvError::Empty) => return glib::Continue(true),
+                Err(TryRecvError::Disconnected) => return glib::Continue(false),
+            };
+            match result {
+                Ok(items) => {
+                    Self::set_source_menu(&row, build(&items));
+                    error.set_visible(false);
+                }
+                Err(err) => {
+                    error.set_label(&err.to_string());
+                    error.set_visible(true);
+                }
+            }
+            button.set_sensitive(true);
+            glib::Continue(false)
+        });
+    }
+
+    /// Swaps the menu of a sourced menu, it's the first child of the row. The selected value
+    /// is kept, a value loaded before the items arrived takes precedence.
+    fn set_source_menu(row: &Box, menu: Widget) {
+        let previous = row
+            .first_child()
+            .filter(|w| w.downcast_ref::<Button>().is_none());
+        let selected = previous
+            .as_ref()
+            .and_then(|p| ValueExtractor::new(p).get_name_value())
+            .map(|param| param.value);
+
+        if let Some(previous) = previous {
+            row.remove(&previous);
+        }
+        menu.set_hexpand(true);
+        row.prepend(&menu);
+
+        let container = match row.parent().and_then(|c| c.downcast::<Box>().ok()) {
+            Some(container) => container,
+            None => return,
+        };
+        match Self::get_pending_source_value(&container) {
+            Some(pending) => Self::set_source_menu_value(&container, &pending),
+            None => {
+                if let Some(selected) = selected {
+                    Self::select_source_value(&container, &selected);
+                }
+            }
+        }
+    }
+
+    /// Selects `value` in a sourced menu. It stays pending until an item holding it is loaded.
+    pub fn set_source_menu_value(container: &Box, value: &str) {
+        let selected = Self::select_source_value(container, value);
+
+        if let Some(pending) = container
+            .last_child()
+            .and_then(|w| w.downcast::<Label>().ok())
+        {
+            pending.set_label(if selected { "" } else { value });
+        }
+    }
+
+    fn select_source_value(container: &Box, value: &str) -> bool {
+        let menu = match container.first_child().and_then(|row| row.first_child()) {
+            Some(menu) => menu,
+            None => return false,
+        };
+        ValueLoader::new(&menu).set_value(&toml::Value::String(value.to_string()), "");
+
+        ValueExtractor::new(&menu)
+            .get_name_value()
+            .is_some_and(|param| param.value == value)
+    }
+
+    fn get_pending_source_value(container: &Box) -> Option<String> {
+        container
+            .last_child()
+            .and_then(|w| w.downcast::<Label>().ok())
+            .map(|pending| pending.label().to_string())
+            .filter(|value| !value.is_empty())
+    }
+
+    /// Dropdown showing the titles of `choices` while the selected item holds the value.
+    pub fn labelled_dropdown(
+        &self,
+        name: &str,
+        choices: &[(String, String)],
+        default: Option<Primitive>,
+    ) -> DropDown {
+        let values: Vec<String> = choices.iter().map(|(_, value)| value.clone()).collect();
+        let dropdown =
+            DropDown::from_strings(&values.iter().map(|v| v.as_str()).collect::<Vec<&str>>());
+        let titles: HashMap<String, String> = choices
+            .iter()
+            .map(|(title, value)| (value.clone(), title.clone()))
+            .collect();
+
+        dropdown.set_expression(Some(&ClosureExpression::with_callback(
+            std::iter::empty::<Expression>(),
+            move |args| {
+                let value = args[0]
+                    .get::<StringObject>()
+                    .map(|item| item.string().to_string())
+                    .unwrap_or_default();
+                titles.get(&value).cloned().unwrap_or(value)
+            },
+        )));
+        dropdown.set_widget_name(name);
+        dropdown.set_css_classes(&["dropdown"]);
+
+        let selected = default
+            .map(|d| d.to_string())
+            .and_then(|d| values.iter().position(|v| v == &d))
+            .unwrap_or_default();
+        dropdown.set_selected(selected as u32);
+        dropdown
+    }
+
+    /// Lets the user pick the type of a mixed-type `anyOf`, showing the widget of that type.
+    /// Variants are (title, type, widget), the type is kept as the title of the stack page.
+    pub fn type_switch(
+        &self,
+        name: &str,
+        variants: &[(String, String, Widget)],
+        selected: u32,
+    ) -> Box {
+        let container = Box::new(Orientation::Vertical, 5);
+        container.set_widget_name(name);
+        container.set_css_classes(&["type_switch_container"]);
+
+        let stack = Stack::new();
+        for (index, (_, r#type, widget)) in variants.iter().enumerate() {
+            stack.add_titled(widget, Some(&index.to_string()), r#type);
+        }
+
+        let titles: Vec<&str> = variants.iter().map(|(t, _, _)| t.as_str()).collect();
+        let switcher = DropDown::from_strings(&titles);
+        switcher.set_css_classes(&["dropdown", "type_switcher"]);
+        switcher.set_halign(Align::Start);
+
+        let switcher_stack = stack.clone();
+        switcher.connect_selected_notify(move |switcher| {
+            switcher_stack.set_visible_child_name(&switcher.selected().to_string());
+        });
+        switcher.set_selected(selected);
+        stack.set_visible_child_name(&selected.to_string());
+
+        container.append(&switcher);
+        container.append(&stack);
+        container
+    }
+
+    /// JSON editor for the properties the other widgets can't represent, validated against `schema`.
+    pub fn json_input(
+        &self,
+        name: &str,
+        schema: &serde_json::Value,
+        default: Option<Primitive>,
+    ) -> Box {
+        let container = Box::new(Orientation::Vertical, 5);
+        container.set_widget_name(name);
+        container.set_css_classes(&["json_input_container"]);
+
+        let buffer = Self::get_json_buffer(None);
+        let view = sourceview5::View::with_buffer(&buffer);
+        view.set_css_classes(&["json_input"]);
+        view.set_monospace(true);
+        view.set_show_line_numbers(true);
+        view.set_tab_width(2);
+        view.set_hexpand(true);
+        view.set_height_request(100);
+
+        let error = self.label("", "error", None, Some(vec!["error"]));
+        error.set_wrap(true);
+        error.set_visible(false);
+
+        let schema = schema.clone();
+        let error_label = error.clone();
+        buffer.connect_changed(move |buffer| {
+            let text = buffer.text(&buffer.start_iter(), &buffer.end_iter(), false);
+            let errors = JsonValidator::validate_str(&schema, &text);
+            error_label.set_label(&errors.join("\n"));
+            error_label.set_visible(!errors.is_empty());
+        });
+
+        if let Some(value) = default.and_then(|d| serde_json::to_string_pretty(&d).ok()) {
+            buffer.set_text(&value);
+        }
+
+        container.append(&view);
+        container.append(&error);
+        container
+    }
+
+    /// Key/value table for objects with `additionalProperties`.
+    pub fn map_input(&self, name: &str, default: Option<Primitive>) -> Box {
+        let container = Box::new(Orientation::Vertical, 5);
+        container.set_widget_name(name);
+        container.set_css_classes(&["map_input_container"]);
+
+        let rows = Box::new(Orientation::Vertical, 5);
+        rows.set_css_classes(&["map_rows"]);
+
+        let add = self.action_button("add", Some("list-add"));
+        add.set_tooltip_text(Some("Add an entry"));
+        add.set_halign(Align::Start);
+        let add_rows = rows.clone();
+        add.connect_clicked(move |_| {
+            FormUtils::new().map_row(&add_rows, "", "");
+        });
+
+        if let Some(Primitive::Unknown(value)) = default {
+            let entries =
+                Param::new(String::default(), value.to_string(), InputType::Map).get_map_entries();
+            self.set_map_entries(&rows, &entries);
+        }
+
+        container.append(&rows);
+        container.append(&add);
+        container
+    }
+
+    pub fn set_map_entries(&self, rows: &Box, entries: &[(String, String)]) {
+        while let Some(row) = rows.first_child() {
+            rows.remove(&row);
+        }
+        for (key, value) in entries {
+            self.map_row(rows, key, value);
+        }
+    }
+
+    fn map_row(&self, rows: &Box, key: &str, value: &str) {
+        let row = Box::new(Orientation::Horizontal, 5);
+        row.set_css_classes(&["map_row"]);
+
+        let key_entry = Entry::new();
+        key_entry.set_css_classes(&["text_input"]);
+        key_entry.set_placeholder_text(Some("key"));
+        key_entry.set_text(key);
+
+        let value_entry = Entry::new();
+        value_entry.set_css_classes(&["text_input"]);
+        value_entry.set_placeholder_text(Some("value"));
+        value_entry.set_text(value);
+        value_entry.set_hexpand(true);
+
+        let remove = self.action_button("remove", Some("list-remove"));
+        remove.set_tooltip_text(Some("Remove the entry"));
+        remove.connect_clicked(|button| {
+            if let Some(row) = button.parent() {
+                if let Some(rows) = row.parent().and_then(|p| p.downcast::<Box>().ok()) {
+                    rows.remove(&row);
+                }
+            }
+        });
+
+        row.append(&key_entry);
+        row.append(&value_entry);
+        row.append(&remove);
+        rows.append(&row);
+    }
 }
@@ -0,0 +1,349 @@
+use regex::Regex;
+use serde_json::{Map, Value};
+
+/// Validates values against the subset of JSON Schema used by schematic options.
+///
+/// Unsupported keywords (e.g.: `$ref`, `format`) are ignored, so a value is only rejected when
+/// one of the known rules is broken.
+pub struct JsonValidator {}
+
+impl JsonValidator {
+    /// Error messages prefixed with the path of the offending value, empty if `value` is valid.
+    pub fn validate(schema: &Value, value: &Value) -> Vec<String> {
+        let mut result: Vec<String> = vec![];
+        Self::validate_at(schema, value, "$", &mut result);
+        result
+    }
+
+    /// Same as `validate` for JSON typed by the user, empty text counts as no value.
+    pub fn validate_str(schema: &Value, text: &str) -> Vec<String> {
+        if text.trim().is_empty() {
+            return vec![];
+        }
+        match serde_json::from_str::<Value>(text) {
+            Ok(value) => Self::validate(schema, &value),
+            Err(err) => vec![format!("Invalid JSON: {}", err)],
+        }
+    }
+
+    pub fn is_valid(schema: &Value, value: &Value) -> bool {
+        Self::validate(schema, value).is_empty()
+    }
+
+    fn get_type(value: &Value) -> &'static str {
+        match value {
+            Value::Null => "null",
+            Value::Bool(_) => "boolean",
+            Value::Number(n) if n.is_i64() || n.is_u64() => "integer",
+            Value::Number(_) => "number",
+            Value::String(_) => "string",
+            Value::Array(_) => "array",
+            Value::Object(_) => "object",
+        }
+    }
+
+    fn is_type(value: &Value, expected: &str) -> bool {
+        let actual = Self::get_type(value);
+        actual == expected
+            || (expected == "number" && actual == "integer")
+            || (expected == "integer" && value.as_f64().is_some_and(|n| n.fract() == 0.0))
+    }
+
+    fn validate_at(schema: &Value, value: &Value, path: &str, result: &mut Vec<String>) {
+        let schema = match schema {
+            Value::Object(schema) => schema,
+            Value::Bool(false) => {
+                result.push(format!("{}: no value is allowed here", path));
+                return;
+            }
+            _ => return,
+        };
+
+        if !Self::validate_type(schema, value, path, result) {
+            return;
+        }
+
+        if let Some(allowed) = schema.get("enum").and_then(|e| e.as_array()) {
+            if !allowed.contains(value) {
+                let allowed: Vec<String> = allowed.iter().map(|v| v.to_string()).collect();
+                result.push(format!("{}: must be one of {}", path, allowed.join(", ")));
+            }
+        }
+        if let Some(expected) = schema.get("const") {
+            if expected != value {
+                result.push(format!("{}: must be {}", path, expected));
+            }
+        }
+
+        match value {
+            Value::Number(_) => {
+                Self::validate_number(schema, value.as_f64().unwrap(), path, result)
+            }
+            Value::String(s) => Self::validate_string(schema, s, path, result),
+            Value::Array(items) => Self::validate_array(schema, items, path, result),
+            Value::Object(map) => Self::validate_object(schema, map, path, result),
+            _ => {}
+        }
+
+        Self::validate_combinators(schema, value, path, result);
+    }
+
+    fn validate_type(
+        schema: &Map<String, Value>,
+        value: &Value,
+        path: &str,
+        result: &mut Vec<String>,
+    ) -> bool {
+        let types: Vec<&str> = match schema.get("type") {
+            Some(Value::String(t)) => vec![t.as_str()],
+            Some(Value::Array(types)) => types.iter().filter_map(|t| t.as_str()).collect(),
+            _ => return true,
+        };
+
+        if types.iter().any(|t| Self::is_type(value, t)) {
+            return true;
+        }
+        result.push(format!(
+            "{}: expected {}, got {}",
+            path,
+            types.join(" or "),
+            Self::get_type(value)
+        ));
+        false
+    }
+
+    fn validate_number(
+        schema: &Map<String, Value>,
+        value: f64,
+        path: &str,
+        result: &mut Vec<String>,
+    ) {
+        let get = |key: &str| schema.get(key).and_then(|v| v.as_f64());
+
+        if let Some(min) = get("minimum") {
+            if value < min {
+                result.push(format!("{}: must be at least {}", path, min));
+            }
+        }
+        if let Some(max) = get("maximum") {
+            if value > max {
+                result.push(format!("{}: must be at most {}", path, max));
+            }
+        }
+        if let Some(min) = get("exclusiveMinimum") {
+            if value <= min {
+                result.push(format!("{}: must be greater than {}", path, min));
+            }
+        }
+        if let Some(max) = get("exclusiveMaximum") {
+            if value >= max {
+                result.push(format!("{}: must be less than {}", path, max));
+            }
+        }
+        if let Some(step) = get("multipleOf").filter(|s| *s > 0.0) {
+            let ratio = value / step;
+            if (ratio - ratio.round()).abs() > 1e-9 {
+                result.push(format!("{}: must be a multiple of {}", path, step));
+            }
+        }
+    }
+
+    fn validate_string(
+        schema: &Map<String, Value>,
+        value: &str,
+        path: &str,
+        result: &mut Vec<String>,
+    ) {
+        let len = value.chars().count() as u64;
+
+        if let Some(min) = schema.get("minLength").and_then(|v| v.as_u64()) {
+            if len < min {
+                result.push(format!("{}: must be at least {} characters", path, min));
+            }
+        }
+        if let Some(max) = schema.get("maxLength").and_then(|v| v.as_u64()) {
+            if len > max {
+                result.push(format!("{}: must be at most {} characters", path, max));
+            }
+        }
+        if let Some(pattern) = schema.get("pattern").and_then(|v| v.as_str()) {
+            if Regex::new(pattern).is_ok_and(|re| !re.is_match(value)) {
+                result.push(format!("{}: must match `{}`", path, pattern));
+            }
+        }
+    }
+
+    fn validate_array(
+        schema: &Map<String, Value>,
+        items: &[Value],
+        path: &str,
+        result: &mut Vec<String>,
+    ) {
+        let len = items.len() as u64;
+
+        if let Some(min) = schema.get("minItems").and_then(|v| v.as_u64()) {
+            if len < min {
+                result.push(format!("{}: must have at least {} items", path, min));
+            }
+        }
+        if let Some(max) = schema.get("maxItems").and_then(|v| v.as_u64()) {
+            if len > max {
+                result.push(format!("{}: must have at most {} items", path, max));
+            }
+        }
+        if let Some(item_schema) = schema.get("items") {
+            for (index, item) in items.iter().enumerate() {
+                Self::validate_at(item_schema, item, &format!("{}[{}]", path, index), result);
+            }
+        }
+    }
+
+    fn validate_object(
+        schema: &Map<String, Value>,
+        map: &Map<String, Value>,
+        path: &str,
+        result: &mut Vec<String>,
+    ) {
+        let empty = Map::new();
+        let properties = schema
+            .get("properties")
+            .and_then(|p| p.as_object())
+            .unwrap_or(&empty);
+
+        if let Some(required) = schema.get("required").and_then(|r| r.as_array()) {
+            for key in required.iter().filter_map(|k| k.as_str()) {
+                if !map.contains_key(key) {
+                    result.push(format!("{}: `{}` is required", path, key));
+                }
+            }
+        }
+
+        for (key, value) in map {
+            let key_path = format!("{}.{}", path, key);
+            match properties.get(key) {
+                Some(prop_schema) => Self::validate_at(prop_schema, value, &key_path, result),
+                None => match schema.get("additionalProperties") {
+                    Some(Value::Bool(false)) => {
+                        result.push(format!("{}: unknown property `{}`", path, key));
+                    }
+                    Some(additional) => Self::validate_at(additional, value, &key_path, result),
+                    None => {}
+                },
+            }
+        }
+    }
+
+    fn validate_combinators(
+        schema: &Map<String, Value>,
+        value: &Value,
+        path: &str,
+        result: &mut Vec<String>,
+    ) {
+        let get = |key: &str| schema.get(key).and_then(|v| v.as_array());
+
+        if let Some(all) = get("allOf") {
+            for sub in all {
+                Self::validate_at(sub, value, path, result);
+            }
+        }
+        if let Some(any) = get("anyOf") {
+            if !any.iter().any(|sub| Self::is_valid(sub, value)) {
+                result.push(format!(
+                    "{}: must match at least one of the anyOf schemas",
+                    path
+                ));
+            }
+        }
+        if let Some(one) = get("oneOf") {
+            let matches = one.iter().filter(|sub| Self::is_valid(sub, value)).count();
+            if matches != 1 {
+                result.push(format!(
+                    "{}: must match exactly one of the oneOf schemas, matched {}",
+                    path, matches
+                ));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn validate_types() {
+        let schema = json!({ "type": ["integer", "string"] });
+
+        assert!(JsonValidator::is_valid(&schema, &json!(1)));
+        assert!(JsonValidator::is_valid(&schema, &json!(2.0)));
+        assert!(JsonValidator::is_valid(&schema, &json!("foo")));
+        assert_eq!(
+            JsonValidator::validate(&schema, &json!(1.5)),
+            vec!["$: expected integer or string, got number"]
+        );
+    }
+
+    #[test]
+    fn validate_nested() {
+        let schema = json!({
+            "type": "object",
+            "required": ["name"],
+            "properties": {
+                "name": { "type": "string", "minLength": 2 },
+                "tags": { "type": "array", "items": { "enum": ["a", "b"] } }
+            },
+            "additionalProperties": false
+        });
+        let value = json!({ "name": "x", "tags": ["a", "c"], "extra": 1 });
+
+        assert_eq!(
+            JsonValidator::validate(&schema, &value),
+            vec![
+                "$: unknown property `extra`",
+                "$.name: must be at least 2 characters",
+                "$.tags[1]: must be one of \"a\", \"b\"",
+            ]
+        );
+    }
+
+    #[test]
+    fn validate_numbers() {
+        let schema =
+            json!({ "type": "number", "exclusiveMinimum": 0, "maximum": 10, "multipleOf": 0.5 });
+
+        assert!(JsonValidator::is_valid(&schema, &json!(2.5)));
+        assert_eq!(JsonValidator::validate(&schema, &json!(0)).len(), 1);
+        assert_eq!(JsonValidator::validate(&schema, &json!(10.2)).len(), 2);
+    }
+
+    #[test]
+    fn validate_one_of() {
+        let schema = json!({ "oneOf": [{ "type": "string" }, { "type": "number" }] });
+
+        assert!(JsonValidator::is_valid(&schema, &json!("foo")));
+        assert!(!JsonValidator::is_valid(&schema, &json!(true)));
+    }
+
+    #[test]
+    fn validate_str_success() {
+        let schema = json!({ "type": "object" });
+
+        assert_eq!(
+            JsonValidator::validate_str(&schema, "  "),
+            Vec::<String>::new()
+        );
+        assert_eq!(
+            JsonValidator::validate_str(&schema, "{}"),
+            Vec::<String>::new()
+        );
+        assert!(JsonValidator::validate_str(&schema, "{")[0].starts_with("Invalid JSON"));
+    }
+
+    #[test]
+    fn validate_ignores_unknown_keywords() {
+        let schema = json!({ "$ref": "#/definitions/foo", "format": "path" });
+
+        assert!(JsonValidator::is_valid(&schema, &json!({ "any": "thing" })));
+    }
+}
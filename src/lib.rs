@@ -2,24 +2,40 @@ pub mod about;
 pub mod command_builder;
 pub mod config_editor_dialog;
 pub mod default_widget_builder;
+pub mod directives;
+pub mod discovery_utils;
+pub mod error;
 pub mod file_utils;
 pub mod form_utils;
+pub mod json_validator;
 pub mod package_info;
 pub mod profile_browser;
 pub mod profile_data_list_item;
+pub mod profile_utils;
+pub mod runner_utils;
 pub mod save_dialog;
+pub mod schema_lint;
 pub mod schema_parsing;
 pub mod schema_view;
 pub mod schematic_executor;
+pub mod schematic_list_item;
 pub mod schematic_selector;
 pub mod schematic_ui;
 pub mod schematics;
 pub mod schematics_details;
+pub mod search_utils;
 pub mod settings;
 pub mod settings_utils;
+pub mod source_utils;
 pub mod string_list_item;
 pub mod templates;
+#[cfg(test)]
+pub mod test_utils;
 pub mod traits;
 pub mod value_extractor;
 pub mod value_loader;
+pub mod variable_utils;
+pub mod workspace_utils;
 pub mod xwidget_builder;
+pub mod xwidget_editor;
+pub mod xwidget_options;
@@ -1,4 +1,7 @@
-use gtk::prelude::{ApplicationExt, BoxExt, GtkWindowExt, OrientableExt};
+use gtk::prelude::{
+    ApplicationExt, BoxExt, DialogExt, FileChooserExt, FileExt, GtkWindowExt, ListModelExt,
+    ObjectExt, OrientableExt,
+};
 use relm4::actions::{AccelsPlus, RelmAction, RelmActionGroup};
 use relm4::gtk::traits::{FrameExt, GtkApplicationExt, WidgetExt};
 use relm4::gtk::CssProvider;
@@ -7,12 +10,19 @@ use relm4::{
     RelmWidgetExt, SimpleComponent,
 };
 use schematics_gui_reml::about::AppAboutDialog;
+use schematics_gui_reml::file_utils::FileUtils;
+use schematics_gui_reml::form_utils::FormUtils;
+use schematics_gui_reml::schema_parsing::FsEntry;
 use schematics_gui_reml::schematic_selector::{
     SchematicSelectorInput, SchematicSelectorModel, SchematicSelectorOutput,
 };
-use schematics_gui_reml::schematics_details::{SchematicsDetailsInput, SchematicsDetailsModel};
+use schematics_gui_reml::schematics::Collection;
+use schematics_gui_reml::schematics_details::{
+    SchematicsDetailsInput, SchematicsDetailsModel, SchematicsDetailsOutput,
+};
 use schematics_gui_reml::settings::*;
 use schematics_gui_reml::settings_utils::{SettingsData, SettingsUtils};
+use schematics_gui_reml::xwidget_options::{XWidgetOptions, JSON_SCHEMA_FILE_NAME};
 
 #[derive(Debug, PartialEq, Clone)]
 enum AppMode {
@@ -24,11 +34,15 @@ enum AppMode {
 #[derive(Debug)]
 enum AppMsg {
     SetMode(AppMode),
+    ShowSchematicFrom(Box<SettingsData>, String),
+    SwitchCollection(u32),
+    ReloadCollection,
     CloseRequest,
     Close,
     ShowAbout,
     ShowSettings,
     ShowShortCuts,
+    ExportWidgetSchema,
 }
 
 fn load_css() {
@@ -45,6 +59,10 @@ fn load_css() {
 struct AppModel {
     mode: AppMode,
     settings_util: SettingsUtils,
+    settings: Option<SettingsData>,
+    switcher_items: gtk::StringList,
+    /// Package names of the collections, resolved when the settings or collections change.
+    switcher_names: Vec<String>,
     dialog: Controller<SettingsModel>,
     selector: Controller<SchematicSelectorModel>,
     tabs: Controller<SchematicsDetailsModel>,
@@ -57,6 +75,41 @@ impl AppModel {
             _ => String::from("Details"),
         }
     }
+
+    fn get_switcher_names(settings: &Option<SettingsData>) -> Vec<String> {
+        let mut result: Vec<String> = match settings {
+            Some(settings) => settings
+                .get_collections()
+                .iter()
+                .map(|c| Collection::get_package_name(&c.package))
+                .collect(),
+            None => vec![],
+        };
+        result.push(String::from("All packages"));
+        result
+    }
+
+    fn get_switcher_index(&self) -> u32 {
+        match &self.settings {
+            Some(settings) if settings.merged_view => settings.get_collections().len() as u32,
+            Some(settings) => settings.get_active_index() as u32,
+            None => 0,
+        }
+    }
+
+    fn load_settings(&mut self, settings: &SettingsData) {
+        self.settings = Some(settings.clone());
+        self.switcher_names = Self::get_switcher_names(&self.settings);
+        self.selector
+            .sender()
+            .send(SchematicSelectorInput::Show(settings.clone()))
+            .unwrap();
+
+        self.tabs
+            .sender()
+            .send(SchematicsDetailsInput::Show(Some(settings.clone())))
+            .unwrap();
+    }
 }
 
 #[relm4::component]
@@ -74,6 +127,20 @@ impl SimpleComponent for AppModel {
             set_can_focus: true,
             set_title: Some("Schematics GUI"),
 
+            #[wrap(Some)]
+            set_titlebar = &gtk::HeaderBar {
+                pack_end: collection_switcher = &gtk::DropDown {
+                    set_model: Some(&model.switcher_items),
+                    set_tooltip_text: Some("Schematics package"),
+                    set_css_classes: &["collection_switcher"],
+                    #[watch]
+                    set_visible: model.settings.is_some(),
+                    connect_selected_notify[sender] => move |dropdown| {
+                        sender.input(AppMsg::SwitchCollection(dropdown.selected()));
+                    } @switcher_handler,
+                },
+            },
+
             gtk::Box {
                 #[watch]
                 set_visible: model.settings_util.exists(),
@@ -157,15 +224,25 @@ impl SimpleComponent for AppModel {
                     SchematicSelectorOutput::Selected(schematic) => {
                         AppMsg::SetMode(AppMode::ShowSchematic(schematic))
                     }
+                    SchematicSelectorOutput::SelectedFrom(settings, schematic) => {
+                        AppMsg::ShowSchematicFrom(settings, schematic)
+                    }
                 });
 
-        let tabs = SchematicsDetailsModel::builder()
-            .launch(true)
-            .forward(sender.input_sender(), |_| AppMsg::Close);
+        let tabs =
+            SchematicsDetailsModel::builder()
+                .launch(true)
+                .forward(sender.input_sender(), |msg| match msg {
+                    SchematicsDetailsOutput::CollectionChanged => AppMsg::ReloadCollection,
+                    SchematicsDetailsOutput::LoadPackageInfo(_) => AppMsg::Close,
+                });
 
         let model = AppModel {
             mode: params,
             settings_util: SettingsUtils::new(),
+            settings: None,
+            switcher_items: gtk::StringList::new(&[]),
+            switcher_names: Self::get_switcher_names(&None),
             dialog,
             selector,
             tabs,
@@ -183,6 +260,7 @@ impl SimpleComponent for AppModel {
               "Options" {
                 "About" => AboutAction,
                 "Settings" => SettingsAction,
+                "Export x-widget schema" => ExportSchemaAction,
                 "Help" => HelpAction,
                 "Quit" => QuitAction,
               }
@@ -216,6 +294,11 @@ impl SimpleComponent for AppModel {
             RelmAction::new_stateless(move |_| sender.input(AppMsg::ShowShortCuts))
         };
 
+        let export_action: RelmAction<ExportSchemaAction> = {
+            let sender = sender.clone();
+            RelmAction::new_stateless(move |_| sender.input(AppMsg::ExportWidgetSchema))
+        };
+
         // let action2: RelmAction<ExampleU8Action> =
         //     RelmAction::new_stateful_with_target_value(&0, |_, state, _value| {
         //         *state ^= 1;
@@ -226,6 +309,7 @@ impl SimpleComponent for AppModel {
         group.add_action(about_action);
         group.add_action(settings_action);
         group.add_action(help_action);
+        group.add_action(export_action);
         group.add_action(close_action);
         group.register_for_widget(&widgets.main_window);
 
@@ -234,6 +318,25 @@ impl SimpleComponent for AppModel {
         ComponentParts { model, widgets }
     }
 
+    fn pre_view() {
+        let names = &model.switcher_names;
+        let items = &model.switcher_items;
+
+        collection_switcher.block_signal(switcher_handler);
+        if (0..items.n_items())
+            .map(|i| items.string(i).unwrap_or_default().to_string())
+            .ne(names.iter().cloned())
+        {
+            items.splice(
+                0,
+                items.n_items(),
+                &names.iter().map(|n| n.as_str()).collect::<Vec<&str>>(),
+            );
+        }
+        collection_switcher.set_selected(model.get_switcher_index());
+        collection_switcher.unblock_signal(switcher_handler);
+    }
+
     fn update(&mut self, msg: Self::Input, sender: ComponentSender<Self>) {
         match msg {
             AppMsg::SetMode(mode) => {
@@ -245,33 +348,52 @@ impl SimpleComponent for AppModel {
                             .send(SchematicsDetailsInput::ShowSchematic(schematic))
                             .unwrap();
                     }
-                    AppMode::Initial => {
-                        if !self.settings_util.exists() {
-                            self.dialog.sender().send(SettingsInput::Show).unwrap();
-                        } else {
-                            let settings = self.settings_util.read();
-                            self.selector
-                                .sender()
-                                .send(SchematicSelectorInput::Show(settings.clone()))
-                                .unwrap();
-
-                            self.tabs
-                                .sender()
-                                .send(SchematicsDetailsInput::Show(Some(settings.clone())))
-                                .unwrap();
-                        }
-                    }
+                    AppMode::Initial => match self.settings_util.read() {
+                        Ok(settings) => self.load_settings(&settings),
+                        Err(_) => self.dialog.sender().send(SettingsInput::Show).unwrap(),
+                    },
                     AppMode::SettingsLoaded(data) => {
-                        self.selector
-                            .sender()
-                            .send(SchematicSelectorInput::Show(data.clone()))
-                            .unwrap();
+                        self.load_settings(&data);
+                    }
+                }
+            }
 
-                        self.tabs
-                            .sender()
-                            .send(SchematicsDetailsInput::Show(Some(data.clone())))
-                            .unwrap();
+            AppMsg::ShowSchematicFrom(settings, schematic) => {
+                self.tabs
+                    .sender()
+                    .send(SchematicsDetailsInput::Show(Some(*settings)))
+                    .unwrap();
+                sender.input(AppMsg::SetMode(AppMode::ShowSchematic(schematic)));
+            }
+
+            AppMsg::ReloadCollection => {
+                self.switcher_names = Self::get_switcher_names(&self.settings);
+                if let Some(settings) = &self.settings {
+                    self.selector
+                        .sender()
+                        .send(SchematicSelectorInput::Show(settings.clone()))
+                        .unwrap();
+                }
+            }
+
+            AppMsg::SwitchCollection(index) => {
+                let settings = match &self.settings {
+                    Some(settings) => settings.clone(),
+                    None => return,
+                };
+                let data = match settings.get_collections().get(index as usize) {
+                    Some(collection) => settings.with_collection(collection),
+                    None => SettingsData {
+                        merged_view: true,
+                        ..settings.clone()
+                    },
+                };
+
+                if data != settings {
+                    if let Err(err) = self.settings_util.write(&data) {
+                        FormUtils::new().error_dialog(&err);
                     }
+                    self.load_settings(&data);
                 }
             }
 
@@ -296,6 +418,38 @@ impl SimpleComponent for AppModel {
             }
 
             AppMsg::ShowShortCuts => {}
+
+            AppMsg::ExportWidgetSchema => {
+                let window = match relm4::main_application().active_window() {
+                    Some(window) => window,
+                    None => return,
+                };
+                let options = FsEntry {
+                    mask: String::from("*.json"),
+                    default_name: Some(String::from(JSON_SCHEMA_FILE_NAME)),
+                    ..FsEntry::default()
+                };
+                let dialog = FormUtils::new().file_chooser(
+                    "Export x-widget schema",
+                    &window,
+                    Some(gtk::FileChooserAction::Save),
+                    Some(options),
+                );
+
+                dialog.show();
+                dialog.connect_response(|dialog, resp| {
+                    if resp == gtk::ResponseType::Accept {
+                        if let Some(path) = dialog.file().and_then(|f| f.path()) {
+                            let schema = XWidgetOptions::get_json_schema();
+                            let text = serde_json::to_string_pretty(&schema).unwrap();
+                            if let Err(err) = FileUtils::write_str(&path, &text) {
+                                FormUtils::new().error_dialog(&err);
+                            }
+                        }
+                    }
+                    dialog.close();
+                });
+            }
         }
     }
 }
@@ -306,6 +460,7 @@ relm4::new_stateless_action!(AboutAction, WindowActionGroup, "about");
 relm4::new_stateless_action!(QuitAction, WindowActionGroup, "quit");
 relm4::new_stateless_action!(SettingsAction, WindowActionGroup, "settings");
 relm4::new_stateless_action!(HelpAction, WindowActionGroup, "help");
+relm4::new_stateless_action!(ExportSchemaAction, WindowActionGroup, "export_schema");
 // relm4::new_stateful_action!(ExampleU8Action, WindowActionGroup, "example2", u8, u8);
 
 fn main() {
@@ -2,6 +2,8 @@ use relm4::gtk::prelude::{EntryBufferExtManual, GridExt, OrientableExt, WidgetEx
 use relm4::gtk::{Justification, LinkButton};
 use relm4::{gtk, ComponentParts, ComponentSender, SimpleComponent};
 
+use crate::error::{AppError, Result};
+use crate::file_utils::FileUtils;
 use crate::form_utils::FormUtils;
 use crate::schematics::Collection;
 
@@ -71,11 +73,17 @@ pub enum AuthorType {
     String(String),
 }
 
+impl PartialPackageJsonData {
+    pub fn read(package: &str) -> Result<Self> {
+        let path = Collection::resolve_package_json(package)
+            .ok_or_else(|| AppError::NotFound(format!("package {}", package)))?;
+        FileUtils::read_json(&path)
+    }
+}
+
 impl PackageInfoModel {
-    fn read_package_json(&self, path: String) -> PartialPackageJsonData {
-        let json_str = &Collection::read_str(&path);
-        let package_json: PartialPackageJsonData = serde_json::from_str(json_str).unwrap();
-        package_json
+    fn read_package_json(&self, package: String) -> Result<PartialPackageJsonData> {
+        PartialPackageJsonData::read(&package)
     }
 
     fn populate_view(&self, package_json: PartialPackageJsonData) {
@@ -197,7 +205,13 @@ impl SimpleComponent for PackageInfoModel {
     fn update(&mut self, message: Self::Input, sender: ComponentSender<Self>) {
         match message {
             PackageInfoInput::Show(path) => {
-                let package_json: PartialPackageJsonData = self.read_package_json(path);
+                let package_json = match self.read_package_json(path) {
+                    Ok(package_json) => package_json,
+                    Err(err) => {
+                        self.utils.error_dialog(&err);
+                        return;
+                    }
+                };
                 let _ = sender.output(PackageInfoOutput::PackageData(package_json.clone()));
                 self.populate_view(package_json);
                 self.hidden = false;
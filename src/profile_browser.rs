@@ -10,6 +10,8 @@ use relm4::typed_list_view::TypedListView;
 use relm4::{gtk, ComponentParts, ComponentSender, SimpleComponent};
 use toml::Table;
 
+use crate::error::Result;
+use crate::file_utils::FileUtils;
 use crate::profile_data_list_item::{ProfileDataListItem, ProfileDataMenuItem};
 use crate::save_dialog::ProfileData;
 use crate::settings_utils::SettingsUtils;
@@ -83,16 +85,8 @@ impl ProfileBrowserModel {
         }
     }
 
-    pub fn get_loaded_profile_data(&self, path: &PathBuf) -> Map<String, Value> {
-        match match fs::read_to_string(path) {
-            Ok(contents) => contents,
-            Err(e) => panic!("Could not read file! {}", e),
-        }
-        .parse::<Table>()
-        {
-            Ok(parsed) => parsed,
-            Err(err) => panic!("Could not parse TOML! {}", err),
-        }
+    pub fn get_loaded_profile_data(&self, path: &PathBuf) -> Result<Map<String, Value>> {
+        FileUtils::read_toml::<Table>(path)
     }
 
     pub fn is_profile_loaded(&self) -> bool {
@@ -123,28 +117,42 @@ impl ProfileBrowserModel {
 
     fn load_profiles(&mut self) -> Vec<ProfileDataMenuItem> {
         let mut result: Vec<ProfileDataMenuItem> = vec![];
-        let dir = fs::read_dir(self.get_profile_dir());
+        let dir = match fs::read_dir(self.get_profile_dir()) {
+            Ok(dir) => dir,
+            Err(_) => return result,
+        };
 
-        for entry in dir.unwrap() {
-            let entry = entry.unwrap();
+        for entry in dir.flatten() {
             let path = entry.path();
 
             if path.is_file() && path.extension().unwrap_or(OsStr::new("")) == "toml" {
-                let profile = self.get_loaded_profile_data(&path);
+                let profile = match self.get_loaded_profile_data(&path) {
+                    Ok(profile) => profile,
+                    Err(_) => continue,
+                };
+                let data = match profile.get("data").and_then(|data| data.as_table()) {
+                    Some(data) => data.clone(),
+                    None => continue,
+                };
+                let meta = profile
+                    .get("meta")
+                    .and_then(|meta| meta.as_table())
+                    .cloned()
+                    .unwrap_or_default();
 
                 let file = path.file_name().unwrap().to_str().unwrap();
                 let mut profile_name = path.file_stem().unwrap().to_str().unwrap().to_string();
-                let description = profile["meta"]["description"].as_str().unwrap_or("");
+                let description = profile
+                    .get("meta")
+                    .and_then(|meta| meta.get("description"))
+                    .and_then(|description| description.as_str())
+                    .unwrap_or("");
 
                 if !description.is_empty() {
                     profile_name = format!("{} ({})", profile_name, description)
                 }
 
-                let profile = ProfileData::new(
-                    profile_name.clone(),
-                    file.to_string(),
-                    profile["data"].as_table().unwrap().clone(),
-                );
+                let profile = ProfileData::new(profile_name.clone(), file.to_string(), data, meta);
 
                 self.profiles.push(profile);
                 result.push(ProfileDataMenuItem::new(file.to_string(), profile_name))
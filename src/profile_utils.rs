@@ -0,0 +1,646 @@
+use convert_case::{Case, Casing};
+use serde_json::Value as Json;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+use toml::{map::Map, Table, Value};
+
+use crate::error::{AppError, Result};
+use crate::file_utils::FileUtils;
+use crate::search_utils::SearchUtils;
+
+/// Version of the profile format, the ones without a version hold strings only.
+pub const PROFILE_VERSION: i64 = 2;
+
+/// Profiles hold the form values of a schematic in a `[data]` table, typed by the JSON Schema
+/// of the properties, and a `[meta]` table with the description, the format version and the
+/// fingerprint of the schema they were saved with. `extends` names the profile the values are
+/// layered on.
+pub struct ProfileUtils {}
+
+/// Values of a profile and the ones it inherits through `extends`.
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct ResolvedProfile {
+    /// Values of the profile itself.
+    pub data: Map<String, Value>,
+    /// Values of the profiles it extends, the nearest one wins.
+    pub inherited: Map<String, Value>,
+    /// File name of the profile each inherited value comes from.
+    pub sources: HashMap<String, String>,
+}
+
+impl ResolvedProfile {
+    /// Inherited values with the ones of the profile on top.
+    pub fn get_merged(&self) -> Map<String, Value> {
+        let mut result = self.inherited.clone();
+        result.extend(self.data.clone());
+        result
+    }
+
+    pub fn is_inherited(&self, key: &str) -> bool {
+        self.inherited.contains_key(key) && !self.data.contains_key(key)
+    }
+
+    pub fn is_overridden(&self, key: &str) -> bool {
+        self.inherited.contains_key(key) && self.data.contains_key(key)
+    }
+
+    /// Values of `data` that differ from the inherited ones, the ones a profile extending
+    /// others needs to hold.
+    pub fn get_overrides(&self, data: &Map<String, Value>) -> Map<String, Value> {
+        data.iter()
+            .filter(|(key, value)| self.inherited.get(*key) != Some(value))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect()
+    }
+}
+
+/// Differences between a profile and the current schema of its schematic.
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct ProfileDrift {
+    /// Options the schema doesn't have anymore, with the option they were likely renamed to.
+    pub unknown: Vec<(String, Option<String>)>,
+    /// Option and value pairs the `enum` of the option doesn't allow anymore.
+    pub removed_values: Vec<(String, String)>,
+    /// Required options the profile has no value for.
+    pub missing_required: Vec<String>,
+}
+
+impl ProfileDrift {
+    pub fn is_empty(&self) -> bool {
+        self.unknown.is_empty()
+            && self.removed_values.is_empty()
+            && self.missing_required.is_empty()
+    }
+}
+
+impl Display for ProfileDrift {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut lines: Vec<String> = vec![];
+
+        if !self.unknown.is_empty() {
+            let keys: Vec<&str> = self.unknown.iter().map(|(key, _)| key.as_str()).collect();
+            lines.push(format!("Unknown options: {}", keys.join(", ")));
+        }
+        if !self.removed_values.is_empty() {
+            let values: Vec<String> = self
+                .removed_values
+                .iter()
+                .map(|(key, value)| format!("{}={}", key, value))
+                .collect();
+            lines.push(format!("Values no longer allowed: {}", values.join(", ")));
+        }
+        if !self.missing_required.is_empty() {
+            lines.push(format!(
+                "New required options: {}",
+                self.missing_required.join(", ")
+            ));
+        }
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+impl ProfileUtils {
+    pub fn to_toml(
+        description: &str,
+        extends: Option<&str>,
+        fingerprint: &str,
+        data: &Map<String, Value>,
+    ) -> Result<String> {
+        let mut meta = Table::new();
+        meta.insert(
+            String::from("description"),
+            Value::String(description.to_string()),
+        );
+        if let Some(extends) = extends {
+            meta.insert(String::from("extends"), Value::String(extends.to_string()));
+        }
+        Self::set_version(&mut meta, Some(fingerprint));
+
+        let mut profile = Table::new();
+        profile.insert(String::from("meta"), Value::Table(meta));
+        profile.insert(String::from("data"), Value::Table(data.clone()));
+        Ok(toml::to_string_pretty(&profile)?)
+    }
+
+    /// Profile named by the `extends` of a `[meta]` table.
+    pub fn get_extends(meta: &Map<String, Value>) -> Option<String> {
+        meta.get("extends")
+            .and_then(Value::as_str)
+            .filter(|extends| !extends.is_empty())
+            .map(String::from)
+    }
+
+    /// Values of a profile and the ones it extends, any number of levels deep. The path in
+    /// `extends` is relative to the directory of the profile declaring it.
+    pub fn resolve(path: &Path) -> Result<ResolvedProfile> {
+        let mut result = ResolvedProfile::default();
+        let mut visited: Vec<PathBuf> = vec![];
+        let mut next = Some(path.to_path_buf());
+
+        while let Some(path) = next {
+            let canonical = path.canonicalize().unwrap_or(path.clone());
+            if visited.contains(&canonical) {
+                return Err(AppError::Profile(
+                    path,
+                    String::from("The profiles extend each other."),
+                ));
+            }
+
+            let profile: Table = FileUtils::read_toml(&path)?;
+            let get_table = |key: &str| {
+                profile
+                    .get(key)
+                    .and_then(Value::as_table)
+                    .cloned()
+                    .unwrap_or_default()
+            };
+            let data = get_table("data");
+
+            if visited.is_empty() {
+                result.data = data;
+            } else {
+                let file = path.file_name().unwrap_or_default().to_string_lossy();
+                for (key, value) in data {
+                    if !result.inherited.contains_key(&key) {
+                        result.sources.insert(key.clone(), file.to_string());
+                        result.inherited.insert(key, value);
+                    }
+                }
+            }
+
+            next = Self::get_extends(&get_table("meta"))
+                .map(|extends| path.parent().unwrap_or(Path::new("")).join(extends));
+            visited.push(canonical);
+        }
+        Ok(result)
+    }
+
+    /// Replaces the `[data]` table of a profile, the rest of the file is kept. The fingerprint
+    /// is only updated if given, so the differences to the schema are reported again.
+    pub fn write_data(
+        path: &Path,
+        data: &Map<String, Value>,
+        fingerprint: Option<&str>,
+    ) -> Result<()> {
+        let mut profile: Table = FileUtils::read_toml(path)?;
+        let mut meta = profile
+            .get("meta")
+            .and_then(Value::as_table)
+            .cloned()
+            .unwrap_or_default();
+        Self::set_version(&mut meta, fingerprint);
+
+        profile.insert(String::from("meta"), Value::Table(meta));
+        profile.insert(String::from("data"), Value::Table(data.clone()));
+        FileUtils::write_str(path, &toml::to_string_pretty(&profile)?)
+    }
+
+    fn set_version(meta: &mut Table, fingerprint: Option<&str>) {
+        meta.insert(String::from("version"), Value::Integer(PROFILE_VERSION));
+        if let Some(fingerprint) = fingerprint {
+            meta.insert(
+                String::from("schema"),
+                Value::String(fingerprint.to_string()),
+            );
+        }
+    }
+
+    /// Hash of the options of a schema: their names, types and enums, and the required ones.
+    pub fn get_fingerprint(schema: &Json) -> String {
+        let empty = serde_json::Map::new();
+        let properties: serde_json::Map<String, Json> = schema["properties"]
+            .as_object()
+            .unwrap_or(&empty)
+            .iter()
+            .map(|(name, prop)| {
+                let summary = serde_json::json!({
+                    "type": prop["type"],
+                    "enum": prop["enum"],
+                    "items": prop["items"]["enum"],
+                });
+                (name.clone(), summary)
+            })
+            .collect();
+        let mut required: Vec<&str> = schema["required"]
+            .as_array()
+            .map(|r| r.iter().filter_map(Json::as_str).collect())
+            .unwrap_or_default();
+        required.sort();
+
+        // The keys of serde_json maps are sorted, so equal schemas print the same.
+        let text =
+            serde_json::json!({ "properties": properties, "required": required }).to_string();
+        format!("{:016x}", Self::hash(&text))
+    }
+
+    /// 64 bit FNV-1a, unlike the std hashers it's stable across Rust versions.
+    fn hash(text: &str) -> u64 {
+        text.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+        })
+    }
+
+    /// Whether the profile was saved with the current schema, `meta` is the `[meta]` table.
+    pub fn is_current(meta: &Map<String, Value>, fingerprint: &str) -> bool {
+        meta.get("schema").and_then(Value::as_str) == Some(fingerprint)
+    }
+
+    /// Options of typed profile data the schema doesn't know or allow, and the missing ones.
+    pub fn get_drift(data: &Map<String, Value>, schema: &Json) -> ProfileDrift {
+        let properties = &schema["properties"];
+        let mut drift = ProfileDrift::default();
+
+        for (key, value) in data {
+            let prop = &properties[key];
+            if prop.is_null() {
+                drift
+                    .unknown
+                    .push((key.clone(), Self::suggest_key(key, data, properties)));
+                continue;
+            }
+            let allowed = prop["enum"].as_array().or(prop["items"]["enum"].as_array());
+            if let Some(allowed) = allowed {
+                let allowed: Vec<String> = allowed.iter().map(Self::json_to_text).collect();
+                let values = match value {
+                    Value::Array(items) => items.iter().map(Self::to_text).collect(),
+                    other => vec![Self::to_text(other)],
+                };
+                drift.removed_values.extend(
+                    values
+                        .into_iter()
+                        .filter(|v| !v.is_empty() && !allowed.contains(v))
+                        .map(|v| (key.clone(), v)),
+                );
+            }
+        }
+
+        drift.missing_required = schema["required"]
+            .as_array()
+            .map(|r| r.iter().filter_map(Json::as_str).collect::<Vec<&str>>())
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|name| !data.contains_key(*name) && properties[name]["default"].is_null())
+            .map(String::from)
+            .collect();
+        drift
+    }
+
+    /// Option an unknown key was likely renamed to, among the ones the profile has no value for.
+    fn suggest_key(key: &str, data: &Map<String, Value>, properties: &Json) -> Option<String> {
+        let query: String = key.chars().filter(|c| c.is_alphanumeric()).collect();
+        properties
+            .as_object()?
+            .keys()
+            .filter(|name| !data.contains_key(*name))
+            .filter_map(|name| Some((SearchUtils::fuzzy_score(name, &query)?, name)))
+            .max_by(|(a, x), (b, y)| a.cmp(b).then(y.len().cmp(&x.len())))
+            .map(|(_, name)| name.clone())
+    }
+
+    /// Moves the values of the old keys to the new ones, typed for the new option. A mapping
+    /// without a new key removes the value.
+    pub fn migrate(
+        data: &Map<String, Value>,
+        mapping: &[(String, Option<String>)],
+        properties: &Json,
+    ) -> Map<String, Value> {
+        let mut result = data.clone();
+
+        for (old, new) in mapping {
+            if let (Some(value), Some(new)) = (result.remove(old), new) {
+                let value = Self::to_value(&Self::to_text(&value), &properties[new]);
+                result.insert(new.clone(), value);
+            }
+        }
+        result
+    }
+
+    /// Text of a value as a form would hold it, arrays and tables as JSON.
+    fn to_text(value: &Value) -> String {
+        match value {
+            Value::String(s) => s.clone(),
+            Value::Array(_) | Value::Table(_) => serde_json::to_string(value).unwrap_or_default(),
+            other => other.to_string(),
+        }
+    }
+
+    fn json_to_text(value: &Json) -> String {
+        match value {
+            Json::String(s) => s.clone(),
+            other => other.to_string(),
+        }
+    }
+
+    /// Form values typed by the `properties` of the schema. Older profiles hold every value
+    /// as a string, keyed by the kebab case option name, e.g.: `skip-tests='true'`.
+    pub fn to_typed(data: &Map<String, Value>, properties: &Json) -> Map<String, Value> {
+        data.iter()
+            .map(|(key, value)| {
+                let name = Self::get_property_name(key, properties);
+                let value = match value {
+                    Value::String(text) => Self::to_value(text, &properties[&name]),
+                    other => other.clone(),
+                };
+                (name, value)
+            })
+            .collect()
+    }
+
+    /// Value of the first type of the property the text parses as, preferring anything over
+    /// `string`. The text itself if none fits.
+    pub fn to_value(text: &str, prop: &Json) -> Value {
+        let types: Vec<&str> = match &prop["type"] {
+            Json::String(r#type) => vec![r#type.as_str()],
+            Json::Array(types) => types.iter().filter_map(Json::as_str).collect(),
+            _ => vec![],
+        };
+
+        types
+            .into_iter()
+            .find_map(|r#type| Self::parse(text, r#type, prop))
+            .unwrap_or(Value::String(text.to_string()))
+    }
+
+    fn parse(text: &str, r#type: &str, prop: &Json) -> Option<Value> {
+        match r#type {
+            "boolean" => text.parse::<bool>().ok().map(Value::Boolean),
+            "integer" => match (text.parse::<i64>(), text.parse::<f64>()) {
+                (Ok(i), _) => Some(Value::Integer(i)),
+                (_, Ok(f)) if f.is_finite() => Some(Value::Integer(f.round() as i64)),
+                _ => None,
+            },
+            "number" => text
+                .parse::<f64>()
+                .ok()
+                .filter(|f| f.is_finite())
+                .map(Value::Float),
+            // A JSON array of a JSON input or the comma separated items of a multiselect.
+            "array" => match serde_json::from_str::<Json>(text) {
+                Ok(json @ Json::Array(_)) => Self::from_json(json),
+                _ => Some(Value::Array(
+                    text.split(',')
+                        .filter(|item| !item.is_empty())
+                        .map(|item| Self::to_value(item, &prop["items"]))
+                        .collect(),
+                )),
+            },
+            "object" => serde_json::from_str::<Json>(text)
+                .ok()
+                .filter(Json::is_object)
+                .and_then(Self::from_json),
+            _ => None,
+        }
+    }
+
+    /// TOML has no null, JSON holding one stays a string.
+    fn from_json(json: Json) -> Option<Value> {
+        Value::try_from(json).ok()
+    }
+
+    fn get_property_name(key: &str, properties: &Json) -> String {
+        properties
+            .as_object()
+            .filter(|props| !props.contains_key(key))
+            .and_then(|props| props.keys().find(|name| name.to_case(Case::Kebab) == key))
+            .cloned()
+            .unwrap_or(key.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn get_properties() -> Json {
+        json!({
+            "name": { "type": "string" },
+            "skipTests": { "type": "boolean" },
+            "port": { "type": "integer" },
+            "ratio": { "type": "number" },
+            "styles": { "type": "array", "items": { "type": "string" } },
+            "ports": { "type": "array", "items": { "type": "integer" } },
+            "env": { "type": "object" },
+            "flag": { "type": ["string", "boolean"] }
+        })
+    }
+
+    fn get_data(entries: &[(&str, Value)]) -> Map<String, Value> {
+        entries
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn to_typed_success() {
+        let data = get_data(&[
+            ("name", Value::from("it's")),
+            ("skip-tests", Value::from("true")),
+            ("port", Value::from("4200.0")),
+            ("ratio", Value::from("0.5")),
+            ("styles", Value::from("css,scss")),
+            ("ports", Value::from("[80, 443]")),
+            ("env", Value::from(r#"{"mode":"dev"}"#)),
+            ("flag", Value::from("false")),
+            ("unknown", Value::from("1")),
+        ]);
+        let typed = ProfileUtils::to_typed(&data, &get_properties());
+
+        assert_eq!(typed["name"], Value::from("it's"));
+        assert_eq!(typed["skipTests"], Value::Boolean(true));
+        assert_eq!(typed["port"], Value::Integer(4200));
+        assert_eq!(typed["ratio"], Value::Float(0.5));
+        assert_eq!(typed["styles"], Value::from(vec!["css", "scss"]));
+        assert_eq!(typed["ports"], Value::from(vec![80, 443]));
+        assert_eq!(typed["env"]["mode"], Value::from("dev"));
+        assert_eq!(typed["flag"], Value::Boolean(false));
+        assert_eq!(typed["unknown"], Value::from("1"));
+        assert_eq!(ProfileUtils::to_typed(&typed, &get_properties()), typed);
+    }
+
+    #[test]
+    fn to_value_fallback() {
+        let props = get_properties();
+
+        assert_eq!(
+            ProfileUtils::to_value("yes", &props["skipTests"]),
+            Value::from("yes")
+        );
+        assert_eq!(
+            ProfileUtils::to_value(r#"{"a":null}"#, &props["env"]),
+            Value::from(r#"{"a":null}"#)
+        );
+        assert_eq!(
+            ProfileUtils::to_value("", &props["styles"]),
+            Value::Array(vec![])
+        );
+    }
+
+    #[test]
+    fn to_toml_round_trip() {
+        let data = get_data(&[
+            ("name", Value::from("it's \"quoted\"")),
+            ("skipTests", Value::Boolean(true)),
+            ("styles", Value::from(vec!["css"])),
+        ]);
+        let profile: Table =
+            toml::from_str(&ProfileUtils::to_toml("a 'b'", None, "f00d", &data).unwrap()).unwrap();
+
+        assert_eq!(profile["meta"]["description"], Value::from("a 'b'"));
+        assert_eq!(profile["meta"]["schema"], Value::from("f00d"));
+        assert_eq!(profile["meta"]["version"], Value::Integer(PROFILE_VERSION));
+        assert_eq!(profile["data"], Value::Table(data));
+    }
+
+    #[test]
+    fn get_fingerprint_success() {
+        let schema = json!({ "properties": get_properties(), "required": ["name"] });
+        let described = json!({
+            "$id": "other",
+            "properties": { "name": { "type": "string", "description": "Name" } },
+            "required": ["name"]
+        });
+        let renamed =
+            json!({ "properties": { "title": { "type": "string" } }, "required": ["name"] });
+        let fingerprint = ProfileUtils::get_fingerprint(&schema);
+
+        assert_eq!(fingerprint, ProfileUtils::get_fingerprint(&schema.clone()));
+        assert_eq!(fingerprint.len(), 16);
+        assert_eq!(
+            ProfileUtils::get_fingerprint(&described),
+            ProfileUtils::get_fingerprint(&json!({
+                "properties": { "name": { "type": "string" } },
+                "required": ["name"]
+            }))
+        );
+        assert_ne!(
+            ProfileUtils::get_fingerprint(&described),
+            ProfileUtils::get_fingerprint(&renamed)
+        );
+    }
+
+    #[test]
+    fn get_drift_success() {
+        let schema = json!({
+            "properties": {
+                "skipTests": { "type": "boolean" },
+                "style": { "type": "string", "enum": ["css", "scss"] },
+                "styles": { "type": "array", "items": { "enum": ["css", "scss"] } },
+                "project": { "type": "string" },
+                "path": { "type": "string", "default": "src" }
+            },
+            "required": ["project", "path"]
+        });
+        let data = get_data(&[
+            ("skip-test", Value::from(true)),
+            ("style", Value::from("sass")),
+            ("styles", Value::from(vec!["css", "less"])),
+        ]);
+        let drift = ProfileUtils::get_drift(&data, &schema);
+
+        assert_eq!(
+            drift.unknown,
+            vec![(String::from("skip-test"), Some(String::from("skipTests")))]
+        );
+        assert_eq!(
+            drift.removed_values,
+            vec![
+                (String::from("style"), String::from("sass")),
+                (String::from("styles"), String::from("less"))
+            ]
+        );
+        assert_eq!(drift.missing_required, vec!["project"]);
+        assert!(drift.to_string().contains("Unknown options: skip-test"));
+        assert!(ProfileUtils::get_drift(&Map::new(), &json!({})).is_empty());
+    }
+
+    #[test]
+    fn migrate_success() {
+        let data = get_data(&[
+            ("spec", Value::from("false")),
+            ("styles", Value::from(vec!["css"])),
+            ("old", Value::from("x")),
+        ]);
+        let mapping = vec![
+            (String::from("spec"), Some(String::from("skipTests"))),
+            (String::from("styles"), Some(String::from("ports"))),
+            (String::from("old"), None),
+        ];
+        let migrated = ProfileUtils::migrate(&data, &mapping, &get_properties());
+
+        assert_eq!(
+            migrated,
+            get_data(&[
+                ("skipTests", Value::Boolean(false)),
+                ("ports", Value::from(vec!["css"])),
+            ])
+        );
+    }
+
+    #[test]
+    fn resolve_success() {
+        let dir = std::env::temp_dir().join("schematics-gui-profiles");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("team")).unwrap();
+        let write = |file: &str, extends: Option<&str>, data: &[(&str, Value)]| {
+            let toml = ProfileUtils::to_toml("", extends, "", &get_data(data)).unwrap();
+            std::fs::write(dir.join(file), toml).unwrap();
+        };
+        write(
+            "team/base.toml",
+            None,
+            &[
+                ("style", Value::from("scss")),
+                ("skipTests", Value::from(false)),
+            ],
+        );
+        write(
+            "team/lib.toml",
+            Some("base.toml"),
+            &[("style", Value::from("css")), ("flat", Value::from(true))],
+        );
+        write(
+            "feature.toml",
+            Some("team/lib.toml"),
+            &[("skipTests", Value::from(true))],
+        );
+
+        let resolved = ProfileUtils::resolve(&dir.join("feature.toml")).unwrap();
+
+        assert_eq!(
+            resolved.get_merged(),
+            get_data(&[
+                ("style", Value::from("css")),
+                ("skipTests", Value::from(true)),
+                ("flat", Value::from(true))
+            ])
+        );
+        assert!(resolved.is_inherited("style"));
+        assert!(resolved.is_overridden("skipTests"));
+        assert_eq!(resolved.sources["style"], "lib.toml");
+        assert_eq!(resolved.sources["skipTests"], "base.toml");
+        assert_eq!(
+            resolved.get_overrides(&resolved.get_merged()),
+            get_data(&[("skipTests", Value::from(true))])
+        );
+    }
+
+    #[test]
+    fn resolve_cycle() {
+        let dir = std::env::temp_dir().join("schematics-gui-profiles-cycle");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        for (file, extends) in [("a.toml", "b.toml"), ("b.toml", "a.toml")] {
+            let toml = ProfileUtils::to_toml("", Some(extends), "", &Map::new()).unwrap();
+            std::fs::write(dir.join(file), toml).unwrap();
+        }
+
+        assert!(matches!(
+            ProfileUtils::resolve(&dir.join("a.toml")),
+            Err(AppError::Profile(_, _))
+        ));
+        assert!(ProfileUtils::resolve(&dir.join("missing.toml")).is_err());
+    }
+}
@@ -0,0 +1,350 @@
+use convert_case::Case;
+use serde::{Deserialize, Serialize};
+use std::ffi::OsStr;
+use std::fs::read_dir;
+use std::path::{Path, PathBuf};
+
+use crate::command_builder::CommandBuilderOptions;
+use crate::error::{AppError, Result};
+use crate::file_utils::FileUtils;
+use crate::settings_utils::SettingsUtils;
+
+pub struct RunnerUtils;
+
+impl Default for RunnerUtils {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum BooleanStyle {
+    /// `--x` / `--no-x`
+    Negated,
+    /// `--x=true` / `--x=false`
+    Value,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum MapStyle {
+    /// `--x a=1 --x b=2`
+    Pairs,
+    /// `--x '{"a":"1","b":"2"}'`
+    Json,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum OptionCase {
+    Kebab,
+    Camel,
+    Snake,
+    Pascal,
+}
+
+impl From<OptionCase> for Case {
+    fn from(val: OptionCase) -> Self {
+        match val {
+            OptionCase::Kebab => Case::Kebab,
+            OptionCase::Camel => Case::Camel,
+            OptionCase::Snake => Case::Snake,
+            OptionCase::Pascal => Case::Pascal,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct RunnerDefinition {
+    pub name: String,
+    pub executable: String,
+    pub command: String,
+    pub boolean_style: BooleanStyle,
+    pub map_style: MapStyle,
+    pub option_case: OptionCase,
+    pub dry_run: Option<String>,
+    pub non_interactive: Option<String>,
+    pub quote_paths: bool,
+    pub escape_multiline_text: bool,
+}
+
+impl Default for RunnerDefinition {
+    fn default() -> Self {
+        RunnerDefinition {
+            name: String::from("Custom"),
+            executable: String::default(),
+            command: String::from("{schematic}"),
+            boolean_style: BooleanStyle::Negated,
+            map_style: MapStyle::Pairs,
+            option_case: OptionCase::Kebab,
+            dry_run: None,
+            non_interactive: None,
+            quote_paths: true,
+            escape_multiline_text: true,
+        }
+    }
+}
+
+impl RunnerDefinition {
+    pub fn google() -> Self {
+        RunnerDefinition {
+            name: String::from("Google"),
+            executable: String::from("schematics"),
+            command: String::from("{package}:{schematic}"),
+            dry_run: Some(String::from("--dry-run")),
+            non_interactive: Some(String::from("--no-interactive")),
+            ..Default::default()
+        }
+    }
+
+    pub fn mbh() -> Self {
+        RunnerDefinition {
+            name: String::from("MBH"),
+            executable: String::from("fnd"),
+            dry_run: Some(String::from("--dry-run")),
+            non_interactive: Some(String::from("--no-interactive")),
+            ..Default::default()
+        }
+    }
+
+    pub fn custom() -> Self {
+        RunnerDefinition::default()
+    }
+
+    pub fn built_ins() -> Vec<RunnerDefinition> {
+        vec![Self::google(), Self::mbh(), Self::custom()]
+    }
+
+    pub fn has_dry_run(&self) -> bool {
+        self.dry_run.is_some()
+    }
+
+    pub fn get_dry_run_args(&self) -> Vec<String> {
+        [self.dry_run.clone(), self.non_interactive.clone()]
+            .into_iter()
+            .flatten()
+            .flat_map(|flags| {
+                flags
+                    .split_whitespace()
+                    .map(String::from)
+                    .collect::<Vec<String>>()
+            })
+            .collect()
+    }
+
+    pub fn get_command(&self, package_name: &str, schematic: &str) -> String {
+        self.command
+            .replace("{package}", package_name)
+            .replace("{schematic}", schematic)
+    }
+
+    pub fn get_builder_options(&self) -> CommandBuilderOptions {
+        CommandBuilderOptions {
+            option_case: self.option_case.clone().into(),
+            escape_multiline_text: self.escape_multiline_text,
+            quote_paths: self.quote_paths,
+            pass_boolean: self.boolean_style == BooleanStyle::Value,
+            pass_map_as_json: self.map_style == MapStyle::Json,
+            ..Default::default()
+        }
+    }
+
+    pub fn matches(&self, runner_location: &str) -> bool {
+        !self.executable.is_empty()
+            && Path::new(runner_location)
+                .file_stem()
+                .unwrap_or_default()
+                .eq(OsStr::new(&self.executable))
+    }
+}
+
+impl RunnerUtils {
+    pub fn new() -> Self {
+        RunnerUtils {}
+    }
+
+    pub fn get_runners_dir() -> PathBuf {
+        SettingsUtils::get_config_dir().join("runners")
+    }
+
+    pub fn init(&self) -> Result<()> {
+        let runners_dir = Self::get_runners_dir();
+
+        if runners_dir.exists() {
+            return Ok(());
+        }
+
+        std::fs::create_dir_all(&runners_dir).map_err(|err| AppError::io(&runners_dir, err))?;
+
+        for runner in RunnerDefinition::built_ins() {
+            self.write(&runner)?;
+        }
+        Ok(())
+    }
+
+    pub fn write(&self, runner: &RunnerDefinition) -> Result<()> {
+        let toml = toml::to_string(runner)?;
+        let path = Self::get_runners_dir().join(format!("{}.toml", runner.name.to_lowercase()));
+        FileUtils::write_str(&path, &toml)
+    }
+
+    /// Runner definitions of the runners dir, the built-in ones if it doesn't exist. Files that
+    /// can't be read or parsed are skipped and returned as warnings.
+    pub fn list(&self) -> (Vec<RunnerDefinition>, Vec<AppError>) {
+        Self::list_dir(&Self::get_runners_dir())
+    }
+
+    fn list_dir(runners_dir: &Path) -> (Vec<RunnerDefinition>, Vec<AppError>) {
+        if !runners_dir.is_dir() {
+            return (RunnerDefinition::built_ins(), vec![]);
+        }
+
+        let mut result: Vec<RunnerDefinition> = vec![];
+        let mut warnings: Vec<AppError> = vec![];
+        let entries = match read_dir(runners_dir) {
+            Ok(entries) => entries,
+            Err(err) => {
+                return (
+                    RunnerDefinition::built_ins(),
+                    vec![AppError::io(runners_dir, err)],
+                )
+            }
+        };
+
+        for entry in entries {
+            let path = match entry {
+                Ok(entry) => entry.path(),
+                Err(err) => {
+                    warnings.push(AppError::io(runners_dir, err));
+                    continue;
+                }
+            };
+            if path.is_file() && path.extension().unwrap_or_default() == "toml" {
+                match Self::read(&path) {
+                    Ok(runner) => result.push(runner),
+                    Err(err) => warnings.push(err),
+                }
+            }
+        }
+
+        result.sort_by_cached_key(|r| r.name.clone());
+        (result, warnings)
+    }
+
+    pub fn read(path: &Path) -> Result<RunnerDefinition> {
+        FileUtils::read_toml(path)
+    }
+
+    pub fn get(&self, name: &str) -> RunnerDefinition {
+        self.list()
+            .0
+            .into_iter()
+            .chain(RunnerDefinition::built_ins())
+            .find(|r| r.name == name)
+            .unwrap_or_default()
+    }
+
+    pub fn detect(&self, runner_location: &str) -> RunnerDefinition {
+        self.list()
+            .0
+            .into_iter()
+            .find(|r| r.matches(runner_location))
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn get_command_with_package() {
+        let runner = RunnerDefinition::google();
+
+        assert_eq!(runner.get_command("@foo/bar", "baz"), "@foo/bar:baz")
+    }
+
+    #[test]
+    fn get_command_without_package() {
+        let runner = RunnerDefinition::mbh();
+
+        assert_eq!(runner.get_command("@foo/bar", "baz"), "baz")
+    }
+
+    #[test]
+    fn has_dry_run() {
+        assert!(RunnerDefinition::google().has_dry_run());
+        assert!(!RunnerDefinition::custom().has_dry_run());
+    }
+
+    #[test]
+    fn get_dry_run_args() {
+        let runner = RunnerDefinition {
+            dry_run: Some(String::from("--dry-run --verbose")),
+            non_interactive: Some(String::from("--no-interactive")),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            runner.get_dry_run_args(),
+            vec!["--dry-run", "--verbose", "--no-interactive"]
+        )
+    }
+
+    #[test]
+    fn get_builder_options() {
+        let runner = RunnerDefinition {
+            boolean_style: BooleanStyle::Value,
+            option_case: OptionCase::Camel,
+            quote_paths: false,
+            ..Default::default()
+        };
+        let options = runner.get_builder_options();
+
+        assert!(options.pass_boolean);
+        assert!(!options.pass_map_as_json);
+        assert!(!options.quote_paths);
+        assert!(options.escape_multiline_text);
+        assert_eq!(options.option_case, Case::Camel);
+    }
+
+    #[test]
+    fn matches_executable() {
+        let runner = RunnerDefinition::google();
+
+        assert!(runner.matches("/usr/local/bin/schematics"));
+        assert!(!runner.matches("/usr/local/bin/fnd"));
+        assert!(!RunnerDefinition::custom().matches("/usr/local/bin/fnd"));
+    }
+
+    #[test]
+    fn list_dir_skips_invalid() {
+        let dir = std::env::temp_dir().join("schematics-gui-runners");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("nx.toml"), "name = 'Nx'\nexecutable = 'nx'\n").unwrap();
+        std::fs::write(dir.join("broken.toml"), "name = ").unwrap();
+
+        let (runners, warnings) = RunnerUtils::list_dir(&dir);
+
+        assert_eq!(runners.len(), 1);
+        assert_eq!(runners[0].name, "Nx");
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].to_string().contains("broken.toml"));
+    }
+
+    #[test]
+    fn parse_definition() {
+        let runner: RunnerDefinition = toml::from_str(
+            "name = 'Nx'\nexecutable = 'nx'\ncommand = 'g {package}:{schematic}'\nboolean_style = 'value'\n",
+        )
+        .unwrap();
+
+        assert_eq!(runner.name, "Nx");
+        assert_eq!(runner.boolean_style, BooleanStyle::Value);
+        assert_eq!(runner.option_case, OptionCase::Kebab);
+        assert_eq!(runner.dry_run, None);
+    }
+}
@@ -3,12 +3,13 @@ use relm4::gtk::prelude::{
 };
 use relm4::gtk::EntryBuffer;
 use relm4::{gtk, ComponentParts, ComponentSender, SimpleComponent};
-use std::fs::write;
 use std::path::PathBuf;
-use toml::Table;
 
+use crate::error::{AppError, Result};
+use crate::file_utils::FileUtils;
 use crate::form_utils::FormUtils;
 use crate::impl_validation;
+use crate::profile_utils::ProfileUtils;
 use crate::schema_parsing::FsEntry;
 use crate::settings_utils::SettingsUtils;
 use crate::traits::Validator;
@@ -20,7 +21,9 @@ pub struct SaveDialogModel {
     hidden: bool,
     desc_buf: EntryBuffer,
     file_name_buf: EntryBuffer,
-    data: String,
+    data: Map<String, Value>,
+    extends: Option<String>,
+    fingerprint: String,
     schematic: String,
     package_name: String,
     success: bool,
@@ -30,7 +33,11 @@ pub struct SaveDialogModel {
 
 #[derive(Debug)]
 pub struct SaveDialogInputParams {
-    pub form_data: String,
+    pub form_data: Map<String, Value>,
+    /// Profile the saved one is layered on.
+    pub extends: Option<String>,
+    /// Fingerprint of the schema the form was built from.
+    pub fingerprint: String,
     pub schematic: String,
     pub package_name: String,
     pub file: Option<String>,
@@ -43,37 +50,39 @@ pub struct ProfileData {
     pub title: String,
     pub file: String,
     pub data: Map<String, Value>,
+    pub meta: Map<String, Value>,
 }
 
 impl ProfileData {
-    pub fn new(title: String, file: String, data: Map<String, Value>) -> Self {
-        ProfileData { title, file, data }
+    pub fn new(
+        title: String,
+        file: String,
+        data: Map<String, Value>,
+        meta: Map<String, Value>,
+    ) -> Self {
+        ProfileData {
+            title,
+            file,
+            data,
+            meta,
+        }
     }
 }
 
 impl_validation!(SaveDialogModel);
 
 impl SaveDialogModel {
-    fn write(&self) {
-        let toml = match format!(
-            "[meta]\ndescription='{}'\n[data]\n{}",
-            self.desc_buf.text(),
-            self.data
-        )
-        .parse::<Table>()
-        {
-            Ok(s) => s,
-            Err(err) => panic!("Could not parse TOML! {}", err),
-        };
-
-        let toml_str = toml::to_string_pretty(&toml).unwrap();
-        let dir = self.create_config_dir();
+    fn write(&self) -> Result<()> {
+        let dir = self.create_config_dir()?;
         let file = self.file_name_buf.text();
         let file_path = dir.join(format!("{}", file));
-        match write(file_path.as_os_str(), toml_str) {
-            Ok(s) => s,
-            Err(err) => panic!("Could not save file! {}", err),
-        }
+        let toml_str = ProfileUtils::to_toml(
+            &self.desc_buf.text(),
+            self.extends.as_deref(),
+            &self.fingerprint,
+            &self.data,
+        )?;
+        FileUtils::write_str(&file_path, &toml_str)
     }
 
     fn get_config_dir(&self) -> PathBuf {
@@ -83,18 +92,15 @@ impl SaveDialogModel {
     }
 
     fn get_config_dir_as_string(&self) -> String {
-        String::from(self.create_config_dir().to_str().unwrap())
+        self.get_config_dir().to_string_lossy().to_string()
     }
 
-    fn create_config_dir(&self) -> PathBuf {
+    fn create_config_dir(&self) -> Result<PathBuf> {
         let config_dir: PathBuf = self.get_config_dir();
         if !config_dir.exists() {
-            match std::fs::create_dir_all(&config_dir) {
-                Ok(s) => s,
-                Err(err) => panic!("Could not create settings dir! {}", err),
-            }
+            std::fs::create_dir_all(&config_dir).map_err(|err| AppError::io(&config_dir, err))?;
         }
-        config_dir
+        Ok(config_dir)
     }
 
     fn reset_form(&mut self) {
@@ -221,7 +227,9 @@ impl SimpleComponent for SaveDialogModel {
     ) -> ComponentParts<Self> {
         let model = SaveDialogModel {
             hidden: true,
-            data: String::default(),
+            data: Map::new(),
+            extends: None,
+            fingerprint: String::default(),
             desc_buf: EntryBuffer::default(),
             file_name_buf: EntryBuffer::default(),
             schematic: String::default(),
@@ -239,6 +247,8 @@ impl SimpleComponent for SaveDialogModel {
         match message {
             SaveDialogInput::Show(data) => {
                 self.data = data.form_data;
+                self.extends = data.extends;
+                self.fingerprint = data.fingerprint;
                 self.set_schematic(data.schematic);
                 self.set_package_name(data.package_name);
                 self.file_name_buf
@@ -246,6 +256,12 @@ impl SimpleComponent for SaveDialogModel {
                 self.desc_buf
                     .set_text(data.description.unwrap_or(String::default()));
 
+                if let Err(err) = self.create_config_dir() {
+                    self.print_error(&err.to_string());
+                    self.hidden = false;
+                    return;
+                }
+
                 if !data.auto_save {
                     self.hidden = false;
                 } else {
@@ -253,13 +269,19 @@ impl SimpleComponent for SaveDialogModel {
                 }
             }
             SaveDialogInput::Apply => {
+                // An auto save runs hidden, the dialog is shown for the error.
                 if !self.validate() {
+                    self.hidden = false;
                     return;
                 }
 
                 self.set_error(false);
 
-                self.write();
+                if let Err(err) = self.write() {
+                    self.print_error(&err.to_string());
+                    self.hidden = false;
+                    return;
+                }
                 sender
                     .output(SaveDialogOutput::Apply(
                         self.file_name_buf.text().to_string(),
@@ -0,0 +1,420 @@
+use serde_json::{Map, Value};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::directives::DirectiveRegistry;
+use crate::error::Result;
+use crate::file_utils::FileUtils;
+use crate::schema_parsing::{XWidget, XWidgetType};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub property: String,
+    pub message: String,
+}
+
+impl Diagnostic {
+    fn error(property: &str, message: String) -> Self {
+        Diagnostic {
+            severity: Severity::Error,
+            property: property.to_string(),
+            message,
+        }
+    }
+
+    fn warning(property: &str, message: String) -> Self {
+        Diagnostic {
+            severity: Severity::Warning,
+            property: property.to_string(),
+            message,
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}: {}", self.severity, self.property, self.message)
+    }
+}
+
+/// Reports the `x-widget` and `x-prompt` customizations the UI would silently ignore.
+///
+/// The widget options are checked by deserializing them the same way the UI does and comparing
+/// the result with the input, so the rules can't drift from the widget builders.
+pub struct SchemaLint {}
+
+impl SchemaLint {
+    pub fn lint(schema: &Value) -> Vec<Diagnostic> {
+        let mut result: Vec<Diagnostic> = vec![];
+
+        if let Some(properties) = schema["properties"].as_object() {
+            for (name, prop) in properties {
+                result.extend(Self::lint_widget(name, prop));
+                result.extend(Self::lint_directive(name, prop));
+            }
+        }
+        result
+    }
+
+    pub fn lint_file(path: &Path) -> Result<Vec<Diagnostic>> {
+        Ok(Self::lint(&FileUtils::read_json(path)?))
+    }
+
+    pub fn has_errors(diagnostics: &[Diagnostic]) -> bool {
+        diagnostics.iter().any(|d| d.severity == Severity::Error)
+    }
+
+    /// Absolute schema paths of the schematics listed in a `collection.json`.
+    pub fn get_schema_paths(collection: &Path) -> Result<Vec<PathBuf>> {
+        let data: Value = FileUtils::read_json(collection)?;
+        let dir = collection.parent().unwrap_or(Path::new("/"));
+
+        Ok(data["schematics"]
+            .as_object()
+            .map(|schematics| {
+                schematics
+                    .values()
+                    .filter_map(|s| s["schema"].as_str())
+                    .map(|schema| dir.join(schema))
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+
+    fn get_widget_defaults() -> Map<String, Value> {
+        XWidgetType::defaults()
+            .into_iter()
+            .filter_map(|w| match serde_json::to_value(w) {
+                Ok(Value::Object(map)) => Some(map),
+                _ => None,
+            })
+            .flatten()
+            .collect()
+    }
+
+    fn get_property_types(widget: &str) -> Vec<&'static str> {
+        match widget {
+            "numeric" => vec!["number", "integer"],
+            "choice" => vec!["boolean"],
+            "file" | "dir" | "menu" => vec!["string", "array"],
+            _ => vec!["string"],
+        }
+    }
+
+    fn lint_widget(name: &str, prop: &Value) -> Vec<Diagnostic> {
+        let mut result: Vec<Diagnostic> = vec![];
+        let xwidget = match prop.get("x-widget") {
+            Some(xwidget) => xwidget,
+            None => return result,
+        };
+        let widget = match xwidget.as_object() {
+            Some(map) if map.len() == 1 => map.iter().next().unwrap(),
+            _ => {
+                result.push(Diagnostic::error(
+                    name,
+                    String::from("x-widget must be an object with exactly one widget type"),
+                ));
+                return result;
+            }
+        };
+        let (widget_type, options) = widget;
+        let defaults = Self::get_widget_defaults();
+
+        let default_options = match defaults.get(widget_type) {
+            Some(default_options) => default_options,
+            None => {
+                result.push(Diagnostic::error(
+                    name,
+                    format!(
+                        "unknown widget type `{}`{}",
+                        widget_type,
+                        Self::get_suggestion(widget_type, defaults.keys())
+                    ),
+                ));
+                return result;
+            }
+        };
+
+        if let Some(prop_type) = prop["type"].as_str() {
+            let types = Self::get_property_types(widget_type);
+            if !types.contains(&prop_type) {
+                result.push(Diagnostic::error(
+                    name,
+                    format!(
+                        "`{}` widget can't be used with a `{}` property, expected {}",
+                        widget_type,
+                        prop_type,
+                        types.join(" or ")
+                    ),
+                ));
+            }
+        }
+
+        if widget_type == "menu" && !Self::has_items(prop) {
+            result.push(Diagnostic::warning(
+                name,
+                String::from(
+                    "`menu` widget has no items, add an `enum`, `x-prompt.items` or a `source`",
+                ),
+            ));
+        }
+
+        let options = match options.as_object() {
+            Some(options) => options,
+            None => {
+                result.push(Diagnostic::error(
+                    name,
+                    format!("options of the `{}` widget must be an object", widget_type),
+                ));
+                return result;
+            }
+        };
+        let default_options = default_options.as_object().unwrap();
+        let parsed = serde_json::from_value::<XWidget>(xwidget.clone())
+            .ok()
+            .and_then(|w| serde_json::to_value(w).ok())
+            .unwrap_or_default();
+
+        for (key, value) in options {
+            if !default_options.contains_key(key) {
+                result.push(Diagnostic::warning(
+                    name,
+                    format!(
+                        "unknown `{}` option `{}`{}",
+                        widget_type,
+                        key,
+                        Self::get_suggestion(key, default_options.keys())
+                    ),
+                ));
+                continue;
+            }
+
+            let used = &parsed[widget_type][key];
+            if !Self::is_equivalent(value, used) {
+                result.push(Diagnostic::error(
+                    name,
+                    format!(
+                        "invalid value {} for `{}`, {} is used instead",
+                        value, key, used
+                    ),
+                ));
+            }
+        }
+        result
+    }
+
+    fn lint_directive(name: &str, prop: &Value) -> Vec<Diagnostic> {
+        let items = prop["x-prompt"]["items"].as_str().unwrap_or_default();
+        if !items.starts_with('$') {
+            return vec![];
+        }
+
+        if DirectiveRegistry::default().is_known(items) {
+            return vec![];
+        }
+        vec![Diagnostic::error(
+            name,
+            format!("unknown directive `{}`", items),
+        )]
+    }
+
+    fn has_items(prop: &Value) -> bool {
+        prop["enum"].is_array()
+            || !prop["x-prompt"]["items"].is_null()
+            || !prop["x-widget"]["menu"]["source"].is_null()
+    }
+
+    /// Compares numbers by value and only the keys present in `input`.
+    fn is_equivalent(input: &Value, used: &Value) -> bool {
+        match (input, used) {
+            (Value::Number(a), Value::Number(b)) => a.as_f64() == b.as_f64(),
+            (Value::Array(a), Value::Array(b)) => {
+                a.len() == b.len() && a.iter().zip(b).all(|(a, b)| Self::is_equivalent(a, b))
+            }
+            (Value::Object(a), Value::Object(b)) => a
+                .iter()
+                .all(|(k, v)| Self::is_equivalent(v, b.get(k).unwrap_or(&Value::Null))),
+            _ => input == used,
+        }
+    }
+
+    fn get_suggestion<'a>(key: &str, candidates: impl Iterator<Item = &'a String>) -> String {
+        candidates
+            .map(|c| (Self::get_distance(key, c), c))
+            .filter(|(distance, _)| *distance <= 2)
+            .min()
+            .map(|(_, c)| format!(", did you mean `{}`?", c))
+            .unwrap_or_default()
+    }
+
+    /// Levenshtein distance, good enough for spotting typos in option names.
+    fn get_distance(a: &str, b: &str) -> usize {
+        let b: Vec<char> = b.chars().collect();
+        let mut row: Vec<usize> = (0..=b.len()).collect();
+
+        for (i, ca) in a.chars().enumerate() {
+            let mut previous = row[0];
+            row[0] = i + 1;
+            for (j, cb) in b.iter().enumerate() {
+                let current = row[j + 1];
+                row[j + 1] = if ca == *cb {
+                    previous
+                } else {
+                    1 + previous.min(row[j]).min(current)
+                };
+                previous = current;
+            }
+        }
+        row[b.len()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn lint_prop(prop: Value) -> Vec<Diagnostic> {
+        SchemaLint::lint(&json!({ "properties": { "foo": prop } }))
+    }
+
+    #[test]
+    fn lint_valid() {
+        let result = lint_prop(json!({
+            "type": "number",
+            "x-widget": { "numeric": { "type": "slider", "max": 10, "marks": [{ "value": 1, "text": "one" }] } }
+        }));
+
+        assert_eq!(result, vec![]);
+    }
+
+    #[test]
+    fn lint_unknown_option() {
+        let result = lint_prop(json!({
+            "type": "number",
+            "x-widget": { "numeric": { "tpye": "slider" } }
+        }));
+
+        assert_eq!(
+            result,
+            vec![Diagnostic::warning(
+                "foo",
+                String::from("unknown `numeric` option `tpye`, did you mean `type`?")
+            )]
+        );
+    }
+
+    #[test]
+    fn lint_invalid_enum_value() {
+        let result = lint_prop(json!({
+            "type": "string",
+            "x-widget": { "date": { "type": "date-time" } }
+        }));
+
+        assert_eq!(
+            result,
+            vec![Diagnostic::error(
+                "foo",
+                String::from("invalid value \"date-time\" for `type`, \"date\" is used instead")
+            )]
+        );
+    }
+
+    #[test]
+    fn lint_type_mismatch() {
+        let result = lint_prop(json!({
+            "type": "string",
+            "x-widget": { "numeric": {} }
+        }));
+
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].severity, Severity::Error);
+        assert!(result[0].message.contains("`string` property"));
+    }
+
+    #[test]
+    fn lint_unknown_widget() {
+        let result = lint_prop(json!({
+            "type": "string",
+            "x-widget": { "txt": {} }
+        }));
+
+        assert_eq!(
+            result[0].message,
+            "unknown widget type `txt`, did you mean `text`?"
+        );
+    }
+
+    #[test]
+    fn lint_menu_without_items() {
+        let result = lint_prop(json!({
+            "type": "string",
+            "x-widget": { "menu": {} }
+        }));
+
+        assert_eq!(result[0].severity, Severity::Warning);
+    }
+
+    #[test]
+    fn lint_menu_with_source() {
+        let valid = lint_prop(json!({
+            "type": "string",
+            "x-widget": { "menu": { "source": { "command": "nx", "args": ["show", "projects"] } } }
+        }));
+        let invalid = lint_prop(json!({
+            "type": "string",
+            "x-widget": { "menu": { "source": { "command": "nx", "timeout": "1m" } } }
+        }));
+
+        assert!(valid.is_empty());
+        assert_eq!(invalid[0].severity, Severity::Error);
+    }
+
+    #[test]
+    fn lint_directives() {
+        let known = lint_prop(json!({
+            "type": "string",
+            "x-prompt": { "type": "list", "message": "Dir", "items": "$dir:/src" }
+        }));
+        let unknown = lint_prop(json!({
+            "type": "string",
+            "x-prompt": { "type": "list", "message": "Dir", "items": "$modles" }
+        }));
+
+        assert_eq!(known, vec![]);
+        assert_eq!(
+            unknown,
+            vec![Diagnostic::error(
+                "foo",
+                String::from("unknown directive `$modles`")
+            )]
+        );
+    }
+
+    #[test]
+    fn has_errors_ignores_warnings() {
+        let warnings = vec![Diagnostic::warning("foo", String::from("bar"))];
+
+        assert!(!SchemaLint::has_errors(&warnings));
+        assert!(SchemaLint::has_errors(&[Diagnostic::error(
+            "foo",
+            String::from("bar")
+        )]));
+    }
+}
@@ -1,4 +1,3 @@
-use regex::Regex;
 use relm4::gtk::{
     glib::{DateTime, TimeZone},
     InputHints, InputPurpose, Justification, Orientation, PositionType,
@@ -8,7 +7,8 @@ use serde_json::Map;
 use serde_with::{serde_as, DefaultOnError};
 use std::{fmt, path::Path};
 
-use crate::file_utils::FileUtils;
+use crate::directives::DirectiveRegistry;
+use crate::workspace_utils::Workspace;
 
 #[serde_as]
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -34,6 +34,20 @@ impl Schema {
         None
     }
 
+    /// Names of the properties passed as positional arguments, in order.
+    pub fn get_positional(&self) -> Vec<String> {
+        let mut positional: Vec<(usize, String)> = self
+            .properties
+            .iter()
+            .filter_map(|(key, value)| {
+                let prop = serde_json::from_value::<SchemaProp>(value.clone()).ok()?;
+                prop.get_position().map(|index| (index, key.clone()))
+            })
+            .collect();
+        positional.sort();
+        positional.into_iter().map(|(_, key)| key).collect()
+    }
+
     pub fn has_directives(&self) -> bool {
         self.properties.iter().any(|p| {
             let obj = p.1.as_object().unwrap();
@@ -50,7 +64,9 @@ impl Schema {
 #[serde_as]
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SchemaProp {
+    #[serde(default)]
     pub r#type: String,
+    pub title: Option<String>,
     pub description: Option<String>,
     pub default: Option<Primitive>,
     pub alias: Option<String>,
@@ -61,6 +77,252 @@ pub struct SchemaProp {
     pub x_prompt: Option<StringOrPrompt>,
     #[serde(alias = "x-widget")]
     pub x_widget: Option<XWidget>,
+    #[serde(alias = "additionalProperties")]
+    pub additional_properties: Option<serde_json::Value>,
+    pub r#const: Option<Primitive>,
+    #[serde(alias = "$default")]
+    pub source_default: Option<SourceDefault>,
+    pub minimum: Option<f64>,
+    pub maximum: Option<f64>,
+    /// A number (draft 6+) or a flag making `minimum` exclusive (draft 4).
+    #[serde(alias = "exclusiveMinimum")]
+    pub exclusive_minimum: Option<serde_json::Value>,
+    #[serde(alias = "exclusiveMaximum")]
+    pub exclusive_maximum: Option<serde_json::Value>,
+    #[serde(alias = "multipleOf")]
+    pub multiple_of: Option<f64>,
+    #[serde(alias = "oneOf")]
+    pub one_of: Option<Vec<SchemaProp>>,
+    #[serde(alias = "anyOf")]
+    pub any_of: Option<Vec<SchemaProp>>,
+}
+
+impl SchemaProp {
+    fn get_variants(&self) -> &[SchemaProp] {
+        self.one_of
+            .as_deref()
+            .or(self.any_of.as_deref())
+            .unwrap_or_default()
+    }
+
+    /// `oneOf`/`anyOf` made of constants, as (title, value) pairs.
+    pub fn get_choices(&self) -> Option<Vec<(String, String)>> {
+        let variants = self.get_variants();
+        if variants.is_empty() || variants.iter().any(|v| v.r#const.is_none()) {
+            return None;
+        }
+
+        Some(
+            variants
+                .iter()
+                .map(|v| {
+                    let value = v.r#const.as_ref().unwrap().to_string();
+                    (v.title.clone().unwrap_or(value.clone()), value)
+                })
+                .collect(),
+        )
+    }
+
+    /// `oneOf`/`anyOf` of different types, e.g.: `boolean` or `string`. The default of the
+    /// property is handed to the variant of the matching type.
+    pub fn get_type_variants(&self) -> Option<Vec<SchemaProp>> {
+        let variants = self.get_variants();
+        if variants.is_empty() || !variants.iter().all(|v| v.is_supported()) {
+            return None;
+        }
+
+        Some(
+            variants
+                .iter()
+                .map(|v| SchemaProp {
+                    default: match &self.default {
+                        Some(d) if d.is_type(&v.r#type) => Some(d.clone()),
+                        _ => v.default.clone(),
+                    },
+                    ..v.clone()
+                })
+                .collect(),
+        )
+    }
+
+    /// Whether the widget builders can render this property, the rest get a JSON editor.
+    pub fn is_supported(&self) -> bool {
+        if self.get_choices().is_some() || self.get_type_variants().is_some() {
+            return true;
+        }
+        match self.r#type.as_str() {
+            "string" | "boolean" | "number" => true,
+            "integer" => !self.has_fractional_step(),
+            "array" => matches!(
+                self.items.as_ref().map(|i| i.r#type.as_str()),
+                None | Some("") | Some("string")
+            ),
+            "object" => self.is_map(),
+            _ => false,
+        }
+    }
+
+    /// Index of the positional argument set by `"$default": { "$source": "argv" }`.
+    pub fn get_position(&self) -> Option<usize> {
+        self.source_default
+            .as_ref()
+            .filter(|d| d.source == "argv")
+            .map(|d| d.index.unwrap_or_default())
+    }
+
+    /// The `--project` option, or any other filled in from the `projectName` source.
+    fn is_project(&self, name: &str) -> bool {
+        name == "project"
+            || self
+                .source_default
+                .as_ref()
+                .is_some_and(|d| d.source == "projectName")
+    }
+
+    /// Applies what the workspace in the cwd knows about the property: the schematic `defaults`
+    /// configured there, the default project and its prefix, and the projects as choices.
+    pub fn with_workspace(
+        self,
+        name: &str,
+        workspace: &Workspace,
+        defaults: &Map<String, serde_json::Value>,
+    ) -> SchemaProp {
+        let mut prop = self;
+        let default_project = workspace.get_default_project();
+
+        if let Some(value) = defaults.get(name) {
+            prop.default = serde_json::from_value(value.clone()).ok();
+        }
+        if prop.is_project(name) && prop.r#type == "string" {
+            let names = workspace.get_project_names();
+            let has_items = prop.x_prompt.as_ref().is_some_and(|p| p.has_items());
+
+            if prop.default.is_none() {
+                prop.default = default_project.clone().map(Primitive::Str);
+            }
+            if prop.r#enum.is_none() && !has_items && !names.is_empty() {
+                prop.r#enum = Some(names);
+            }
+        }
+        if name == "prefix" && prop.default.is_none() {
+            prop.default = default_project
+                .and_then(|p| workspace.get_project(&p)?.prefix.clone())
+                .map(Primitive::Str);
+        }
+        prop
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.r#type == "integer"
+    }
+
+    /// An integer property with a `multipleOf` that isn't whole, no integer input can honour it.
+    pub fn has_fractional_step(&self) -> bool {
+        self.is_integer() && self.multiple_of.is_some_and(|s| s.fract() != 0.0)
+    }
+
+    fn get_bound(bound: Option<f64>, exclusive: &Option<serde_json::Value>) -> Option<(f64, bool)> {
+        match (exclusive, bound) {
+            (Some(serde_json::Value::Number(n)), _) => n.as_f64().map(|n| (n, true)),
+            (Some(serde_json::Value::Bool(true)), Some(b)) => Some((b, true)),
+            (_, b) => b.map(|b| (b, false)),
+        }
+    }
+
+    /// Spin button options honouring the type, bounds and `multipleOf` of the property.
+    pub fn get_numeric_entry(&self) -> NumericEntry {
+        let defaults = NumericEntry::default();
+        let step = self.multiple_of.filter(|s| *s > 0.0);
+        // bounds are moved inwards to the closest allowed value, spinning counts steps from
+        // the lower bound so it has to be a multiple as well
+        let unit = step.unwrap_or(match self.is_integer() {
+            true => 1.0,
+            false => 10f64.powi(-i32::from(defaults.precision.clone())),
+        });
+        let snap = step.is_some() || self.is_integer();
+        let min =
+            Self::get_bound(self.minimum, &self.exclusive_minimum).map(|(n, exclusive)| {
+                match (exclusive, snap) {
+                    (true, _) => ((n / unit).floor() + 1.0) * unit,
+                    (false, true) => (n / unit).ceil() * unit,
+                    (false, false) => n,
+                }
+            });
+        let max =
+            Self::get_bound(self.maximum, &self.exclusive_maximum).map(|(n, exclusive)| {
+                match (exclusive, snap) {
+                    (true, _) => ((n / unit).ceil() - 1.0) * unit,
+                    (false, true) => (n / unit).floor() * unit,
+                    (false, false) => n,
+                }
+            });
+
+        if self.is_integer() {
+            NumericEntry {
+                value_type: NumericValueType::Int,
+                stepping: IntOrFloat::Int(step.map_or(1, |s| s as i64)),
+                min: IntOrFloat::Int(min.map_or(i64::MIN, |m| m as i64)),
+                max: IntOrFloat::Int(max.map_or(i64::MAX, |m| m as i64)),
+                precision: IntOrFloat::Int(0),
+                page_size: IntOrFloat::Int(0),
+                ..defaults
+            }
+        } else {
+            NumericEntry {
+                stepping: step.map_or(defaults.stepping.clone(), IntOrFloat::Float),
+                precision: step.map_or(defaults.precision.clone(), |s| {
+                    IntOrFloat::Int(get_decimals(s).into())
+                }),
+                min: min.map_or(defaults.min.clone(), IntOrFloat::Float),
+                max: max.map_or(defaults.max.clone(), IntOrFloat::Float),
+                page_size: IntOrFloat::Float(0.0),
+                ..defaults
+            }
+        }
+    }
+
+    /// Choices of a menu: the `enum`, the `x-prompt.items` or the items of their directive.
+    /// Directives need the cwd, a placeholder is shown until it's set.
+    pub fn get_items(&self, cwd: Option<&str>, registry: &DirectiveRegistry) -> Vec<String> {
+        if self.r#type == "string" && self.r#enum.is_some() {
+            return self.r#enum.clone().unwrap();
+        }
+        let prompt = match &self.x_prompt {
+            Some(prompt)
+                if prompt.has_items() && (self.r#type == "array" || self.r#type == "string") =>
+            {
+                prompt
+            }
+            _ => return vec![],
+        };
+
+        match (prompt.get_directive(), cwd) {
+            (Some(directive), Some(cwd)) if registry.is_known(directive) => registry
+                .get_items(directive, Path::new(cwd))
+                .unwrap_or_default(),
+            (Some(directive), None) if registry.is_known(directive) => {
+                prompt.get_items_placeholder()
+            }
+            _ => prompt.get_items(),
+        }
+    }
+
+    /// Objects with free-form keys, e.g.: `--define` or `--env`.
+    pub fn is_map(&self) -> bool {
+        self.r#type == "object"
+            && self
+                .additional_properties
+                .as_ref()
+                .is_some_and(|p| p.is_object() || p.as_bool() == Some(true))
+    }
+}
+
+/// Value provided by the CLI instead of the schema, e.g.: the positional `name` of a component.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SourceDefault {
+    #[serde(alias = "$source")]
+    pub source: String,
+    pub index: Option<usize>,
 }
 
 #[serde_as]
@@ -91,6 +353,22 @@ impl Default for XWidgetType {
     }
 }
 
+impl XWidgetType {
+    /// Every widget type with its default options.
+    pub fn defaults() -> Vec<XWidgetType> {
+        vec![
+            XWidgetType::Text(TextEntry::default()),
+            XWidgetType::Numeric(NumericEntry::default()),
+            XWidgetType::Date(DateEntry::default()),
+            XWidgetType::Color(ColorEntry::default()),
+            XWidgetType::File(FsEntry::default()),
+            XWidgetType::Dir(FsEntry::default()),
+            XWidgetType::Choice(ChoiceEntry::default()),
+            XWidgetType::Menu(MenuEntry::default()),
+        ]
+    }
+}
+
 #[serde_as]
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(default)]
@@ -256,6 +534,9 @@ pub struct MenuEntry {
     pub multichoice: bool,
     #[serde_as(deserialize_as = "DefaultOnError")]
     pub orientation: OrientationType,
+    /// Command printing the items, instead of the `enum` or `x-prompt.items`.
+    #[serde_as(deserialize_as = "DefaultOnError")]
+    pub source: Option<CommandSource>,
 }
 
 impl Default for MenuEntry {
@@ -265,10 +546,55 @@ impl Default for MenuEntry {
             multichoice: false,
             searchable: false,
             orientation: OrientationType::Vertical,
+            source: None,
+        }
+    }
+}
+
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CommandSource {
+    pub command: String,
+    pub args: Vec<String>,
+    #[serde_as(deserialize_as = "DefaultOnError")]
+    pub format: SourceFormat,
+    /// Seconds to wait for the command before it's killed.
+    pub timeout: u64,
+    /// Seconds the items are reused for, `0` runs the command every time.
+    pub cache: u64,
+}
+
+impl Default for CommandSource {
+    fn default() -> Self {
+        CommandSource {
+            command: String::default(),
+            args: vec![],
+            format: SourceFormat::Lines,
+            timeout: 10,
+            cache: 300,
         }
     }
 }
 
+impl fmt::Display for CommandSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut parts = vec![self.command.as_str()];
+        parts.extend(self.args.iter().map(String::as_str));
+        write!(f, "{}", parts.join(" "))
+    }
+}
+
+#[derive(Default, Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SourceFormat {
+    /// One item per line.
+    #[default]
+    Lines,
+    /// An array, items other than strings are used as JSON.
+    Json,
+}
+
 #[derive(Default, Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "lowercase")]
 pub enum MenuType {
@@ -519,6 +845,31 @@ impl Default for NumericEntry {
     }
 }
 
+impl NumericEntry {
+    /// Decimals shown for floats: the ones of the default or of the stepping if it has more,
+    /// the precision without a default.
+    pub fn get_digits(&self, default: &Option<Primitive>) -> i32 {
+        if self.value_type != NumericValueType::Float {
+            return 0;
+        }
+        match default {
+            Some(default) => {
+                let default: String = default.clone().into();
+                let decimals = default.split_once('.').map_or(0, |(_, d)| d.len() as i32);
+                decimals.max(get_decimals(self.stepping.clone().into()))
+            }
+            None => self.precision.clone().into(),
+        }
+    }
+}
+
+/// Decimals of a number, e.g.: 3 for `0.125`.
+fn get_decimals(n: f64) -> i32 {
+    n.to_string()
+        .split_once('.')
+        .map_or(0, |(_, d)| d.len() as i32)
+}
+
 #[serde_as]
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "snake_case")]
@@ -526,6 +877,7 @@ pub struct MarkData {
     #[serde_as(deserialize_as = "DefaultOnError")]
     pub value: f64,
     #[serde_as(deserialize_as = "DefaultOnError")]
+    #[serde(default)]
     pub text: Option<String>,
 }
 
@@ -564,7 +916,7 @@ pub enum NumericType {
 #[serde(rename_all = "snake_case")]
 #[serde(untagged)]
 pub enum IntOrFloat {
-    Int(i32),
+    Int(i64),
     Float(f64),
 }
 
@@ -587,7 +939,9 @@ impl From<IntOrFloat> for i32 {
     fn from(val: IntOrFloat) -> Self {
         match val {
             IntOrFloat::Float(f) => f as i32,
-            IntOrFloat::Int(i) => i,
+            IntOrFloat::Int(i) => {
+                i32::try_from(i).unwrap_or(if i < 0 { i32::MIN } else { i32::MAX })
+            }
         }
     }
 }
@@ -596,7 +950,7 @@ impl From<IntOrFloat> for u32 {
     fn from(val: IntOrFloat) -> Self {
         match val {
             IntOrFloat::Float(f) => f as u32,
-            IntOrFloat::Int(i) => i as u32,
+            IntOrFloat::Int(i) => u32::try_from(i).unwrap_or(if i < 0 { 0 } else { u32::MAX }),
         }
     }
 }
@@ -629,7 +983,8 @@ pub struct SchemaItem {
 }
 
 #[serde_as]
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct SchemaPropItem {
     #[serde_as(deserialize_as = "DefaultOnError")]
     pub r#enum: Vec<String>,
@@ -659,7 +1014,7 @@ impl XPrompt {
 #[serde(untagged)]
 pub enum Primitive {
     Str(String),
-    Int(i32),
+    Int(i64),
     Float(f64),
     Bool(bool),
     StringVec(Vec<String>),
@@ -667,6 +1022,20 @@ pub enum Primitive {
     Unknown(serde_json::Value),
 }
 
+impl Primitive {
+    /// Whether the value fits a JSON Schema `type`.
+    pub fn is_type(&self, r#type: &str) -> bool {
+        matches!(
+            (self, r#type),
+            (Primitive::Str(_), "string")
+                | (Primitive::Bool(_), "boolean")
+                | (Primitive::Int(_), "number" | "integer")
+                | (Primitive::Float(_), "number")
+                | (Primitive::StringVec(_), "array")
+        )
+    }
+}
+
 impl fmt::Display for Primitive {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let s = match self {
@@ -734,7 +1103,9 @@ impl From<Primitive> for f64 {
 impl From<Primitive> for i32 {
     fn from(val: Primitive) -> Self {
         match val {
-            Primitive::Int(i) => i,
+            Primitive::Int(i) => {
+                i32::try_from(i).unwrap_or(if i < 0 { i32::MIN } else { i32::MAX })
+            }
             Primitive::Float(f) => f as i32,
             _ => 0,
         }
@@ -744,7 +1115,7 @@ impl From<Primitive> for i32 {
 impl From<Primitive> for u32 {
     fn from(val: Primitive) -> Self {
         match val {
-            Primitive::Int(i) => i as u32,
+            Primitive::Int(i) => u32::try_from(i).unwrap_or(if i < 0 { 0 } else { u32::MAX }),
             Primitive::Float(f) => f as u32,
             _ => 0,
         }
@@ -784,21 +1155,6 @@ pub enum StringOrPrompt {
 }
 
 impl StringOrPrompt {
-    fn has_directive(&self, directive: &str, pattern: Option<Regex>) -> bool {
-        match self {
-            StringOrPrompt::Prompt(x) => {
-                let items = x.items.clone();
-                if let Some(items_values) = items {
-                    return items_values.clone() == VecOrString::Str(directive.to_string())
-                        || (pattern.is_some()
-                            && pattern.unwrap().is_match(&String::from(items_values)));
-                }
-                false
-            }
-            _ => false,
-        }
-    }
-
     pub fn has_multiselect(&self) -> bool {
         match self {
             StringOrPrompt::Prompt(x) => x.has_multiselect(),
@@ -806,40 +1162,21 @@ impl StringOrPrompt {
         }
     }
 
-    pub fn has_modules(&self) -> bool {
-        match self {
-            StringOrPrompt::Prompt(_x) => self.has_directive("$modules", None),
-            _ => false,
-        }
-    }
-
-    pub fn has_models(&self) -> bool {
-        match self {
-            StringOrPrompt::Prompt(_x) => self.has_directive("$models", None),
-            _ => false,
-        }
-    }
-
-    pub fn has_dirs(&self) -> bool {
-        let pattern = Regex::new(r"\$dir:([a-zA-Z0-9\/\-]+)").unwrap();
-        match self {
-            StringOrPrompt::Prompt(_x) => self.has_directive("$dir", Some(pattern)),
-            _ => false,
-        }
-    }
-
-    pub fn has_files(&self) -> bool {
-        let pattern = Regex::new(r"\$files:([a-zA-Z0-9\/\-]+)").unwrap();
+    pub fn has_items(&self) -> bool {
         match self {
-            StringOrPrompt::Prompt(_x) => self.has_directive("$files", Some(pattern)),
+            StringOrPrompt::Prompt(x) => x.has_items(),
             _ => false,
         }
     }
 
-    pub fn has_items(&self) -> bool {
+    /// The `items` if they are a directive, e.g.: `$modules`.
+    pub fn get_directive(&self) -> Option<&str> {
         match self {
-            StringOrPrompt::Prompt(x) => x.has_items(),
-            _ => false,
+            StringOrPrompt::Prompt(XPrompt {
+                items: Some(VecOrString::Str(items)),
+                ..
+            }) if items.starts_with('$') => Some(items),
+            _ => None,
         }
     }
 
@@ -847,69 +1184,6 @@ impl StringOrPrompt {
         vec!["Select current working directory!".to_string()]
     }
 
-    pub fn get_modules(&self, dir: &str) -> Vec<String> {
-        let path = Path::new(dir).join("src");
-        let mut result: Vec<String> = FileUtils::read_fs_entries(&path, true)
-            .iter()
-            .map(|p| p.file_name().unwrap().to_str().unwrap().to_owned())
-            .collect();
-
-        result.sort();
-        result
-    }
-
-    pub fn get_dirs_or_files(&self, is_dirs: bool, cwd: &str) -> Vec<String> {
-        let dirs = &self.get_items()[0];
-        let re = Regex::new(if is_dirs {
-            r"\$dir:([a-zA-Z0-9\/\-]+)"
-        } else {
-            r"\$files:([a-zA-Z0-9\/\-]+)"
-        })
-        .unwrap()
-        .captures(dirs);
-        let subdir = if let Some(re) = re {
-            re.get(1).unwrap().as_str()
-        } else {
-            "/"
-        };
-
-        let mut path = Path::new(cwd).join(subdir);
-
-        if !path.exists() {
-            path = Path::new(cwd).to_path_buf();
-        }
-        let mut result: Vec<String> = FileUtils::read_fs_entries(&path, is_dirs)
-            .iter()
-            .map(|p| p.file_name().unwrap().to_str().unwrap().to_owned())
-            .collect();
-
-        result.sort();
-        result
-    }
-
-    pub fn get_models(&self, dir: &str) -> Vec<String> {
-        let path = Path::new(dir).join("src");
-        let mut result: Vec<String> =
-            FileUtils::read_fs_entries_recursive(&path, &Some(vec!["models"]))
-                .unwrap()
-                .iter()
-                .filter_map(|p| {
-                    let path_str = p.to_str().unwrap_or_default();
-                    if path_str.ends_with("model.ts") {
-                        return Some(
-                            path_str
-                                .replace(path.to_str().unwrap_or_default(), "")
-                                .replace("model.ts", "model"),
-                        );
-                    }
-                    None
-                })
-                .collect();
-
-        result.sort();
-        result
-    }
-
     pub fn get_items(&self) -> Vec<String> {
         match self {
             StringOrPrompt::Prompt(x) => x.items.as_ref().unwrap().clone().into(),
@@ -949,3 +1223,237 @@ impl From<VecOrString> for String {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::workspace_utils::WorkspaceProject;
+    use serde_json::json;
+
+    fn get_prop(value: serde_json::Value) -> SchemaProp {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn get_choices_success() {
+        let prop = get_prop(json!({
+            "type": "string",
+            "oneOf": [{ "const": "css", "title": "CSS" }, { "const": "scss" }]
+        }));
+
+        assert_eq!(
+            prop.get_choices(),
+            Some(vec![
+                (String::from("CSS"), String::from("css")),
+                (String::from("scss"), String::from("scss")),
+            ])
+        );
+        assert!(prop.is_supported());
+    }
+
+    #[test]
+    fn get_type_variants_success() {
+        let prop = get_prop(json!({
+            "default": true,
+            "anyOf": [{ "type": "string" }, { "type": "boolean" }]
+        }));
+        let variants = prop.get_type_variants().unwrap();
+
+        assert_eq!(prop.get_choices(), None);
+        assert_eq!(variants[0].default, None);
+        assert_eq!(variants[1].default, Some(Primitive::Bool(true)));
+        assert!(prop.is_supported());
+    }
+
+    #[test]
+    fn get_type_variants_unsupported() {
+        let prop = get_prop(json!({
+            "anyOf": [{ "type": "string" }, { "type": "object" }]
+        }));
+
+        assert_eq!(prop.get_type_variants(), None);
+        assert!(!prop.is_supported());
+    }
+
+    #[test]
+    fn get_numeric_entry_integer() {
+        let prop = get_prop(json!({
+            "type": "integer",
+            "exclusiveMinimum": 2.5,
+            "maximum": 20,
+            "multipleOf": 4
+        }));
+        let entry = prop.get_numeric_entry();
+
+        assert!(prop.is_supported());
+        assert_eq!(entry.value_type, NumericValueType::Int);
+        assert_eq!(entry.precision, IntOrFloat::Int(0));
+        assert_eq!(entry.stepping, IntOrFloat::Int(4));
+        assert_eq!(entry.min, IntOrFloat::Int(4));
+        assert_eq!(entry.max, IntOrFloat::Int(20));
+    }
+
+    #[test]
+    fn int_conversions_saturate() {
+        assert_eq!(i32::from(IntOrFloat::Int(i64::MIN)), i32::MIN);
+        assert_eq!(i32::from(IntOrFloat::Int(i64::MAX)), i32::MAX);
+        assert_eq!(i32::from(IntOrFloat::Int(-4)), -4);
+        assert_eq!(u32::from(IntOrFloat::Int(-4)), 0);
+        assert_eq!(i32::from(Primitive::Int(5000000000)), i32::MAX);
+        assert_eq!(u32::from(Primitive::Int(5000000000)), u32::MAX);
+        assert_eq!(u32::from(Primitive::Int(20)), 20);
+    }
+
+    #[test]
+    fn get_numeric_entry_integer_range() {
+        let entry =
+            get_prop(json!({ "type": "integer", "default": 5000000000i64 })).get_numeric_entry();
+
+        assert_eq!(entry.min, IntOrFloat::Int(i64::MIN));
+        assert_eq!(entry.max, IntOrFloat::Int(i64::MAX));
+        assert_eq!(
+            get_prop(json!({ "default": 5000000000i64 })).default,
+            Some(Primitive::Int(5000000000))
+        );
+    }
+
+    #[test]
+    fn get_numeric_entry_number() {
+        let entry = get_prop(json!({
+            "type": "number",
+            "minimum": 1,
+            "exclusiveMaximum": 2,
+            "multipleOf": 0.25
+        }))
+        .get_numeric_entry();
+
+        assert_eq!(entry.value_type, NumericValueType::Float);
+        assert_eq!(entry.stepping, IntOrFloat::Float(0.25));
+        assert_eq!(entry.min, IntOrFloat::Float(1.0));
+        assert_eq!(entry.max, IntOrFloat::Float(1.75));
+    }
+
+    #[test]
+    fn get_numeric_entry_step_precision() {
+        let quarter = get_prop(json!({ "type": "number", "multipleOf": 0.25, "default": 1 }));
+        let eighth = get_prop(json!({ "type": "number", "multipleOf": 0.125 }));
+
+        assert_eq!(quarter.get_numeric_entry().get_digits(&quarter.default), 2);
+        assert_eq!(eighth.get_numeric_entry().precision, IntOrFloat::Int(3));
+        assert_eq!(eighth.get_numeric_entry().get_digits(&None), 3);
+        assert_eq!(
+            NumericEntry::default().get_digits(&Some(Primitive::Float(1.5))),
+            1
+        );
+    }
+
+    #[test]
+    fn get_numeric_entry_fractional_integer_step() {
+        let prop = get_prop(json!({ "type": "integer", "multipleOf": 0.5 }));
+
+        assert!(prop.has_fractional_step());
+        assert!(!prop.is_supported());
+        assert!(!get_prop(json!({ "type": "integer", "multipleOf": 2 })).has_fractional_step());
+    }
+
+    #[test]
+    fn get_numeric_entry_draft4_exclusive() {
+        let entry = get_prop(json!({
+            "type": "integer",
+            "minimum": 0,
+            "exclusiveMinimum": true
+        }))
+        .get_numeric_entry();
+
+        assert_eq!(entry.min, IntOrFloat::Int(1));
+    }
+
+    #[test]
+    fn get_positional_success() {
+        let schema: Schema = serde_json::from_value(json!({
+            "$schema": "http://json-schema.org/schema",
+            "$id": "component",
+            "title": "Component",
+            "type": "object",
+            "properties": {
+                "name": { "type": "string", "$default": { "$source": "argv", "index": 0 } },
+                "path": { "type": "string", "$default": { "$source": "argv", "index": 1 } },
+                "project": { "type": "string", "$default": { "$source": "projectName" } },
+                "flat": { "type": "boolean" }
+            }
+        }))
+        .unwrap();
+
+        assert_eq!(schema.get_positional(), vec!["name", "path"]);
+        assert_eq!(schema.get_property("path").unwrap().get_position(), Some(1));
+        assert_eq!(schema.get_property("project").unwrap().get_position(), None);
+    }
+
+    #[test]
+    fn with_workspace_project() {
+        let workspace = Workspace {
+            projects: vec![
+                WorkspaceProject {
+                    name: String::from("app"),
+                    prefix: Some(String::from("shop")),
+                    ..Default::default()
+                },
+                WorkspaceProject {
+                    name: String::from("lib"),
+                    ..Default::default()
+                },
+            ],
+            default_project: Some(String::from("app")),
+            ..Default::default()
+        };
+        let defaults = Map::new();
+        let project = get_prop(json!({
+            "type": "string",
+            "$default": { "$source": "projectName" }
+        }))
+        .with_workspace("project", &workspace, &defaults);
+        let prefix =
+            get_prop(json!({ "type": "string" })).with_workspace("prefix", &workspace, &defaults);
+
+        assert_eq!(project.default, Some(Primitive::Str(String::from("app"))));
+        assert_eq!(
+            project.r#enum,
+            Some(vec![String::from("app"), String::from("lib")])
+        );
+        assert_eq!(prefix.default, Some(Primitive::Str(String::from("shop"))));
+    }
+
+    #[test]
+    fn with_workspace_defaults() {
+        let workspace = Workspace::default();
+        let defaults = json!({ "style": "scss" }).as_object().unwrap().clone();
+        let prop = get_prop(json!({ "type": "string", "default": "css" }))
+            .with_workspace("style", &workspace, &defaults);
+
+        assert_eq!(prop.default, Some(Primitive::Str(String::from("scss"))));
+    }
+
+    #[test]
+    fn get_items_success() {
+        let registry = DirectiveRegistry::default();
+        let literal = get_prop(json!({
+            "type": "string",
+            "x-prompt": { "type": "list", "message": "Style", "items": ["css", "scss"] }
+        }));
+        let directive = get_prop(json!({
+            "type": "string",
+            "x-prompt": { "type": "list", "message": "Module", "items": "$modules" }
+        }));
+        let unknown = get_prop(json!({
+            "type": "string",
+            "x-prompt": { "type": "list", "message": "Module", "items": "$modles" }
+        }));
+
+        assert_eq!(literal.get_items(None, &registry), vec!["css", "scss"]);
+        assert_eq!(
+            directive.get_items(None, &registry),
+            vec!["Select current working directory!"]
+        );
+        assert_eq!(unknown.get_items(Some("/"), &registry), vec!["$modles"]);
+    }
+}
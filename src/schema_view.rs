@@ -1,24 +1,135 @@
 use std::path::PathBuf;
+use std::rc::Rc;
+use std::time::Duration;
 
-use relm4::gtk::prelude::{OrientableExt, TextBufferExt, TextViewExt, WidgetExt};
-use relm4::gtk::Orientation;
-use relm4::{gtk, ComponentParts, ComponentSender, SimpleComponent};
+use relm4::gtk::prelude::{
+    ButtonExt, FrameExt, OrientableExt, TextBufferExt, TextViewExt, ToggleButtonExt, WidgetExt,
+};
+use relm4::gtk::{glib, Orientation};
+use relm4::{
+    gtk, Component, ComponentController, ComponentParts, ComponentSender, Controller,
+    SimpleComponent,
+};
 use serde_json::Value;
 use sourceview5::prelude::ViewExt;
 use sourceview5::Buffer;
 
-use crate::schematics::Collection;
+use crate::directives::DirectiveRegistry;
+use crate::file_utils::FileUtils;
+use crate::schema_lint::{Diagnostic, SchemaLint};
+use crate::schematic_ui::SchematicUiModel;
 use crate::traits::JsonBuffer;
+use crate::xwidget_editor::{XWidgetEditorInput, XWidgetEditorModel, XWidgetEditorOutput};
 
+#[tracker::track]
 pub struct SchemaViewModel {
     hidden: bool,
+    #[no_eq]
     json: Buffer,
     title: String,
+    diagnostics: Vec<Diagnostic>,
+    designer: bool,
+    path: Option<PathBuf>,
+    error: Option<String>,
+    modified: bool,
+    /// Schema as last read from or written to disk, to tell whether the buffer is modified.
+    #[do_not_track]
+    saved: Option<Value>,
+    /// Counts the edits, only the preview of the last one within the delay is rendered.
+    #[do_not_track]
+    edits: u32,
+    #[do_not_track]
+    registry: Rc<DirectiveRegistry>,
+    #[no_eq]
+    preview: gtk::Frame,
+    #[no_eq]
+    inspector: Controller<XWidgetEditorModel>,
+}
+
+impl SchemaViewModel {
+    fn get_diagnostics_text(&self) -> String {
+        if self.diagnostics.is_empty() {
+            return String::from("No problems found.");
+        }
+        self.diagnostics
+            .iter()
+            .map(|d| d.to_string())
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    fn get_text(&self) -> String {
+        self.json
+            .text(&self.json.start_iter(), &self.json.end_iter(), false)
+            .to_string()
+    }
+
+    /// Re-renders the preview from the buffer, keeping the last good one while the JSON is invalid.
+    fn update_preview(&mut self) {
+        let text = self.get_text();
+        if text.is_empty() {
+            return;
+        }
+
+        let schema = match serde_json::from_str::<Value>(&text) {
+            Ok(schema) => schema,
+            Err(err) => {
+                self.set_error(Some(format!("Invalid JSON: {}", err)));
+                return;
+            }
+        };
+        self.set_diagnostics(SchemaLint::lint(&schema));
+        self.set_modified(self.saved.as_ref() != Some(&schema));
+
+        if !self.designer {
+            self.set_error(None);
+            return;
+        }
+
+        let form = gtk::Box::new(Orientation::Vertical, 5);
+        form.set_css_classes(&["ui"]);
+        form.set_hexpand(true);
+
+        let errors =
+            SchematicUiModel::append_widgets(&form, &schema, None, None, self.registry.clone());
+        self.preview.set_child(Some(&form));
+        self.set_error(match errors.is_empty() {
+            true => None,
+            false => Some(format!("Invalid property: {}", errors.join("\n"))),
+        });
+        self.inspector
+            .sender()
+            .send(XWidgetEditorInput::Load(schema))
+            .unwrap();
+    }
+
+    fn save(&mut self) {
+        let path = match &self.path {
+            Some(path) => path.clone(),
+            None => return,
+        };
+
+        let text = self.get_text();
+
+        match FileUtils::write_str(&path, &text) {
+            Ok(_) => {
+                self.saved = serde_json::from_str::<Value>(&text).ok();
+                self.set_modified(false);
+            }
+            Err(err) => self.set_error(Some(err.to_string())),
+        }
+    }
 }
 
 #[derive(Debug)]
 pub enum SchemaViewInput {
     Show(PathBuf),
+    ToggleDesigner(bool),
+    Edited,
+    /// Renders the preview if there was no edit since the one with the given count.
+    Render(u32),
+    Save,
+    SetSchema(Value),
 }
 
 #[derive(Debug)]
@@ -46,21 +157,95 @@ impl SimpleComponent for SchemaViewModel {
             set_halign: gtk::Align::Center,
             set_label: "Please, select a schematic!"
           },
-          gtk::ScrolledWindow {
-          #[watch]
-          set_visible: !model.hidden,
-          set_hscrollbar_policy: gtk::PolicyType::Never,
-            sourceview5::View {
-                  #[watch]
-                  set_visible: !model.hidden,
-                  set_editable: false,
-                  set_hexpand: true,
-                  set_vexpand: true,
-                  set_show_line_numbers: true,
-                  set_highlight_current_line: true,
-                  set_tab_width: 4,
-                  set_monospace: true,
-                  set_buffer: Some(&model.json)
+          gtk::Box {
+            #[watch]
+            set_visible: !model.hidden,
+            set_orientation: Orientation::Horizontal,
+            set_spacing: 5,
+            set_css_classes: &["designer_bar"],
+            gtk::ToggleButton {
+              set_label: "Designer",
+              set_tooltip_text: Some("Edit the schema and preview the form"),
+              #[watch]
+              set_active: model.designer,
+              connect_toggled[sender] => move |button| {
+                sender.input(SchemaViewInput::ToggleDesigner(button.is_active()));
+              }
+            },
+            gtk::Button {
+              set_label: "Save",
+              #[watch]
+              set_visible: model.designer,
+              #[watch]
+              set_sensitive: model.modified && model.error.is_none(),
+              connect_clicked[sender] => move |_| {
+                sender.input(SchemaViewInput::Save);
+              }
+            },
+            gtk::Label {
+              set_hexpand: true,
+              set_xalign: 0.0,
+              set_wrap: true,
+              set_css_classes: &["label", "error"],
+              #[watch]
+              set_visible: model.error.is_some(),
+              #[watch]
+              set_label: model.error.as_deref().unwrap_or_default(),
+            }
+          },
+          gtk::Paned {
+            #[watch]
+            set_visible: !model.hidden,
+            set_orientation: Orientation::Horizontal,
+            set_vexpand: true,
+            #[wrap(Some)]
+            set_start_child = &gtk::ScrolledWindow {
+              set_hscrollbar_policy: gtk::PolicyType::Never,
+              sourceview5::View {
+                    #[watch]
+                    set_editable: model.designer,
+                    set_hexpand: true,
+                    set_vexpand: true,
+                    set_show_line_numbers: true,
+                    set_highlight_current_line: true,
+                    set_tab_width: 4,
+                    set_monospace: true,
+                    set_buffer: Some(&model.json)
+              }
+            },
+            set_shrink_start_child: false,
+            #[wrap(Some)]
+            set_end_child = &gtk::Box {
+              #[watch]
+              set_visible: model.designer,
+              set_orientation: Orientation::Vertical,
+              gtk::Expander {
+                set_label: Some("x-widget inspector"),
+                set_css_classes: &["inspector"],
+                set_child: Some(model.inspector.widget()),
+              },
+              gtk::ScrolledWindow {
+                set_vexpand: true,
+                set_hscrollbar_policy: gtk::PolicyType::Never,
+                set_child: Some(&model.preview),
+              },
+            },
+            set_shrink_end_child: false,
+          },
+          gtk::Expander {
+            #[watch]
+            set_visible: !model.hidden,
+            #[watch]
+            set_label: Some(&format!("Diagnostics ({})", model.diagnostics.len())),
+            #[track = "model.changed(SchemaViewModel::diagnostics())"]
+            set_expanded: SchemaLint::has_errors(&model.diagnostics),
+            set_css_classes: &["diagnostics"],
+            gtk::Label {
+              set_xalign: 0.0,
+              set_selectable: true,
+              set_wrap: true,
+              #[watch]
+              set_label: &model.get_diagnostics_text(),
             }
           }
         }
@@ -69,27 +254,99 @@ impl SimpleComponent for SchemaViewModel {
     fn init(
         _init: Self::Init,
         root: &Self::Root,
-        _sender: ComponentSender<Self>,
+        sender: ComponentSender<Self>,
     ) -> ComponentParts<Self> {
         let model = SchemaViewModel {
             hidden: true,
             title: String::from(""),
             json: Self::get_json_buffer(None),
+            diagnostics: vec![],
+            designer: false,
+            path: None,
+            error: None,
+            modified: false,
+            saved: None,
+            edits: 0,
+            registry: Rc::new(DirectiveRegistry::from_settings()),
+            preview: gtk::Frame::new(None),
+            inspector: XWidgetEditorModel::builder().launch(()).forward(
+                sender.input_sender(),
+                |msg| match msg {
+                    XWidgetEditorOutput::Changed(schema) => SchemaViewInput::SetSchema(schema),
+                },
+            ),
+            tracker: 0,
         };
+
+        let edit_sender = sender.clone();
+        model.json.connect_changed(move |_| {
+            edit_sender.input(SchemaViewInput::Edited);
+        });
+
+        model.preview.set_css_classes(&["ui_container"]);
+
         let widgets = view_output!();
         ComponentParts { model, widgets }
     }
 
-    fn update(&mut self, message: Self::Input, _sender: ComponentSender<Self>) {
+    fn update(&mut self, message: Self::Input, sender: ComponentSender<Self>) {
+        self.reset();
+
         match message {
             SchemaViewInput::Show(schema_path) => {
-                let schema: Value =
-                    serde_json::from_str(&Collection::read_str(schema_path.to_str().unwrap()))
-                        .unwrap();
-                self.title = schema["title"].as_str().unwrap_or("").to_string();
+                let same_file = self.path.as_ref() == Some(&schema_path);
+
+                match FileUtils::read_json::<Value>(&schema_path) {
+                    Ok(schema) => {
+                        self.title = schema["title"].as_str().unwrap_or("").to_string();
+                        self.registry = Rc::new(DirectiveRegistry::from_settings());
+                        let current = serde_json::from_str::<Value>(&self.get_text()).ok();
+
+                        // Reloads triggered by saving, or by editing the file elsewhere while
+                        // there are unsaved changes, must not reset the designer.
+                        if !(same_file
+                            && self.designer
+                            && (self.modified || current.as_ref() == Some(&schema)))
+                        {
+                            self.json
+                                .set_text(&serde_json::to_string_pretty(&schema).unwrap());
+                        }
+                        self.saved = Some(schema);
+                        self.update_preview();
+                    }
+                    Err(err) => {
+                        self.saved = None;
+                        self.title = err.to_string();
+                        self.set_diagnostics(vec![]);
+                        self.set_error(Some(err.to_string()));
+                        self.json.set_text("");
+                    }
+                }
+                self.set_path(Some(schema_path));
+                self.hidden = false
+            }
+            SchemaViewInput::ToggleDesigner(active) => {
+                self.set_designer(active);
+                self.update_preview();
+            }
+            SchemaViewInput::Edited => {
+                self.edits = self.edits.wrapping_add(1);
+                let edits = self.edits;
+                glib::timeout_add_local_once(Duration::from_millis(300), move || {
+                    sender.input(SchemaViewInput::Render(edits));
+                });
+            }
+            SchemaViewInput::Render(edits) => {
+                if edits == self.edits {
+                    self.update_preview();
+                }
+            }
+            SchemaViewInput::Save => {
+                self.save();
+            }
+            SchemaViewInput::SetSchema(schema) => {
                 self.json
                     .set_text(&serde_json::to_string_pretty(&schema).unwrap());
-                self.hidden = false
             }
         }
     }
@@ -1,10 +1,10 @@
-use crate::command_builder::{CommandBuilder, CommandBuilderOptions, InputType, Param};
+use crate::command_builder::{CommandBuilder, Param};
 use crate::form_utils::FormUtils;
 use crate::impl_validation;
+use crate::runner_utils::{RunnerDefinition, RunnerUtils};
 use crate::schema_parsing::FsEntry;
-use crate::settings_utils::{Runner, SettingsData};
+use crate::settings_utils::SettingsData;
 use crate::traits::Validator;
-use convert_case::Case;
 use relm4::gtk::prelude::{
     BoxExt, ButtonExt, Cast, DialogExt, DisplayExt, EditableExt, EntryBufferExtManual, EntryExt,
     FileChooserExt, FileExt, GtkWindowExt, OrientableExt, TextBufferExt, TextViewExt, WidgetExt,
@@ -25,7 +25,7 @@ async fn run_schematic(
     executor: String,
     command: String,
     cwd: String,
-    params: Vec<Param>,
+    args: Vec<String>,
     env: Option<Vec<Env>>,
 ) -> Child {
     let mut cmd = Command::new(executor);
@@ -37,14 +37,8 @@ async fn run_schematic(
     }
 
     cmd.current_dir(cwd);
-    cmd.arg(command);
-
-    for param in params {
-        cmd.arg(format!("--{}", param.name));
-        if !param.value.is_empty() {
-            cmd.arg(param.value);
-        }
-    }
+    cmd.args(command.split_whitespace());
+    cmd.args(args);
 
     let child = cmd
         .stdout(Stdio::piped())
@@ -73,6 +67,7 @@ pub struct SchematicExecutorModel {
     success: bool,
     message: String,
     settings: Option<SettingsData>,
+    runner: Option<RunnerDefinition>,
     use_dry_run: bool,
     configurable: bool,
 }
@@ -111,12 +106,10 @@ impl SchematicExecutorModel {
     }
 
     fn has_dry_run(&self) -> bool {
-        if self.settings.is_none() {
-            return false;
+        match &self.runner {
+            Some(runner) => runner.has_dry_run(),
+            None => false,
         }
-
-        let settings = self.settings.clone().unwrap();
-        settings.runner == Runner::Google || settings.runner == Runner::MBH
     }
 
     fn set_output<T: std::io::Read + std::marker::Send + std::marker::Sync + 'static>(
@@ -415,6 +408,7 @@ impl Component for SchematicExecutorModel {
             success: true,
             message: String::default(),
             settings: None,
+            runner: None,
             use_dry_run: false,
             configurable: false,
         };
@@ -447,25 +441,15 @@ impl Component for SchematicExecutorModel {
             SchematicExecutorInput::Show(data) => {
                 self.reset_view(false);
 
-                let options: CommandBuilderOptions = CommandBuilderOptions {
-                    option_case: Case::Kebab,
-                    escape_multiline_text: true,
-                    quote_paths: true,
-                    pass_boolean: false,
-                    ..Default::default()
-                };
+                let runner = RunnerUtils::new().get(&data.settings.runner);
 
                 self.configurable = data.configurable;
-                self.builder = CommandBuilder::new(Some(options));
+                self.builder = CommandBuilder::new(Some(runner.get_builder_options()));
                 self.settings = Some(data.settings);
                 self.builder.set_params(data.params);
-                self.builder.set_command({
-                    if self.settings.as_ref().unwrap().runner == Runner::Google {
-                        format!("{}:{}", data.package_name, data.schematic)
-                    } else {
-                        data.schematic
-                    }
-                });
+                self.builder
+                    .set_command(runner.get_command(&data.package_name, &data.schematic));
+                self.runner = Some(runner);
                 self.builder
                     .set_executable(self.settings.clone().unwrap().runner_location);
 
@@ -487,19 +471,10 @@ impl Component for SchematicExecutorModel {
                 self.executing = true;
                 self.submitted = true;
 
-                let mut params = self.builder.to_params();
+                let mut args = self.builder.to_args();
 
                 if self.has_dry_run() && self.use_dry_run {
-                    params.push(Param::new(
-                        String::from("dry-run"),
-                        String::from("true"),
-                        InputType::Text,
-                    ));
-                    params.push(Param::new(
-                        String::from("no-interactive"),
-                        String::from("true"),
-                        InputType::Text,
-                    ));
+                    args.extend(self.runner.as_ref().unwrap().get_dry_run_args());
                 }
 
                 let command = self.builder.get_command();
@@ -516,7 +491,7 @@ impl Component for SchematicExecutorModel {
                 }];
 
                 sender.oneshot_command(async move {
-                    CommandMsg::Data(run_schematic(executor, command, cwd, params, Some(env)).await)
+                    CommandMsg::Data(run_schematic(executor, command, cwd, args, Some(env)).await)
                 });
             }
             SchematicExecutorInput::CopyToClipboard => {
@@ -0,0 +1,197 @@
+use relm4::gtk::prelude::{OrientableExt, WidgetExt};
+use relm4::{binding::StringBinding, gtk, typed_list_view::RelmListItem};
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SchematicMenuItem {
+    /// Package the schematic is listed under in the merged view, items are sorted by it first.
+    pub group: Option<String>,
+    pub name: String,
+    pub aliases: Vec<String>,
+    pub origin: Option<String>,
+    /// Path of the collection the schematic comes from in the merged view.
+    pub source: Option<String>,
+    /// Shows the group above the item, set for the first item of each group.
+    pub header: bool,
+}
+
+impl SchematicMenuItem {
+    pub fn new(name: String, aliases: Vec<String>, origin: Option<String>) -> Self {
+        SchematicMenuItem {
+            name,
+            aliases,
+            origin,
+            ..Default::default()
+        }
+    }
+
+    /// Lists the item under the package of a collection.
+    pub fn with_source(self, group: &str, source: &str) -> Self {
+        SchematicMenuItem {
+            group: Some(group.to_string()),
+            source: Some(source.to_string()),
+            ..self
+        }
+    }
+
+    /// Sets the header of the first item of each group, `items` are sorted.
+    pub fn set_headers(items: &mut [SchematicMenuItem]) {
+        let mut previous: Option<&str> = None;
+
+        for item in items.iter_mut() {
+            item.header = item.group.is_some() && item.group.as_deref() != previous;
+            previous = item.group.as_deref();
+        }
+    }
+
+    pub fn matches(&self, query: &str) -> bool {
+        self.group.as_ref().is_some_and(|g| g.starts_with(query))
+            || self.name.starts_with(query)
+            || self
+                .name
+                .rsplit(':')
+                .next()
+                .unwrap_or_default()
+                .starts_with(query)
+            || self.aliases.iter().any(|a| a.starts_with(query))
+    }
+
+    pub fn get_details(&self) -> String {
+        let mut details: Vec<String> = vec![];
+
+        if !self.aliases.is_empty() {
+            details.push(format!("aliases: {}", self.aliases.join(", ")));
+        }
+        if let Some(origin) = &self.origin {
+            details.push(format!("from {}", origin));
+        }
+        details.join(" · ")
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SchematicListItem {
+    pub value: SchematicMenuItem,
+    binding: StringBinding,
+}
+
+impl SchematicListItem {
+    pub fn new(value: SchematicMenuItem) -> Self {
+        Self {
+            value,
+            binding: StringBinding::new(""),
+        }
+    }
+}
+
+pub struct Widgets {
+    header: gtk::Label,
+    label: gtk::Label,
+    details: gtk::Label,
+}
+
+impl RelmListItem for SchematicListItem {
+    type Root = gtk::Box;
+    type Widgets = Widgets;
+
+    fn setup(_item: &gtk::ListItem) -> (gtk::Box, Widgets) {
+        relm4::view! {
+            my_box = gtk::Box {
+                set_orientation: gtk::Orientation::Vertical,
+                #[name = "header"]
+                gtk::Label {
+                    set_xalign: 0.0,
+                },
+                #[name = "label"]
+                gtk::Label {
+                    set_xalign: 0.0,
+                },
+                #[name = "details"]
+                gtk::Label {
+                    set_xalign: 0.0,
+                },
+            }
+        }
+
+        let widgets = Widgets {
+            header,
+            label,
+            details,
+        };
+
+        (my_box, widgets)
+    }
+
+    fn bind(&mut self, widgets: &mut Self::Widgets, _root: &mut Self::Root) {
+        let Widgets {
+            header,
+            label,
+            details,
+        } = widgets;
+        let details_text = self.value.get_details();
+
+        header.set_label(self.value.group.as_deref().unwrap_or_default());
+        header.set_css_classes(&["selector_group"]);
+        header.set_visible(self.value.header);
+        label.set_label(&self.value.name);
+        label.set_css_classes(&["selector_item"]);
+        details.set_label(&details_text);
+        details.set_css_classes(&["selector_item_details"]);
+        details.set_visible(!details_text.is_empty());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_item() -> SchematicMenuItem {
+        SchematicMenuItem::new(
+            String::from("@schematics/angular:component"),
+            vec![String::from("c")],
+            Some(String::from("@schematics/base")),
+        )
+    }
+
+    #[test]
+    fn matches_name_schematic_and_alias() {
+        let item = get_item();
+
+        assert!(item.matches("@schematics"));
+        assert!(item.matches("comp"));
+        assert!(item.matches("c"));
+        assert!(!item.matches("service"));
+    }
+
+    #[test]
+    fn set_headers_first_of_group() {
+        let mut items = vec![
+            SchematicMenuItem::new(String::from("service"), vec![], None).with_source("b", "/b"),
+            get_item(),
+            SchematicMenuItem::new(String::from("module"), vec![], None).with_source("a", "/a"),
+            SchematicMenuItem::new(String::from("class"), vec![], None).with_source("a", "/a"),
+        ];
+        items.sort();
+        SchematicMenuItem::set_headers(&mut items);
+
+        let names: Vec<(&str, bool)> = items.iter().map(|i| (i.name.as_str(), i.header)).collect();
+        assert_eq!(
+            names,
+            vec![
+                ("@schematics/angular:component", false),
+                ("class", true),
+                ("module", false),
+                ("service", true),
+            ]
+        );
+        assert!(items[1].matches("a"));
+    }
+
+    #[test]
+    fn get_details_success() {
+        assert_eq!(
+            get_item().get_details(),
+            "aliases: c · from @schematics/base"
+        );
+        assert_eq!(SchematicMenuItem::default().get_details(), "");
+    }
+}
@@ -2,30 +2,117 @@ use relm4::gtk::prelude::{EditableExt, EntryBufferExtManual, EntryExt, Orientabl
 use relm4::typed_list_view::TypedListView;
 use relm4::{gtk, ComponentParts, ComponentSender, SimpleComponent};
 
+use crate::error::{AppError, Result};
+use crate::impl_validation;
+use crate::schematic_list_item::{SchematicListItem, SchematicMenuItem};
 use crate::schematics::Collection;
 use crate::settings_utils::{SettingsData, SettingsUtils};
-use crate::string_list_item::StringListItem;
+use crate::traits::Validator;
 
 pub struct SchematicSelectorModel {
     hidden: bool,
-    list_view_wrapper: TypedListView<StringListItem, gtk::SingleSelection>,
+    list_view_wrapper: TypedListView<SchematicListItem, gtk::SingleSelection>,
     search: gtk::EntryBuffer,
-    schematics: Vec<String>,
+    schematics: Vec<SchematicMenuItem>,
     settings: Option<SettingsData>,
+    /// Settings of the collections of the merged view, by collection path.
+    sources: Vec<(String, SettingsData)>,
+    error: bool,
+    success: bool,
+    message: String,
 }
 
+impl_validation!(SchematicSelectorModel);
+
 impl SchematicSelectorModel {
-    fn load_options(&self) -> Vec<String> {
+    fn list_schematics(settings: &SettingsData) -> Result<Vec<SchematicMenuItem>> {
+        let mut collection_utils = Collection::new(settings.clone());
+        collection_utils.init()?;
+        Ok(collection_utils
+            .list_schematic_names()
+            .iter()
+            .map(|name| {
+                let data = collection_utils.get_schematic(name);
+                SchematicMenuItem::new(name.to_owned(), data.aliases, data.origin)
+            })
+            .collect())
+    }
+
+    /// Schematics of the active collection, or of all the registered ones in the merged view.
+    /// Collections that can't be read are left out, their errors are returned.
+    fn load_options(&mut self) -> (Vec<SchematicMenuItem>, Vec<String>) {
         let settings_util = SettingsUtils::new();
-        let mut collection_utils: Collection;
         let settings = self.settings.as_ref().unwrap();
+        self.sources = vec![];
+
+        if !settings_util.exists() {
+            return (vec![SchematicMenuItem::default()], vec![]);
+        }
+
+        if !settings.merged_view {
+            return match Self::list_schematics(settings) {
+                Ok(items) => (items, vec![]),
+                Err(err) => (vec![], vec![err.to_string()]),
+            };
+        }
+
+        let collections = settings.get_collections();
+        let mut result: Vec<SchematicMenuItem> = vec![];
+        let mut errors: Vec<String> = vec![];
+
+        for collection in &collections {
+            let data = settings.with_collection(collection);
+            let package = Collection::get_package_name(&collection.package);
+            let items = Collection::resolve_collection_path(&data)
+                .ok_or(AppError::NotFound(format!(
+                    "package {}",
+                    collection.package
+                )))
+                .and_then(|path| Self::list_schematics(&data).map(|items| (path, items)));
+
+            match items {
+                Ok((path, items)) => {
+                    let source = path.to_string_lossy().to_string();
+                    let shared = collections
+                        .iter()
+                        .filter(|c| Collection::get_package_name(&c.package) == package)
+                        .count()
+                        > 1;
+                    let group = match shared {
+                        true => format!("{} ({})", package, source),
+                        false => package,
+                    };
+                    result.extend(items.into_iter().map(|i| i.with_source(&group, &source)));
+                    self.sources.push((source, data));
+                }
+                Err(err) => errors.push(format!("{}: {}", package, err)),
+            }
+        }
+        (result, errors)
+    }
 
-        if settings_util.exists() {
-            collection_utils = Collection::new(settings.clone());
-            collection_utils.init();
-            collection_utils.list_schematic_names()
-        } else {
-            vec![String::default()]
+    fn find_source(&self, source: &str) -> Option<SettingsData> {
+        self.sources
+            .iter()
+            .find(|(path, _)| path == source)
+            .map(|(_, settings)| settings.clone())
+    }
+
+    /// Lists the schematics matching the search, with a header above each package.
+    fn show_schematics(&mut self) {
+        let query = self.search.text().to_string();
+        let mut items: Vec<SchematicMenuItem> = self
+            .schematics
+            .iter()
+            .filter(|item| item.matches(&query))
+            .cloned()
+            .collect();
+        items.sort();
+        SchematicMenuItem::set_headers(&mut items);
+
+        self.list_view_wrapper.clear();
+        for item in items {
+            self.list_view_wrapper.append(SchematicListItem::new(item));
         }
     }
 }
@@ -40,6 +127,7 @@ pub enum SchematicSelectorInput {
 #[derive(Debug)]
 pub enum SchematicSelectorOutput {
     Selected(String),
+    SelectedFrom(Box<SettingsData>, String),
     Load,
 }
 
@@ -60,6 +148,18 @@ impl SimpleComponent for SchematicSelectorModel {
         set_css_classes: &["schematics_selector"],
         set_hexpand: false,
         set_width_request: 300,
+        gtk::Revealer {
+          set_transition_type: gtk::RevealerTransitionType::SlideDown,
+          #[watch]
+          set_reveal_child: model.error,
+          gtk::Label {
+            set_hexpand: true,
+            set_wrap: true,
+            set_css_classes: &["label", "error"],
+            #[watch]
+            set_label: &model.message
+          },
+        },
         gtk::Entry {
           set_buffer: &model.search,
           set_css_classes: &["search", "text_input"],
@@ -94,15 +194,19 @@ impl SimpleComponent for SchematicSelectorModel {
         root: &Self::Root,
         sender: ComponentSender<Self>,
     ) -> ComponentParts<Self> {
-        let list_view_wrapper: TypedListView<StringListItem, gtk::SingleSelection> =
+        let list_view_wrapper: TypedListView<SchematicListItem, gtk::SingleSelection> =
             TypedListView::with_sorting();
 
         let model = SchematicSelectorModel {
             hidden: init,
             list_view_wrapper,
-            schematics: vec![String::default()],
+            schematics: vec![SchematicMenuItem::default()],
             search: gtk::EntryBuffer::default(),
             settings: None,
+            sources: vec![],
+            error: false,
+            success: false,
+            message: String::default(),
         };
 
         let my_view = &model.list_view_wrapper.view;
@@ -115,28 +219,27 @@ impl SimpleComponent for SchematicSelectorModel {
         match message {
             SchematicSelectorInput::Selected(n) => {
                 let item = self.list_view_wrapper.get(n).unwrap();
-                let _ = sender.output(SchematicSelectorOutput::Selected(
-                    item.borrow().value.clone(),
-                ));
-            }
-            SchematicSelectorInput::FilterChange => {
-                let query_str = self.search.text().to_string();
-                self.list_view_wrapper.pop_filter();
-                self.list_view_wrapper
-                    .add_filter(move |item| item.value.starts_with(&query_str));
-                self.list_view_wrapper.set_filter_status(0, true);
+                let value = item.borrow().value.clone();
+                let settings = value.source.as_ref().and_then(|s| self.find_source(s));
+                let _ = sender.output(match settings {
+                    Some(settings) => {
+                        SchematicSelectorOutput::SelectedFrom(Box::new(settings), value.name)
+                    }
+                    None => SchematicSelectorOutput::Selected(value.name),
+                });
             }
+            SchematicSelectorInput::FilterChange => self.show_schematics(),
 
             SchematicSelectorInput::Show(settings) => {
                 self.settings = Some(settings);
-                self.schematics = self.load_options();
-                self.list_view_wrapper.clear();
-                self.list_view_wrapper.clear_filters();
+                let (schematics, errors) = self.load_options();
+                self.schematics = schematics;
 
-                for schematic in &self.schematics {
-                    self.list_view_wrapper
-                        .append(StringListItem::new(schematic.to_string()));
+                match errors.is_empty() {
+                    true => self.clear_error(),
+                    false => self.print_error(&errors.join("\n")),
                 }
+                self.show_schematics();
                 self.hidden = false;
             }
         }
@@ -1,30 +1,38 @@
 use relm4::gtk::prelude::{BoxExt, ButtonExt, Cast, FrameExt, OrientableExt, WidgetExt};
-use relm4::gtk::{Align, ApplicationWindow, DialogFlags, Inhibit, MessageDialog};
+use relm4::gtk::{Align, Inhibit};
 use relm4::{gtk, Component, ComponentController, ComponentParts, ComponentSender, Controller};
+use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
 
 use crate::command_builder::{CommandBuilder, Param};
 use crate::config_editor_dialog::{ConfigEditorDialogInput, ConfigEditorDialogModel};
 use crate::default_widget_builder::DefaultWidgetBuilder;
+use crate::directives::DirectiveRegistry;
+use crate::error::{AppError, Result};
+use crate::file_utils::FileUtils;
 use crate::form_utils::FormUtils;
 use crate::impl_validation;
 use crate::profile_browser::{
     ProfileBrowserInput, ProfileBrowserInputParams, ProfileBrowserModel, ProfileBrowserOutput,
 };
+use crate::profile_utils::{ProfileDrift, ProfileUtils, ResolvedProfile};
 use crate::save_dialog::{
     ProfileData, SaveDialogInput, SaveDialogInputParams, SaveDialogModel, SaveDialogOutput,
 };
 use crate::schema_parsing::Primitive;
 use crate::schema_parsing::{Schema, SchemaProp, StringOrPrompt};
-use crate::schematics::Collection;
 use crate::settings_utils::SettingsUtils;
 use crate::traits::Validator;
 use crate::traits::WidgetUtils;
 use crate::value_extractor::ValueExtractor;
 use crate::value_loader::ValueLoader;
+use crate::variable_utils::{VariableContext, VariableUtils};
+use crate::workspace_utils::WorkspaceUtils;
 use crate::xwidget_builder::XWidgetBuilder;
 use std::borrow::Borrow;
+use toml::{map::Map, Value};
 
 #[tracker::track]
 pub struct SchematicUiModel {
@@ -41,6 +49,12 @@ pub struct SchematicUiModel {
     success: bool,
     message: String,
     has_directives: bool,
+    positional: Vec<String>,
+    #[no_eq]
+    inheritance: Option<ResolvedProfile>,
+    /// Loaded options holding placeholders, with their template and resolved values.
+    #[no_eq]
+    templates: HashMap<String, (Value, Value)>,
     #[no_eq]
     profiles: Vec<ProfileData>,
     #[no_eq]
@@ -57,9 +71,12 @@ impl_validation!(SchematicUiModel);
 impl SchematicUiModel {
     fn reset_view(&mut self) {
         self.set_configurable(None);
+        self.set_inheritance(None);
+        self.templates.clear();
         self.set_cwd(None);
         self.set_file(None);
         self.set_success(false);
+        self.clear_error();
         self.hidden = false;
         self.loader = false;
         self.browser.state().get_mut().model.clear();
@@ -100,38 +117,407 @@ impl SchematicUiModel {
         }
     }
 
-    fn load_values(&self, widgets: &mut SchematicUiModelWidgets, data_id: usize) {
-        let mut w = widgets
+    fn get_form(widgets: &SchematicUiModelWidgets) -> gtk::Box {
+        widgets
             .frame
             .child()
             .unwrap()
             .downcast::<gtk::Box>()
             .unwrap()
-            .first_child();
+    }
 
-        loop {
-            let widget = w.as_ref().unwrap();
+    fn get_form_widgets(&self, widgets: &mut SchematicUiModelWidgets) -> Vec<gtk::Widget> {
+        let mut result: Vec<gtk::Widget> = vec![];
+        let mut w = Self::get_form(widgets).first_child();
 
-            if self.is_a::<_, gtk::Label>(widget) {
-                w = w.as_ref().unwrap().next_sibling();
-                continue;
+        while let Some(widget) = w {
+            w = widget.next_sibling();
+            if !self.is_a::<_, gtk::Label>(&widget) && !widget.has_css_class("inheritance_bar") {
+                result.push(widget);
             }
+        }
+        result
+    }
+
+    /// Loads a profile on top of the ones it extends, older ones saved with string values are
+    /// typed in memory, the file is only rewritten when saved. If the schema changed since the
+    /// profile was saved, the differences are reported and the profile can be migrated.
+    fn load_values(
+        &mut self,
+        widgets: &mut SchematicUiModelWidgets,
+        data_id: usize,
+        sender: &ComponentSender<Self>,
+    ) {
+        let properties = &self.json["properties"];
+        let fingerprint = ProfileUtils::get_fingerprint(&self.json);
+        let (resolved, path, drift) = {
             let browser_model = self.browser.model();
             let profile: &ProfileData = browser_model.profiles[data_id].borrow();
+            let path = browser_model.get_profile_dir().join(&profile.file);
+            let data = ProfileUtils::to_typed(&profile.data, properties);
+            let resolved = ProfileUtils::resolve(&path).map(|resolved| ResolvedProfile {
+                data: data.clone(),
+                inherited: ProfileUtils::to_typed(&resolved.inherited, properties),
+                ..resolved
+            });
+            let is_current = ProfileUtils::is_current(&profile.meta, &fingerprint);
+            let mut drift = match is_current {
+                true => ProfileDrift::default(),
+                false => ProfileUtils::get_drift(&data, &self.json),
+            };
+            if let Ok(resolved) = &resolved {
+                drift
+                    .missing_required
+                    .retain(|key| !resolved.inherited.contains_key(key));
+            }
+            let resolved = resolved.map_err(|err| (err, data));
+            (resolved, path, drift)
+        };
+
+        self.templates.clear();
+        match resolved {
+            Ok(resolved) => {
+                self.fill_form(widgets, resolved.get_merged(), None, sender);
+                self.set_inheritance(Some(resolved));
+            }
+            Err((err, data)) => {
+                self.fill_form(widgets, data, None, sender);
+                self.set_inheritance(None);
+                self.print_error(&err.to_string());
+            }
+        }
+        self.show_inheritance(widgets, sender);
+
+        if !drift.is_empty() {
+            let sender = sender.clone();
+            let file = path.to_string_lossy().to_string();
+            let options: Vec<String> = self.json["properties"]
+                .as_object()
+                .map(|props| props.keys().cloned().collect())
+                .unwrap_or_default();
+
+            FormUtils::new().profile_drift_dialog(&drift, &options, move |mapping| {
+                sender.input(SchematicUiInput::MigrateProfile(file.clone(), mapping));
+            });
+        }
+    }
+
+    /// Sets profile values with their placeholders resolved. The prompts are asked first, the
+    /// form is filled once they're answered, with the prompts left as they are if cancelled.
+    /// Git placeholders are resolved in a command, as git may take a while.
+    fn fill_form(
+        &mut self,
+        widgets: &mut SchematicUiModelWidgets,
+        data: Map<String, Value>,
+        answers: Option<HashMap<String, String>>,
+        sender: &ComponentSender<Self>,
+    ) {
+        let prompts = VariableUtils::get_prompts(&data);
+        let answers = match answers {
+            Some(answers) => answers,
+            None if !prompts.is_empty() => {
+                let sender = sender.clone();
+                FormUtils::new().prompt_dialog(&prompts, move |answers| {
+                    sender.input(SchematicUiInput::Prompted(
+                        data.clone(),
+                        answers.unwrap_or_default(),
+                    ));
+                });
+                return;
+            }
+            None => HashMap::new(),
+        };
+        let mut context = self.get_variable_context(answers);
+        let texts = VariableUtils::get_texts(&data);
+
+        if VariableUtils::has_git(&texts) {
+            sender.spawn_oneshot_command(move || {
+                context.git = VariableUtils::get_git(&texts, context.cwd.as_deref());
+                SchematicUiCommand::Fill(data, context)
+            });
+            return;
+        }
+        self.set_resolved_values(widgets, &data, &context);
+    }
+
+    /// Sets profile values resolved with the context, the placeholders are kept to save them.
+    fn set_resolved_values(
+        &mut self,
+        widgets: &mut SchematicUiModelWidgets,
+        data: &Map<String, Value>,
+        context: &VariableContext,
+    ) {
+        let resolved = ProfileUtils::to_typed(
+            &VariableUtils::resolve(data, context),
+            &self.json["properties"],
+        );
+
+        for (key, value) in data {
+            match resolved.get(key) {
+                Some(resolved) if resolved != value => {
+                    self.templates
+                        .insert(key.clone(), (value.clone(), resolved.clone()));
+                }
+                _ => {
+                    self.templates.remove(key);
+                }
+            }
+        }
+        self.set_values(widgets, &resolved);
+    }
+
+    fn get_variable_context(&self, answers: HashMap<String, String>) -> VariableContext {
+        VariableContext {
+            cwd: self.cwd.as_ref().map(PathBuf::from),
+            answers,
+            ..Default::default()
+        }
+    }
+
+    /// Fails on the first JSON editor that doesn't parse and focuses it.
+    fn validate_json_inputs(
+        &self,
+        widgets: &mut SchematicUiModelWidgets,
+    ) -> std::result::Result<(), String> {
+        for widget in self.get_form_widgets(widgets) {
+            if !widget.has_css_class("json_input_container") {
+                continue;
+            }
+            let container = widget.downcast::<gtk::Box>().unwrap();
+
+            if let Some(err) = ValueExtractor::get_json_input_error(&container) {
+                if let Some(view) = container.first_child() {
+                    view.grab_focus();
+                }
+                return Err(format!(
+                    "Invalid JSON for {}: {}",
+                    container.widget_name(),
+                    err
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Emits the params of the form, placeholders typed in are resolved as well.
+    fn submit(
+        &mut self,
+        widgets: &mut SchematicUiModelWidgets,
+        answers: Option<HashMap<String, String>>,
+        sender: &ComponentSender<Self>,
+    ) {
+        if let Err(err) = self.validate_json_inputs(widgets) {
+            self.print_error(&err);
+            return;
+        }
+        let params = self.extract_values(widgets).to_params();
+        let texts: Vec<String> = params.iter().map(|param| param.value.clone()).collect();
+        let prompts = VariableUtils::get_text_prompts(&texts);
+        let answers = match answers {
+            Some(answers) => answers,
+            None if !prompts.is_empty() => {
+                let sender = sender.clone();
+                FormUtils::new().prompt_dialog(&prompts, move |answers| {
+                    if let Some(answers) = answers {
+                        sender.input(SchematicUiInput::SubmitPrompted(answers));
+                    }
+                });
+                return;
+            }
+            None => HashMap::new(),
+        };
+        let mut context = self.get_variable_context(answers);
+
+        if VariableUtils::has_git(&texts) {
+            sender.spawn_oneshot_command(move || {
+                context.git = VariableUtils::get_git(&texts, context.cwd.as_deref());
+                SchematicUiCommand::Submit(params, context)
+            });
+            return;
+        }
+        self.emit_params(params, &context, sender);
+    }
+
+    fn emit_params(
+        &self,
+        mut params: Vec<Param>,
+        context: &VariableContext,
+        sender: &ComponentSender<Self>,
+    ) {
+        for param in params.iter_mut() {
+            param.value = VariableUtils::interpolate(&param.value, context);
+        }
+        sender.output_sender().emit(SchematicUiOutput::Params(
+            params,
+            self.configurable.is_some(),
+        ));
+    }
+
+    /// Marks the options of a profile extending others as inherited or overridden, the
+    /// overridden ones get a button resetting them to the inherited value.
+    fn show_inheritance(
+        &self,
+        widgets: &mut SchematicUiModelWidgets,
+        sender: &ComponentSender<Self>,
+    ) {
+        let form = Self::get_form(widgets);
+        let mut child = form.first_child();
+
+        while let Some(widget) = child {
+            child = widget.next_sibling();
+            if widget.has_css_class("inheritance_bar") {
+                form.remove(&widget);
+            } else if self.is_a::<_, gtk::Label>(&widget) {
+                widget.remove_css_class("inherited");
+                widget.remove_css_class("overridden");
+            }
+        }
+
+        let resolved = match &self.inheritance {
+            Some(resolved) => resolved,
+            None => return,
+        };
+        let utils = FormUtils::new();
+        let mut child = form.first_child();
+
+        while let Some(widget) = child {
+            child = widget.next_sibling();
+            let key = widget.widget_name().to_string();
+            let input = match &child {
+                Some(input) if self.is_a::<_, gtk::Label>(&widget) => input.clone(),
+                _ => continue,
+            };
+            let source = resolved.sources.get(&key).cloned().unwrap_or_default();
+
+            let bar = if resolved.is_overridden(&key) {
+                let sender = sender.clone();
+                let reset = utils.action_button("reset", Some("edit-undo"));
+                reset.set_tooltip_text(Some("Reset to inherited"));
+                reset.connect_clicked(move |_| {
+                    sender.input(SchematicUiInput::ResetField(key.clone()));
+                });
+                widget.add_css_class("overridden");
+                utils.inheritance_bar(&format!("Overrides {}", source), Some(&reset))
+            } else if resolved.is_inherited(&key) {
+                widget.add_css_class("inherited");
+                utils.inheritance_bar(&format!("Inherited from {}", source), None)
+            } else {
+                continue;
+            };
+            form.insert_child_after(&bar, Some(&input));
+        }
+    }
+
+    /// Loads the inherited value of an overridden option.
+    fn reset_field(
+        &mut self,
+        widgets: &mut SchematicUiModelWidgets,
+        key: &str,
+        sender: &ComponentSender<Self>,
+    ) {
+        let value = self
+            .inheritance
+            .as_ref()
+            .and_then(|resolved| resolved.inherited.get(key))
+            .cloned();
+
+        if let Some(value) = value {
+            let mut data = Map::new();
+            data.insert(key.to_string(), value);
+            self.fill_form(widgets, data, None, sender);
+        }
+        if let Some(resolved) = self.inheritance.as_mut() {
+            resolved.data.remove(key);
+        }
+    }
+
+    /// Applies the option mapping of the drift dialog. The current schema is only stamped if
+    /// nothing else differs, so removed values and new required options are reported again.
+    fn migrate_profile(&self, path: &Path, mapping: &[(String, Option<String>)]) -> Result<()> {
+        let profile = FileUtils::read_toml::<Map<String, Value>>(path)?;
+        let data = profile
+            .get("data")
+            .and_then(|data| data.as_table())
+            .cloned()
+            .unwrap_or_default();
+        let properties = &self.json["properties"];
+        let data = ProfileUtils::migrate(
+            &ProfileUtils::to_typed(&data, properties),
+            mapping,
+            properties,
+        );
+        let inherited = ProfileUtils::resolve(path)?.inherited;
+        let mut drift = ProfileUtils::get_drift(&data, &self.json);
+        drift
+            .missing_required
+            .retain(|key| !inherited.contains_key(key));
+        let fingerprint = ProfileUtils::get_fingerprint(&self.json);
+
+        ProfileUtils::write_data(
+            path,
+            &data,
+            drift.is_empty().then_some(fingerprint.as_str()),
+        )
+    }
+
+    fn set_values(&self, widgets: &mut SchematicUiModelWidgets, data: &Map<String, Value>) {
+        for widget in self.get_form_widgets(widgets) {
             let widget_name = widget.widget_name().to_string();
-            let value = profile.data.get(&widget_name);
 
-            if value.is_some() {
-                let loader = ValueLoader::new(widget);
-                loader.set_value(value.unwrap(), &widget_name);
+            if let Some(value) = data.get(&widget_name) {
+                let loader = ValueLoader::new(&widget);
+                loader.set_value(value, &widget_name);
             }
+        }
+    }
 
-            w = w.as_ref().unwrap().next_sibling();
+    /// Current form values keyed by widget name, as expected by `set_values`.
+    fn get_values(&self, widgets: &mut SchematicUiModelWidgets) -> Map<String, Value> {
+        let mut result = Map::new();
 
-            if w.is_none() {
-                break;
+        for widget in self.get_form_widgets(widgets) {
+            let widget_name = widget.widget_name().to_string();
+            let param = ValueExtractor::new(&widget).get_name_value();
+
+            if let Some(p) = param {
+                if !p.name.is_empty() && !widget_name.is_empty() {
+                    result.insert(widget_name, Value::String(p.value));
+                }
             }
         }
+        result
+    }
+
+    fn read_schema(&mut self, params: SchematicUiInputParams) -> Result<()> {
+        let mut json: serde_json::Value = FileUtils::read_json(&params.schema_path)?;
+        let schema = serde_json::from_value::<Schema>(json.clone())
+            .map_err(|err| AppError::json(&params.schema_path, err))?;
+
+        self.has_directives = schema.has_directives();
+        self.positional = schema.get_positional();
+        self.set_configurable(None);
+
+        if schema.configurable.is_some() {
+            let config_option = schema.configurable.clone().unwrap_or_default();
+            let path_prop = schema.get_property("path").unwrap_or_default();
+            let configurable = ConfigurableSchematicOptions::new(
+                config_option,
+                path_prop
+                    .default
+                    .unwrap_or(Primitive::Str(String::from("")))
+                    .into(),
+            );
+            self.set_configurable(Some(configurable));
+        }
+
+        if let Some(cwd) = &self.cwd {
+            json["$id"] = serde_json::Value::String(cwd.clone());
+        }
+
+        self.set_json(json);
+        self.set_schematic(params.schematic);
+        self.set_package_name(params.package_name);
+        Ok(())
     }
 
     fn extract_values(&self, widgets: &mut SchematicUiModelWidgets) -> CommandBuilder {
@@ -151,7 +537,8 @@ impl SchematicUiModel {
             let param = extractor.get_name_value();
 
             if param.is_some() {
-                let p = param.unwrap();
+                let mut p = param.unwrap();
+                p.position = self.positional.iter().position(|name| name == &p.name);
                 let c = self.configurable.clone().unwrap_or_default();
 
                 if p.name == c.config_option && p.value == "true" {
@@ -172,7 +559,7 @@ impl SchematicUiModel {
         command
     }
 
-    fn get_label_text(&self, prop: &SchemaProp) -> String {
+    fn get_label_text(prop: &SchemaProp) -> String {
         if prop.x_prompt.is_some() {
             let text = match prop.x_prompt.as_ref().unwrap() {
                 StringOrPrompt::Str(s) => String::from(s),
@@ -185,68 +572,124 @@ impl SchematicUiModel {
         return String::from(prop.description.as_ref().unwrap_or(&String::default()));
     }
 
-    fn build_form(
-        &self,
-        parent: &gtk::Frame,
-        json: &serde_json::Value,
-        cwd: Option<String>,
-    ) -> Option<gtk::Box> {
-        let utils = FormUtils::new();
+    fn build_form(&self, json: &serde_json::Value, cwd: Option<String>) -> gtk::Box {
         let form = gtk::Box::new(relm4::gtk::Orientation::Vertical, 5);
         form.set_css_classes(&["ui"]);
         form.set_hexpand(true);
 
-        match json["$id"].as_str() {
-            Some(_) => {
-                let empty = serde_json::Map::new();
-                let props = json["properties"].as_object().unwrap_or(&empty);
-                let keys = props.keys();
-
-                for key in keys {
-                    let prop_value: serde_json::Value = props.get(key).unwrap().clone();
-                    match serde_json::from_value::<SchemaProp>(prop_value) {
-                        Ok(prop) => {
-                            let label_text = self.get_label_text(&prop);
-                            form.append(&utils.label(&label_text, key, None, None));
-                            if prop.x_widget.is_some() {
-                                let builder = XWidgetBuilder::new(&prop, key.clone(), cwd.clone());
-
-                                form.append(&builder.get_widget());
-                            } else {
-                                let builder =
-                                    DefaultWidgetBuilder::new(&prop, key.clone(), cwd.clone());
-
-                                form.append(&builder.get_widget());
-                            }
-
-                            prop
-                        }
-                        Err(e) => {
-                            let window: ApplicationWindow = parent
-                                .root()
-                                .unwrap()
-                                .downcast::<ApplicationWindow>()
-                                .unwrap();
-                            let dialog = MessageDialog::new(
-                                Some(&window),
-                                DialogFlags::all(),
-                                gtk::MessageType::Error,
-                                gtk::ButtonsType::YesNo,
-                                "Oops.. an error has occured!",
-                            );
-                            dialog.set_secondary_text(Some(&format!(
-                                "{}\n{}",
-                                e, "Do you wish to try again?"
-                            )));
-                            dialog.show();
-                            return None;
-                        }
-                    };
+        if json["$id"].as_str().is_some() {
+            Self::append_widgets(
+                &form,
+                json,
+                cwd,
+                Some((
+                    self.get_package_name().as_str(),
+                    self.get_schematic().as_str(),
+                )),
+                Rc::new(DirectiveRegistry::from_settings()),
+            );
+        }
+        form
+    }
+
+    /// Label of a property `SchemaProp` can't parse, e.g.: one without a `type`.
+    fn get_raw_label_text(prop: &serde_json::Value) -> String {
+        prop["x-prompt"]
+            .as_str()
+            .or(prop["x-prompt"]["message"].as_str())
+            .or(prop["description"].as_str())
+            .unwrap_or_default()
+            .to_string()
+    }
+
+    /// Appends a label and an input widget to `form` for each property of the schema.
+    /// Positional arguments come first, properties the widget builders can't handle get a
+    /// JSON editor instead. Defaults configured for the (collection, schematic) pair in the
+    /// workspace of `cwd` take precedence over the ones of the schema. Directives are looked up
+    /// in `registry`. Returns the errors of the properties that don't parse.
+    pub fn append_widgets(
+        form: &gtk::Box,
+        json: &serde_json::Value,
+        cwd: Option<String>,
+        schematic: Option<(&str, &str)>,
+        registry: Rc<DirectiveRegistry>,
+    ) -> Vec<String> {
+        let utils = FormUtils::new();
+        let mut errors: Vec<String> = vec![];
+        let empty = serde_json::Map::new();
+        let workspace = cwd
+            .as_ref()
+            .and_then(|cwd| WorkspaceUtils::read(Path::new(cwd)));
+        let defaults = match (&workspace, schematic) {
+            (Some(workspace), Some((collection, schematic))) => workspace.get_schematic_defaults(
+                collection,
+                schematic,
+                workspace.get_default_project().as_deref(),
+            ),
+            _ => serde_json::Map::new(),
+        };
+        let mut props: Vec<(&String, &serde_json::Value, Option<SchemaProp>)> = json["properties"]
+            .as_object()
+            .unwrap_or(&empty)
+            .iter()
+            .map(|(key, value)| {
+                let prop = match serde_json::from_value::<SchemaProp>(value.clone()) {
+                    Ok(p) => Some(match &workspace {
+                        Some(workspace) => p.with_workspace(key, workspace, &defaults),
+                        None => p,
+                    }),
+                    Err(err) => {
+                        errors.push(format!("{}: {}", key, err));
+                        None
+                    }
+                };
+                (key, value, prop)
+            })
+            .collect();
+        props.sort_by_key(|(_, _, prop)| {
+            prop.as_ref()
+                .and_then(|p| p.get_position())
+                .unwrap_or(usize::MAX)
+        });
+
+        for (key, prop_value, prop) in props {
+            let label_text = match &prop {
+                Some(prop) => Self::get_label_text(prop),
+                None => Self::get_raw_label_text(prop_value),
+            };
+            let label = utils.label(&label_text, key, None, None);
+            if prop.as_ref().and_then(|p| p.get_position()).is_some() {
+                label.add_css_class("positional");
+            }
+            form.append(&label);
+
+            match prop {
+                Some(prop) if prop.x_widget.is_some() => {
+                    let builder =
+                        XWidgetBuilder::new(&prop, key.clone(), cwd.clone(), registry.clone());
+
+                    form.append(&builder.get_widget());
+                }
+                Some(prop) if prop.is_supported() => {
+                    let builder = DefaultWidgetBuilder::new(
+                        &prop,
+                        key.clone(),
+                        cwd.clone(),
+                        registry.clone(),
+                    );
+
+                    form.append(&builder.get_widget());
+                }
+                _ => {
+                    let default = prop_value
+                        .get("default")
+                        .and_then(|d| serde_json::from_value::<Primitive>(d.clone()).ok());
+
+                    form.append(&utils.json_input(key, prop_value, default));
                 }
-                Some(form)
             }
-            None => Some(form),
         }
+        errors
     }
 }
 
@@ -275,17 +718,36 @@ impl ConfigurableSchematicOptions {
 #[derive(Debug)]
 pub enum SchematicUiInput {
     Show(SchematicUiInputParams),
+    Reload(SchematicUiInputParams),
+    /// Form values to set again once the form of a reloaded schema is rendered.
+    Restore(Map<String, Value>),
     Submit,
+    /// Answers to the prompts of the form values.
+    SubmitPrompted(HashMap<String, String>),
     ShowSave(bool),
     ShowConfig,
     ShowBrowser,
     HideBrowser,
     Selected(usize, String),
     Saved(String),
+    /// Profile file and the new name of each unknown option, `None` to remove it.
+    MigrateProfile(String, Vec<(String, Option<String>)>),
+    ResetField(String),
+    /// Profile values and the answers to their prompts.
+    Prompted(Map<String, Value>, HashMap<String, String>),
     ConfigDone,
     CwdChanged(String),
 }
 
+/// Form values with placeholders resolved off the GTK thread.
+#[derive(Debug)]
+pub enum SchematicUiCommand {
+    /// Profile values and the context to resolve them with.
+    Fill(Map<String, Value>, VariableContext),
+    /// Params of the form and the context to resolve them with.
+    Submit(Vec<Param>, VariableContext),
+}
+
 #[derive(Debug)]
 pub enum SchematicUiOutput {
     Params(Vec<Param>, bool),
@@ -297,7 +759,7 @@ impl Component for SchematicUiModel {
     type Input = SchematicUiInput;
     type Output = SchematicUiOutput;
     type Init = bool;
-    type CommandOutput = bool;
+    type CommandOutput = SchematicUiCommand;
 
     view! {
         #[root]
@@ -322,6 +784,19 @@ impl Component for SchematicUiModel {
             #[watch]
             set_visible: !model.hidden,
             set_css_classes: &["profile_bar"]
+          },
+           gtk::Revealer {
+            set_transition_type: gtk::RevealerTransitionType::SlideDown,
+            #[watch]
+            set_reveal_child: model.error,
+            gtk::Label {
+              set_hexpand: true,
+              set_vexpand: false,
+              set_css_classes: &["label", "error"],
+              set_halign: gtk::Align::Center,
+              #[watch]
+              set_label: &(model.message).to_string()
+            },
           },
            gtk::Revealer {
             set_transition_type: gtk::RevealerTransitionType::SlideDown,
@@ -361,7 +836,7 @@ impl Component for SchematicUiModel {
                   set_hexpand: true,
                   set_css_classes: &["ui_container"],
                   #[track = "model.changed(SchematicUiModel::json())"]
-                  set_child: Some(&model.build_form(frame.as_ref(), &model.json, model.cwd.clone()).unwrap())
+                  set_child: Some(&model.build_form(&model.json, model.cwd.clone()))
                 },
                 gtk::Revealer {
                     set_transition_type: gtk::RevealerTransitionType::SlideLeft,
@@ -483,12 +958,36 @@ impl Component for SchematicUiModel {
             config,
             browser,
             has_directives: false,
+            positional: vec![],
+            inheritance: None,
+            templates: HashMap::new(),
         };
 
         let widgets = view_output!();
         ComponentParts { model, widgets }
     }
 
+    fn update_cmd_with_view(
+        &mut self,
+        widgets: &mut Self::Widgets,
+        message: Self::CommandOutput,
+        sender: ComponentSender<Self>,
+        _root: &Self::Root,
+    ) {
+        self.reset();
+
+        match message {
+            SchematicUiCommand::Fill(data, context) => {
+                self.set_resolved_values(widgets, &data, &context);
+            }
+            SchematicUiCommand::Submit(params, context) => {
+                self.emit_params(params, &context, &sender);
+            }
+        }
+
+        self.update_view(widgets, sender)
+    }
+
     fn update_with_view(
         &mut self,
         widgets: &mut Self::Widgets,
@@ -500,50 +999,56 @@ impl Component for SchematicUiModel {
 
         match message {
             SchematicUiInput::Show(params) => {
-                let json: serde_json::Value = serde_json::from_str(&Collection::read_str(
-                    params.schema_path.to_str().unwrap(),
-                ))
-                .unwrap();
-                let schema = serde_json::from_value::<Schema>(json.clone()).unwrap();
-
-                self.has_directives = schema.has_directives();
-
                 self.reset_view();
-
-                if schema.configurable.is_some() {
-                    let config_option = schema.configurable.clone().unwrap_or_default();
-                    let path_prop = schema.get_property("path").unwrap_or_default();
-                    let configurable = ConfigurableSchematicOptions::new(
-                        config_option,
-                        path_prop
-                            .default
-                            .unwrap_or(Primitive::Str(String::from("")))
-                            .into(),
-                    );
-                    self.set_configurable(Some(configurable));
+                if let Err(err) = self.read_schema(params) {
+                    self.print_error(&err.to_string());
                 }
+            }
+            SchematicUiInput::Reload(params) => {
+                let values = self.get_values(widgets);
 
-                self.set_json(json);
-                self.set_schematic(params.schematic);
-                self.set_package_name(params.package_name);
+                match self.read_schema(params) {
+                    Ok(_) => {
+                        self.clear_error();
+                        sender.input(SchematicUiInput::Restore(values));
+                    }
+                    Err(err) => self.print_error(&err.to_string()),
+                }
+            }
+            SchematicUiInput::Restore(values) => {
+                self.set_values(widgets, &values);
+                self.show_inheritance(widgets, &sender);
             }
             SchematicUiInput::ShowSave(save_as) => {
-                let command = self.extract_values(widgets);
-                let mut description: Option<String> = None;
+                let form_data =
+                    ProfileUtils::to_typed(&self.get_values(widgets), &self.json["properties"]);
+                // Values still matching what a placeholder resolved to are saved as the placeholder.
+                let mut form_data = VariableUtils::restore(&form_data, &self.templates);
+                let mut meta = Map::new();
                 let browser_model = &self.browser.state().get().model;
                 let path = browser_model.get_loaded_profile_path();
                 if browser_model.is_profile_loaded() {
-                    description = Some(
-                        browser_model.get_loaded_profile_data(&path)["meta"]["description"]
-                            .as_str()
-                            .unwrap()
-                            .to_string(),
-                    );
+                    meta = browser_model
+                        .get_loaded_profile_data(&path)
+                        .ok()
+                        .and_then(|profile| profile.get("meta").and_then(|m| m.as_table()).cloned())
+                        .unwrap_or_default();
+                }
+                let description = meta
+                    .get("description")
+                    .and_then(|description| description.as_str())
+                    .map(String::from);
+                // A profile extending others only holds the values it overrides.
+                let extends = ProfileUtils::get_extends(&meta);
+                if let (Some(_), Some(resolved)) = (&extends, &self.inheritance) {
+                    form_data = resolved.get_overrides(&form_data);
                 }
                 self.save
                     .sender()
                     .send(SaveDialogInput::Show(SaveDialogInputParams {
-                        form_data: command.to_toml(),
+                        form_data,
+                        extends,
+                        fingerprint: ProfileUtils::get_fingerprint(&self.json),
                         schematic: self.schematic.clone(),
                         package_name: self.package_name.clone(),
                         file: browser_model.get_loaded_profile_file_as_option(),
@@ -580,13 +1085,9 @@ impl Component for SchematicUiModel {
                 *json.get_mut("$id").unwrap() = serde_json::Value::String(path.clone());
                 self.set_cwd(path.into());
             }
-            SchematicUiInput::Submit => {
-                let command = self.extract_values(widgets);
-
-                sender.output_sender().emit(SchematicUiOutput::Params(
-                    command.to_params(),
-                    self.configurable.is_some(),
-                ));
+            SchematicUiInput::Submit => self.submit(widgets, None, &sender),
+            SchematicUiInput::SubmitPrompted(answers) => {
+                self.submit(widgets, Some(answers), &sender)
             }
             SchematicUiInput::Saved(file) => {
                 let _ = self.browser.sender().send(ProfileBrowserInput::Show(
@@ -600,8 +1101,31 @@ impl Component for SchematicUiModel {
                 self.set_file(Some(file));
                 self.print_success("Saved");
             }
+            SchematicUiInput::ResetField(key) => {
+                self.reset_field(widgets, &key, &sender);
+                self.show_inheritance(widgets, &sender);
+            }
+            SchematicUiInput::Prompted(data, answers) => {
+                self.fill_form(widgets, data, Some(answers), &sender);
+            }
+            SchematicUiInput::MigrateProfile(file, mapping) => {
+                match self.migrate_profile(Path::new(&file), &mapping) {
+                    Ok(()) => {
+                        let _ = self.browser.sender().send(ProfileBrowserInput::Show(
+                            ProfileBrowserInputParams::new(
+                                self.schematic.clone(),
+                                self.package_name.clone(),
+                                false,
+                                Some(file),
+                            ),
+                        ));
+                        self.print_success("Migrated");
+                    }
+                    Err(err) => self.print_error(&err.to_string()),
+                }
+            }
             SchematicUiInput::Selected(selected, file) => {
-                self.load_values(widgets, selected);
+                self.load_values(widgets, selected, &sender);
                 self.set_file(Some(file));
             }
         }
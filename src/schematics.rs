@@ -1,8 +1,11 @@
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use serde_json::*;
 use std::fs::read_to_string;
 
+use crate::discovery_utils::DiscoveryUtils;
+use crate::error::{AppError, Result};
+use crate::file_utils::FileUtils;
 use crate::settings_utils::SettingsData;
 
 #[derive(Debug)]
@@ -17,6 +20,8 @@ pub struct SchematicData {
     pub description: String,
     pub private: bool,
     pub hidden: bool,
+    pub aliases: Vec<String>,
+    pub origin: Option<String>,
 }
 
 impl Collection {
@@ -33,14 +38,38 @@ impl Collection {
         let empty_map = Map::default();
         let empty_obj = &Value::Object(empty_map);
         let list = self.list();
-        let data = list.get(name).unwrap_or(empty_obj);
+        let data = list.get(&self.resolve_alias(name)).unwrap_or(empty_obj);
         SchematicData {
             schema: data["schema"].as_str().unwrap_or_default().to_owned(),
             description: data["description"].as_str().unwrap_or_default().to_owned(),
             hidden: data["hidden"].as_bool().unwrap_or_default(),
             private: data["private"].as_bool().unwrap_or_default(),
+            aliases: Self::get_aliases(data),
+            origin: data["$origin"].as_str().map(String::from),
         }
     }
+
+    fn get_aliases(data: &Value) -> Vec<String> {
+        data["aliases"]
+            .as_array()
+            .unwrap_or(&vec![])
+            .iter()
+            .filter_map(|a| a.as_str().map(String::from))
+            .collect()
+    }
+
+    /// Returns the name of the schematic having `name` as an alias, or `name` itself.
+    pub fn resolve_alias(&self, name: &str) -> String {
+        let list = self.list();
+
+        if list.contains_key(name) {
+            return name.to_string();
+        }
+        list.iter()
+            .find(|(_, data)| Self::get_aliases(data).iter().any(|a| a == name))
+            .map(|(key, _)| key.to_owned())
+            .unwrap_or(name.to_string())
+    }
     fn list(&self) -> Map<String, Value> {
         let empty = Map::default();
         // let black_list = vec!["app", "mutation", "query"];
@@ -70,9 +99,139 @@ impl Collection {
             .collect()
     }
 
-    pub fn init(&mut self) -> &Collection {
-        self.data = Self::read(&self.settings.schematics_collection);
-        self
+    pub fn init(&mut self) -> Result<&Collection> {
+        self.data = Self::read_with_extends(&self.collection_path()?, &mut vec![])?;
+        Ok(self)
+    }
+
+    pub fn collection_path(&self) -> Result<PathBuf> {
+        Self::resolve_collection_path(&self.settings).ok_or(AppError::NotFound(format!(
+            "the collection of {}",
+            self.settings.schematics_package
+        )))
+    }
+
+    /// Accepts a path to `package.json`, a package directory or a package name.
+    pub fn resolve_package_json(package: &str) -> Option<PathBuf> {
+        let path = Path::new(package);
+
+        if package.is_empty() {
+            return None;
+        } else if path.is_file() {
+            return Some(path.to_path_buf());
+        } else if path.is_dir() {
+            return Some(path.join("package.json")).filter(|p| p.is_file());
+        }
+
+        let cwd = std::env::current_dir().unwrap_or_default();
+        Self::find_package_dir(package, &cwd).map(|dir| dir.join("package.json"))
+    }
+
+    /// The `name` from `package.json`, or the input itself if it can't be resolved.
+    pub fn get_package_name(package: &str) -> String {
+        Self::resolve_package_json(package)
+            .and_then(|path| read_to_string(path).ok())
+            .and_then(|json| from_str::<Value>(&json).ok())
+            .and_then(|json| json["name"].as_str().map(String::from))
+            .unwrap_or(package.to_string())
+    }
+
+    /// Looks up a package in the `node_modules` of `from` and its ancestors, then globally.
+    pub fn find_package_dir(name: &str, from: &Path) -> Option<PathBuf> {
+        let is_package = |dir: &PathBuf| dir.join("package.json").is_file();
+
+        from.ancestors()
+            .map(|dir| dir.join("node_modules").join(name))
+            .find(is_package)
+            .or_else(|| {
+                let discovery = DiscoveryUtils::new();
+                discovery
+                    .get_node_modules_dirs(&discovery.get_bin_dirs())
+                    .iter()
+                    .map(|dir| dir.join(name))
+                    .find(is_package)
+            })
+    }
+
+    /// Reads the collection location from the `schematics` (or `generators`) key of `package.json`.
+    pub fn get_collection_path(package_json: &Path) -> Option<PathBuf> {
+        let json: Value = from_str(&read_to_string(package_json).ok()?).ok()?;
+        let collection = json["schematics"]
+            .as_str()
+            .or(json["generators"].as_str())?;
+        let path = package_json
+            .parent()?
+            .join(collection.trim_start_matches("./"));
+
+        Some(path).filter(|p| p.is_file())
+    }
+
+    pub fn resolve_collection_path(settings: &SettingsData) -> Option<PathBuf> {
+        if !settings.schematics_collection.is_empty() {
+            return Some(PathBuf::from(&settings.schematics_collection));
+        }
+        Self::get_collection_path(&Self::resolve_package_json(&settings.schematics_package)?)
+    }
+
+    fn get_extends(data: &Value) -> Vec<String> {
+        match &data["extends"] {
+            Value::String(name) => vec![name.to_owned()],
+            Value::Array(names) => names
+                .iter()
+                .filter_map(|n| n.as_str().map(String::from))
+                .collect(),
+            _ => vec![],
+        }
+    }
+
+    fn resolve_extends(name: &str, collection_dir: &Path) -> Option<PathBuf> {
+        if name.ends_with(".json") {
+            return Some(collection_dir.join(name)).filter(|p| p.is_file());
+        }
+        let package_dir = Self::find_package_dir(name, collection_dir)?;
+        Self::get_collection_path(&package_dir.join("package.json"))
+    }
+
+    /// Reads a collection and merges in the schematics of the collections it extends, recursively.
+    /// Local schematics win, inherited ones get absolute schema paths and an `$origin`.
+    fn read_with_extends(path: &Path, visited: &mut Vec<PathBuf>) -> Result<Value> {
+        let mut data = Value::Object(FileUtils::read_json::<serde_json::Map<String, Value>>(
+            path,
+        )?);
+        let dir = path.parent().unwrap_or(Path::new("/")).to_path_buf();
+        visited.push(path.canonicalize().unwrap_or(path.to_path_buf()));
+
+        if !data["schematics"].is_object() {
+            data["schematics"] = json!({});
+        }
+
+        for name in Self::get_extends(&data) {
+            let base_path = Self::resolve_extends(&name, &dir)
+                .ok_or(AppError::NotFound(format!("extended collection {}", name)))?;
+            if visited.contains(&base_path.canonicalize().unwrap_or(base_path.clone())) {
+                continue;
+            }
+
+            let base = Self::read_with_extends(&base_path, visited)?;
+            let base_dir = base_path.parent().unwrap_or(Path::new("/")).to_path_buf();
+            let schematics = data["schematics"].as_object_mut().unwrap();
+
+            for (key, value) in base["schematics"].as_object().unwrap() {
+                if schematics.contains_key(key) {
+                    continue;
+                }
+                let mut schematic = value.to_owned();
+                if let Some(schema) = value["schema"].as_str() {
+                    schematic["schema"] =
+                        Value::String(base_dir.join(schema).to_str().unwrap_or_default().into());
+                }
+                if value["$origin"].is_null() {
+                    schematic["$origin"] = Value::String(name.to_owned());
+                }
+                schematics.insert(key.to_owned(), schematic);
+            }
+        }
+        Ok(data)
     }
 
     pub fn list_schematic_names(&self) -> Vec<String> {
@@ -90,25 +249,12 @@ impl Collection {
         self.list_schematic_names().iter().any(|x| x == schematic)
     }
 
-    pub fn read_str(path: &str) -> String {
-        match read_to_string(Path::new(path)) {
-            Ok(str) => str.to_string(),
-            Err(err) => panic!("Invalid UTF8 input: {:?}", err),
-        }
+    pub fn read_str(path: &str) -> Result<String> {
+        FileUtils::read_str(Path::new(path))
     }
 
-    pub fn read(path: &str) -> Value {
-        let contents: Vec<u8> = match std::fs::read(path) {
-            Ok(vec) => vec,
-            Err(err) => panic!("{}, {:?}", path, err),
-        };
-        match from_str(match &std::str::from_utf8(&contents) {
-            Ok(str) => str.to_owned(),
-            Err(err) => panic!("Invalid UTF8 input: {:?}", err),
-        }) {
-            Ok(json) => json,
-            Err(err) => panic!("Invalid JSON: {:?}!", err),
-        }
+    pub fn read(path: &str) -> Result<Value> {
+        FileUtils::read_json(Path::new(path))
     }
 }
 
@@ -119,7 +265,7 @@ mod tests {
     fn get_collection() -> Collection {
         let settings = SettingsData::default();
         let mut collection = Collection::new(settings);
-        collection.data = serde_json::from_str("{\"$schema\": \"location\", \"schematics\": {\"sc1\": {\"description\": \"sc1 desc\"}, \"sc2\": {}, \"sc3\": {\"private\": true}, \"sc4\": {\"hidden\": true}}}").unwrap();
+        collection.data = serde_json::from_str("{\"$schema\": \"location\", \"schematics\": {\"sc1\": {\"description\": \"sc1 desc\", \"aliases\": [\"s\"]}, \"sc2\": {}, \"sc3\": {\"private\": true}, \"sc4\": {\"hidden\": true}}}").unwrap();
         collection
     }
 
@@ -208,11 +354,161 @@ mod tests {
             result,
             SchematicData {
                 description: String::from("sc1 desc"),
+                aliases: vec![String::from("s")],
                 ..Default::default()
             }
         );
     }
 
+    #[test]
+    fn get_schematic_by_alias() {
+        let collection = get_collection();
+        let result = collection.get_schematic("s");
+
+        assert_eq!(result.description, "sc1 desc");
+    }
+
+    #[test]
+    fn resolve_alias_success() {
+        let collection = get_collection();
+
+        assert_eq!(collection.resolve_alias("s"), "sc1");
+        assert_eq!(collection.resolve_alias("sc2"), "sc2");
+        assert_eq!(collection.resolve_alias("none"), "none");
+    }
+
+    fn get_package_dir(name: &str) -> PathBuf {
+        let root = std::env::temp_dir()
+            .join("schematics-gui-collection")
+            .join(name);
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(&root).unwrap();
+        root
+    }
+
+    fn add_package(dir: &Path, package_json: &str, collection: &str) {
+        std::fs::create_dir_all(dir.join("src")).unwrap();
+        std::fs::write(dir.join("package.json"), package_json).unwrap();
+        std::fs::write(dir.join("src/collection.json"), collection).unwrap();
+    }
+
+    #[test]
+    fn resolve_package_json_dir() {
+        let root = get_package_dir("resolve_dir");
+        add_package(&root, "{\"schematics\": \"./src/collection.json\"}", "{}");
+
+        let result = Collection::resolve_package_json(root.to_str().unwrap());
+
+        assert_eq!(result, Some(root.join("package.json")));
+    }
+
+    #[test]
+    fn find_package_dir_in_ancestors() {
+        let root = get_package_dir("find_package");
+        let package = root.join("node_modules/@foo/bar");
+        add_package(&package, "{}", "{}");
+
+        let result = Collection::find_package_dir("@foo/bar", &root.join("some/nested/dir"));
+
+        assert_eq!(result, Some(package));
+    }
+
+    #[test]
+    fn resolve_collection_path_from_package() {
+        let root = get_package_dir("resolve_collection");
+        add_package(&root, "{\"schematics\": \"./src/collection.json\"}", "{}");
+        let settings = SettingsData {
+            schematics_package: root.to_str().unwrap().to_string(),
+            ..Default::default()
+        };
+
+        let result = Collection::resolve_collection_path(&settings);
+
+        assert_eq!(result, Some(root.join("src/collection.json")));
+    }
+
+    #[test]
+    fn read_with_extends_merges_base_schematics() {
+        let root = get_package_dir("extends");
+        let base = root.join("node_modules/base");
+        add_package(
+            &base,
+            "{\"schematics\": \"./src/collection.json\"}",
+            "{\"schematics\": {\"sc1\": {\"schema\": \"./sc1.json\"}, \"sc2\": {\"schema\": \"./sc2.json\"}}}",
+        );
+        add_package(
+            &root,
+            "{\"schematics\": \"./src/collection.json\"}",
+            "{\"extends\": \"base\", \"schematics\": {\"sc1\": {\"schema\": \"./own.json\"}}}",
+        );
+
+        let data =
+            Collection::read_with_extends(&root.join("src/collection.json"), &mut vec![]).unwrap();
+
+        assert_eq!(data["schematics"]["sc1"]["schema"], "./own.json");
+        assert_eq!(data["schematics"]["sc1"]["$origin"], Value::Null);
+        assert_eq!(data["schematics"]["sc2"]["$origin"], "base");
+        assert_eq!(
+            data["schematics"]["sc2"]["schema"],
+            base.join("src/./sc2.json").to_str().unwrap()
+        );
+    }
+
+    #[test]
+    fn read_with_extends_unresolved() {
+        let root = get_package_dir("extends_unresolved");
+        add_package(
+            &root,
+            "{\"schematics\": \"./src/collection.json\"}",
+            "{\"extends\": \"no-such-package\"}",
+        );
+
+        let result = Collection::read_with_extends(&root.join("src/collection.json"), &mut vec![]);
+
+        assert!(matches!(result, Err(AppError::NotFound(_))));
+    }
+
+    #[test]
+    fn read_with_extends_relative_cycle() {
+        let root = get_package_dir("extends_cycle");
+        add_package(
+            &root.join("a"),
+            "{}",
+            "{\"extends\": [\"../../b/src/collection.json\", \"./collection.json\"], \"schematics\": {\"sc1\": {}}}",
+        );
+        add_package(
+            &root.join("b"),
+            "{}",
+            "{\"extends\": \"../../a/src/collection.json\", \"schematics\": {\"sc2\": {}}}",
+        );
+
+        let data = Collection::read_with_extends(&root.join("a/src/collection.json"), &mut vec![])
+            .unwrap();
+
+        assert!(data["schematics"]["sc1"].is_object());
+        assert!(data["schematics"]["sc2"].is_object());
+    }
+
+    #[test]
+    fn read_with_extends_not_an_object() {
+        let root = get_package_dir("extends_array");
+        add_package(&root, "{}", "[]");
+
+        let result = Collection::read_with_extends(&root.join("src/collection.json"), &mut vec![]);
+
+        assert!(matches!(result, Err(AppError::Json(_, _))));
+    }
+
+    #[test]
+    fn read_invalid_json() {
+        let root = get_package_dir("invalid_json");
+        add_package(&root, "{}", "{\"schematics\": ");
+
+        let result = Collection::read(root.join("src/collection.json").to_str().unwrap());
+
+        assert!(matches!(result, Err(AppError::Json(_, _))));
+    }
+
     #[test]
     fn get_schematic_not_found_returns_default() {
         let collection = get_collection();
@@ -1,12 +1,18 @@
-use std::path::Path;
-
+use relm4::gtk::glib;
 use relm4::gtk::prelude::WidgetExt;
 use relm4::{
     gtk, Component, ComponentController, ComponentParts, ComponentSender, Controller,
     SimpleComponent,
 };
 
+use std::fs::read_to_string;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
 use crate::command_builder::Param;
+use crate::error::{AppError, Result};
+use crate::file_utils::FileWatcher;
+use crate::form_utils::FormUtils;
 use crate::package_info::{
     PackageInfoInput, PackageInfoModel, PackageInfoOutput, PartialPackageJsonData,
 };
@@ -31,6 +37,8 @@ pub struct SchematicsDetailsModel {
     settings: Option<SettingsData>,
     package: Option<PartialPackageJsonData>,
     schematic: String,
+    watcher: FileWatcher,
+    utils: FormUtils,
 }
 
 impl SchematicsDetailsModel {
@@ -49,6 +57,61 @@ impl SchematicsDetailsModel {
     pub fn show_shell(&mut self) {
         self.tab = 3;
     }
+
+    fn get_schema_path(&self, collection: &Collection, schematic_name: &str) -> Result<PathBuf> {
+        let schematic = collection.get_schematic(schematic_name);
+        let path = collection
+            .collection_path()?
+            .parent()
+            .unwrap_or(Path::new(""))
+            .join(schematic.schema);
+        path.canonicalize().map_err(|err| AppError::io(&path, err))
+    }
+
+    fn watch(&mut self, schema_path: Option<&Path>) {
+        let collection = Collection::new(self.settings.clone().unwrap());
+        self.watcher.clear();
+        if let Ok(path) = collection.collection_path() {
+            self.watcher.watch(&path);
+        }
+        if let Some(path) = schema_path {
+            self.watcher.watch(path);
+        }
+    }
+
+    fn is_valid_json(path: &Path) -> bool {
+        read_to_string(path)
+            .ok()
+            .and_then(|json| serde_json::from_str::<serde_json::Value>(&json).ok())
+            .is_some()
+    }
+
+    fn reload_schematic(&mut self) {
+        let mut collection = Collection::new(self.settings.clone().unwrap());
+        if collection.init().is_err() || !collection.has_schematic(&self.schematic) {
+            return;
+        }
+
+        let path = match self.get_schema_path(&collection, &self.schematic) {
+            Ok(path) if Self::is_valid_json(&path) => path,
+            _ => return,
+        };
+        self.watch(Some(&path));
+
+        self.schema
+            .sender()
+            .send(SchemaViewInput::Show(path.clone()))
+            .unwrap();
+
+        self.ui
+            .sender()
+            .send(SchematicUiInput::Reload(SchematicUiInputParams {
+                schema_path: path,
+                schematic: self.schematic.clone(),
+                package_name: self.package.as_ref().unwrap().name.clone(),
+            }))
+            .unwrap();
+    }
 }
 
 #[derive(Debug)]
@@ -59,11 +122,13 @@ pub enum SchematicsDetailsInput {
     SetPackage(Box<PartialPackageJsonData>),
     BackToUi,
     CwdChanged(String),
+    Poll,
 }
 
 #[derive(Debug)]
 pub enum SchematicsDetailsOutput {
     LoadPackageInfo(String),
+    CollectionChanged,
 }
 
 pub struct SchematicsDetailsInit {}
@@ -136,24 +201,36 @@ impl SimpleComponent for SchematicsDetailsModel {
             ui: schematic_ui,
             executor: schematic_executor,
             schematic: String::default(),
+            watcher: FileWatcher::new(),
+            utils: FormUtils::new(),
         };
+
+        let poll_sender = sender.clone();
+        glib::timeout_add_local(Duration::from_secs(1), move || {
+            poll_sender.input(SchematicsDetailsInput::Poll);
+            glib::Continue(true)
+        });
+
         let widgets = view_output!();
         ComponentParts { model, widgets }
     }
 
-    fn update(&mut self, message: Self::Input, _sender: ComponentSender<Self>) {
+    fn update(&mut self, message: Self::Input, sender: ComponentSender<Self>) {
         match message {
             SchematicsDetailsInput::ShowSchematic(schematic_name) => {
                 let settings = self.settings.as_ref().unwrap();
                 let mut collection = Collection::new(settings.clone());
-                collection.init();
-                let schematic = collection.get_schematic(&schematic_name);
-                let path = Path::new(&settings.schematics_collection)
-                    .parent()
-                    .unwrap()
-                    .join(schematic.schema)
-                    .canonicalize()
-                    .unwrap();
+                let path = match collection
+                    .init()
+                    .and_then(|collection| self.get_schema_path(collection, &schematic_name))
+                {
+                    Ok(path) => path,
+                    Err(err) => {
+                        self.utils.error_dialog(&err);
+                        return;
+                    }
+                };
+                self.watch(Some(&path));
 
                 self.schema
                     .sender()
@@ -179,10 +256,18 @@ impl SimpleComponent for SchematicsDetailsModel {
             }
             SchematicsDetailsInput::Show(data) => {
                 self.settings = Some(data.clone().unwrap());
-                let pkg = &self.settings.as_ref().unwrap().schematics_package;
+                let pkg = self.settings.as_ref().unwrap().schematics_package.clone();
+                match PartialPackageJsonData::read(&pkg) {
+                    Ok(package) => self.package = Some(package),
+                    Err(err) => {
+                        self.utils.error_dialog(&err);
+                        return;
+                    }
+                }
+                self.watch(None);
                 self.info
                     .sender()
-                    .send(PackageInfoInput::Show(pkg.to_string()))
+                    .send(PackageInfoInput::Show(pkg))
                     .unwrap();
 
                 self.hidden = false
@@ -196,6 +281,24 @@ impl SimpleComponent for SchematicsDetailsModel {
                     .send(SchematicUiInput::CwdChanged(path))
                     .unwrap();
             }
+            SchematicsDetailsInput::Poll => {
+                let changed = self.watcher.changed();
+
+                if changed.is_empty() || !changed.iter().all(|p| Self::is_valid_json(p)) {
+                    return;
+                }
+
+                let collection = Collection::new(self.settings.clone().unwrap());
+                if collection
+                    .collection_path()
+                    .is_ok_and(|path| changed.contains(&path))
+                {
+                    let _ = sender.output(SchematicsDetailsOutput::CollectionChanged);
+                }
+                if !self.schematic.is_empty() {
+                    self.reload_schematic();
+                }
+            }
             SchematicsDetailsInput::SetPackage(data) => {
                 self.package = Some(*data);
             }
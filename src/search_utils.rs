@@ -0,0 +1,112 @@
+use std::cmp::Ordering;
+
+/// Fuzzy matching of menu items: the characters of the query must appear in order, matches at
+/// the start of words and runs of consecutive characters rank higher.
+pub struct SearchUtils {}
+
+impl SearchUtils {
+    /// Score of `candidate` for the query, higher is better. `None` if it doesn't match.
+    pub fn fuzzy_score(candidate: &str, query: &str) -> Option<i64> {
+        let query: Vec<char> = query
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .flat_map(char::to_lowercase)
+            .collect();
+        let chars: Vec<char> = candidate.chars().collect();
+        let mut score: i64 = 0;
+        let mut next = 0;
+        let mut previous: Option<usize> = None;
+
+        for q in query {
+            let index = (next..chars.len()).find(|&i| chars[i].to_lowercase().eq([q]))?;
+
+            score += 1;
+            if previous.is_some_and(|p| p + 1 == index) {
+                score += 5;
+            }
+            if Self::is_word_start(&chars, index) {
+                score += 8;
+            }
+            score -= (index - next) as i64;
+            previous = Some(index);
+            next = index + 1;
+        }
+        Some(score)
+    }
+
+    /// Orders the matches by score, then by length. Items keep their order for an empty query.
+    pub fn compare(a: &str, b: &str, query: &str) -> Ordering {
+        if query.trim().is_empty() {
+            return Ordering::Equal;
+        }
+        match (Self::fuzzy_score(a, query), Self::fuzzy_score(b, query)) {
+            (Some(x), Some(y)) => y.cmp(&x).then(a.len().cmp(&b.len())),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        }
+    }
+
+    fn is_word_start(chars: &[char], index: usize) -> bool {
+        match index {
+            0 => true,
+            _ => {
+                let previous = chars[index - 1];
+                !previous.is_alphanumeric()
+                    || (previous.is_lowercase() && chars[index].is_uppercase())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn search<'a>(items: &[&'a str], query: &str) -> Vec<&'a str> {
+        let mut result: Vec<&str> = items
+            .iter()
+            .filter(|item| SearchUtils::fuzzy_score(item, query).is_some())
+            .copied()
+            .collect();
+        result.sort_by(|a, b| SearchUtils::compare(a, b, query));
+        result
+    }
+
+    #[test]
+    fn fuzzy_score_matches_in_order() {
+        assert!(SearchUtils::fuzzy_score("user.service.ts", "usvc").is_some());
+        assert!(SearchUtils::fuzzy_score("user.service.ts", "USER").is_some());
+        assert!(SearchUtils::fuzzy_score("user.service.ts", "").is_some());
+        assert_eq!(SearchUtils::fuzzy_score("user.service.ts", "svu"), None);
+    }
+
+    #[test]
+    fn compare_ranks_word_starts_and_runs() {
+        let items = [
+            "src/app/shared/user-avatar.component.ts",
+            "src/app/users/user.service.ts",
+            "src/app/orders/order.service.ts",
+            "src/app/users/user.model.ts",
+        ];
+
+        assert_eq!(
+            search(&items, "user service"),
+            vec!["src/app/users/user.service.ts"]
+        );
+        assert_eq!(
+            search(&items, "usm"),
+            vec![
+                "src/app/users/user.model.ts",
+                "src/app/shared/user-avatar.component.ts"
+            ]
+        );
+        assert_eq!(search(&items, "UA")[0], items[0]);
+    }
+
+    #[test]
+    fn compare_keeps_order_without_query() {
+        assert_eq!(SearchUtils::compare("b", "a", " "), Ordering::Equal);
+        assert_eq!(SearchUtils::compare("ab", "xyz", "a"), Ordering::Less);
+    }
+}
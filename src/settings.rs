@@ -2,13 +2,16 @@ use is_executable::IsExecutable;
 use std::ops::{Index, IndexMut};
 use std::path::Path;
 
+use crate::discovery_utils::{DiscoveryResult, DiscoveryUtils};
 use crate::form_utils::FormValue;
+use crate::runner_utils::RunnerUtils;
 use crate::schema_parsing::FsEntry;
-use crate::settings_utils::{Runner, SettingsUtils};
+use crate::schematics::Collection;
+use crate::settings_utils::SettingsUtils;
 use crate::{form_utils::FormUtils, settings_utils::SettingsData};
 use gtk::prelude::{
     ButtonExt, CheckButtonExt, DialogExt, EntryBufferExtManual, EntryExt, FileChooserExt, FileExt,
-    GridExt, GtkWindowExt, WidgetExt,
+    GridExt, GtkWindowExt, ListModelExt, WidgetExt,
 };
 use relm4::gtk::ResponseType;
 use relm4::{gtk::traits::OrientableExt, *};
@@ -24,13 +27,70 @@ pub struct SettingsModel {
     package: gtk::EntryBuffer,
     show_private: bool,
     show_hidden: bool,
-    runner: Runner,
+    runner: String,
+    discovered: DiscoveryResult,
+    node_options: gtk::StringList,
+    runner_options: gtk::StringList,
+    collection_options: gtk::StringList,
     error: bool,
     success: bool,
     message: String,
+    /// Runner definitions that could not be loaded.
+    warning: String,
 }
 
 impl SettingsModel {
+    fn set_options(list: &gtk::StringList, items: Vec<String>) {
+        list.splice(
+            1,
+            list.n_items() - 1,
+            &items.iter().map(|i| i.as_str()).collect::<Vec<&str>>(),
+        );
+    }
+
+    fn get_picked<T: Clone>(items: &[T], index: u32) -> Option<T> {
+        if index == 0 || index == gtk::INVALID_LIST_POSITION {
+            return None;
+        }
+        items.get(index as usize - 1).cloned()
+    }
+
+    /// Creates the built-in runner definitions, the ones that can't be loaded are reported.
+    fn check_runners(&mut self) {
+        let utils = RunnerUtils::new();
+        let mut warnings: Vec<String> = vec![];
+
+        if let Err(err) = utils.init() {
+            warnings.push(err.to_string());
+        }
+        warnings.extend(utils.list().1.iter().map(|err| err.to_string()));
+        self.warning = warnings.join("\n");
+    }
+
+    /// Looks for node, runners and collections in a thread, it walks the filesystem. The
+    /// result comes back as `SettingsInput::Discovered`.
+    fn discover(sender: &ComponentSender<Self>) {
+        let sender = sender.clone();
+        std::thread::spawn(move || {
+            sender.input(SettingsInput::Discovered(DiscoveryUtils::new().discover()));
+        });
+    }
+
+    fn set_discovered(&mut self, discovered: DiscoveryResult) {
+        self.discovered = discovered;
+
+        Self::set_options(&self.node_options, self.discovered.node_binaries.clone());
+        Self::set_options(&self.runner_options, self.discovered.runners.clone());
+        Self::set_options(
+            &self.collection_options,
+            self.discovered
+                .collections
+                .iter()
+                .map(|c| c.name.clone())
+                .collect(),
+        );
+    }
+
     fn validate(&mut self) -> bool {
         let schematic = self.schematic_runner.text();
         let collection = self.collection.text();
@@ -40,8 +100,6 @@ impl SettingsModel {
         let values: Vec<FormValue<'_>> = vec![
             FormValue::new("node executable", node.as_str()),
             FormValue::new("schematics runner", schematic.as_str()),
-            FormValue::new("schematics collection", collection.as_str()),
-            FormValue::new("schematics package", package.as_str()),
         ];
 
         for field in values {
@@ -55,15 +113,39 @@ impl SettingsModel {
                     field.name
                 ));
                 return false;
-            } else if (field.name == "node executable" || field.name == "schematics runner")
-                && !path.is_executable()
-            {
+            } else if !path.is_executable() {
                 self.print_error(&format!("The '{}' is not executable file!", field.name));
                 return false;
             }
         }
 
-        true
+        if package.is_empty() {
+            self.print_error("The 'schematics package' field is mandatory!");
+            return false;
+        } else if Collection::resolve_package_json(package.as_str()).is_none() {
+            self.print_error("The 'schematics package' could not be found!");
+            return false;
+        }
+
+        let settings = SettingsData {
+            schematics_collection: collection.to_string(),
+            schematics_package: package.to_string(),
+            ..Default::default()
+        };
+
+        match Collection::resolve_collection_path(&settings) {
+            Some(path) if path.is_file() => true,
+            Some(_) => {
+                self.print_error("The 'schematics collection' doesn't exist or it's not a file!");
+                false
+            }
+            None => {
+                self.print_error(
+                    "The 'schematics package' doesn't declare a collection, please set the 'schematics collection' field!",
+                );
+                false
+            }
+        }
     }
 }
 
@@ -100,6 +182,11 @@ pub enum SettingsInput {
     PackageSelect(String),
     RunnerSelect(String),
     ToggleCheckbox(bool, String),
+    Discover,
+    Discovered(DiscoveryResult),
+    NodePicked(u32),
+    CollectionPicked(u32),
+    RunnerPicked(u32),
 }
 
 #[derive(Debug)]
@@ -143,6 +230,15 @@ impl SimpleComponent for SettingsModel {
                   set_label: &(model.message).to_string()
                 },
               },
+              gtk::Label {
+                set_css_classes: &["label", "warning"],
+                set_halign: gtk::Align::Center,
+                set_wrap: true,
+                #[watch]
+                set_visible: !model.warning.is_empty(),
+                #[watch]
+                set_label: &model.warning,
+              },
               gtk::Grid {
                 set_row_spacing: 5,
                 set_column_spacing: 5,
@@ -179,10 +275,22 @@ impl SimpleComponent for SettingsModel {
                   }
                 },
 
+                attach[ 3, 0, 1, 1]: node_picker = &gtk::DropDown {
+                  set_model: Some(&model.node_options),
+                  set_css_classes: &["dropdown"],
+                  set_tooltip_text: Some("Detected node executables"),
+                  #[watch]
+                  set_visible: !model.discovered.node_binaries.is_empty(),
+                  connect_selected_notify[sender] => move |dropdown| {
+                    sender.input(SettingsInput::NodePicked(dropdown.selected()));
+                  }
+                },
+
                 attach[ 0, 1, 1, 1]:  &FormUtils::new().label("schematics collection", "schematicsCollLabel", None, Some(vec! ["label_right"])),
                 attach[1, 1, 1, 1]: schematics_location = &gtk::Entry {
                   set_widget_name: "schematicsColInput",
                   set_hexpand: true,
+                  set_placeholder_text: Some("Read from the package if empty"),
                   set_css_classes: &["inputText",  "text_input"],
                   set_buffer: &model.collection,
                 },
@@ -212,9 +320,21 @@ impl SimpleComponent for SettingsModel {
                   }
                 },
 
+                attach[ 3, 1, 1, 1]: collection_picker = &gtk::DropDown {
+                  set_model: Some(&model.collection_options),
+                  set_css_classes: &["dropdown"],
+                  set_tooltip_text: Some("Detected schematics packages"),
+                  #[watch]
+                  set_visible: !model.discovered.collections.is_empty(),
+                  connect_selected_notify[sender] => move |dropdown| {
+                    sender.input(SettingsInput::CollectionPicked(dropdown.selected()));
+                  }
+                },
+
                 attach[ 0, 2, 1, 1]:  &FormUtils::new().label("Schematics package", "schematicsPkgLabel", None, Some(vec! ["label_right"])),
                 attach[1, 2, 1, 1]: package_location = &gtk::Entry {
                   set_widget_name: "schematicsPkgInput",
+                  set_placeholder_text: Some("Package name, directory or package.json"),
                   set_css_classes: &["inputText",  "text_input"],
                   set_buffer: &model.package,
                 },
@@ -272,6 +392,16 @@ impl SimpleComponent for SettingsModel {
 
                   }
                 },
+                attach[ 3, 3, 1, 1]: runner_picker = &gtk::DropDown {
+                  set_model: Some(&model.runner_options),
+                  set_css_classes: &["dropdown"],
+                  set_tooltip_text: Some("Detected schematics runners"),
+                  #[watch]
+                  set_visible: !model.discovered.runners.is_empty(),
+                  connect_selected_notify[sender] => move |dropdown| {
+                    sender.input(SettingsInput::RunnerPicked(dropdown.selected()));
+                  }
+                },
                 attach[ 0, 4, 3, 1]: show_private = &gtk::CheckButton {
                   set_label: Some("Show private"),
                   set_css_classes: &["show_private_checkbox", "checkbox"],
@@ -290,6 +420,14 @@ impl SimpleComponent for SettingsModel {
                     sender.input(SettingsInput::ToggleCheckbox(button.is_active(), "show_hidden".to_string()));
                   }
                 },
+                attach[ 3, 5, 1, 1]: discover_button = &gtk::Button {
+                  set_label: "Detect",
+                  set_tooltip: "Detect node, runners and schematics packages",
+                  set_css_classes: &["button", "action"],
+                  connect_clicked[sender] => move |_| {
+                    sender.input(SettingsInput::Discover);
+                  }
+                },
 
               }
             },
@@ -312,7 +450,7 @@ impl SimpleComponent for SettingsModel {
         root: &Self::Root,
         sender: ComponentSender<Self>,
     ) -> ComponentParts<Self> {
-        let model = SettingsModel {
+        let mut model = SettingsModel {
             hidden: params,
             node: gtk::EntryBuffer::default(),
             schematic_runner: gtk::EntryBuffer::default(),
@@ -320,13 +458,21 @@ impl SimpleComponent for SettingsModel {
             package: gtk::EntryBuffer::default(),
             show_private: false,
             show_hidden: false,
-            runner: Runner::Google,
+            runner: String::from("Google"),
+            discovered: DiscoveryResult::default(),
+            node_options: gtk::StringList::new(&["Detected..."]),
+            runner_options: gtk::StringList::new(&["Detected..."]),
+            collection_options: gtk::StringList::new(&["Detected..."]),
             error: false,
             success: false,
             message: String::default(),
+            warning: String::default(),
         };
 
-        SettingsUtils::new().init();
+        if let Err(err) = SettingsUtils::new().init() {
+            model.print_error(&err.to_string());
+        }
+        model.check_runners();
 
         let widgets = view_output!();
         ComponentParts { model, widgets }
@@ -335,18 +481,46 @@ impl SimpleComponent for SettingsModel {
     fn update(&mut self, msg: Self::Input, sender: ComponentSender<Self>) {
         match msg {
             SettingsInput::Show => {
+                self.check_runners();
                 let utils = SettingsUtils::new();
                 if utils.exists() {
-                    let data = utils.read();
+                    let data = match utils.read() {
+                        Ok(data) => data,
+                        Err(err) => {
+                            self.print_error(&err.to_string());
+                            self.hidden = false;
+                            return;
+                        }
+                    };
                     self.node.set_text(data.node_binary);
                     self.package.set_text(data.schematics_package);
                     self.collection.set_text(data.schematics_collection);
                     self.schematic_runner.set_text(data.runner_location);
                     self.show_private = data.show_private;
                     self.runner = data.runner;
+                } else {
+                    Self::discover(&sender);
                 }
                 self.hidden = false;
             }
+            SettingsInput::Discover => Self::discover(&sender),
+            SettingsInput::Discovered(discovered) => self.set_discovered(discovered),
+            SettingsInput::NodePicked(index) => {
+                if let Some(node) = Self::get_picked(&self.discovered.node_binaries, index) {
+                    self.node.set_text(node);
+                }
+            }
+            SettingsInput::RunnerPicked(index) => {
+                if let Some(runner) = Self::get_picked(&self.discovered.runners, index) {
+                    self.schematic_runner.set_text(runner);
+                }
+            }
+            SettingsInput::CollectionPicked(index) => {
+                if let Some(picked) = Self::get_picked(&self.discovered.collections, index) {
+                    self.collection.set_text(picked.collection);
+                    self.package.set_text(picked.package);
+                }
+            }
             SettingsInput::NodeSelect(file) => {
                 self.node.set_text(file);
             }
@@ -370,15 +544,13 @@ impl SimpleComponent for SettingsModel {
                 self.clear_error();
 
                 let runner_location = self.schematic_runner.text().to_string();
-                let runner = if runner_location.ends_with("/schematics") {
-                    Runner::Google
-                } else if runner_location.ends_with("/fnd") {
-                    Runner::MBH
-                } else {
-                    Runner::Custom
-                };
+                let runner = RunnerUtils::new().detect(&runner_location).name;
                 let settings = SettingsUtils::new();
-                let data = SettingsData {
+                let mut previous = settings.read().unwrap_or_default();
+                if !previous.schematics_package.is_empty() {
+                    previous.register_active();
+                }
+                let mut data = SettingsData {
                     node_binary: self.node.text().to_string(),
                     runner_location: self.schematic_runner.text().to_string(),
                     schematics_collection: self.collection.text().to_string(),
@@ -386,8 +558,15 @@ impl SimpleComponent for SettingsModel {
                     show_private: self.show_private,
                     show_hidden: self.show_hidden,
                     runner,
+                    merged_view: false,
+                    collections: previous.collections,
+                    directives: previous.directives,
                 };
-                settings.write(&data);
+                data.register_active();
+                if let Err(err) = settings.write(&data) {
+                    self.print_error(&err.to_string());
+                    return;
+                }
                 sender
                     .output(SettingsOutput::SettingsLoaded(data.clone()))
                     .unwrap();
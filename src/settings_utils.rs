@@ -1,7 +1,10 @@
 use serde::{Deserialize, Serialize};
-use std::fs::{read_to_string, write};
 use std::path::{Path, PathBuf};
 
+use crate::directives::DirectiveOptions;
+use crate::error::{AppError, Result};
+use crate::file_utils::FileUtils;
+
 pub struct SettingsUtils;
 
 impl Default for SettingsUtils {
@@ -10,22 +13,27 @@ impl Default for SettingsUtils {
     }
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
-pub enum Runner {
-    Google,
-    MBH,
-    Custom,
-}
-
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct SettingsData {
-    pub runner: Runner,
+    pub runner: String,
     pub node_binary: String,
     pub runner_location: String,
     pub schematics_collection: String,
     pub schematics_package: String,
     pub show_private: bool,
     pub show_hidden: bool,
+    #[serde(default)]
+    pub merged_view: bool,
+    #[serde(default)]
+    pub collections: Vec<RegisteredCollection>,
+    #[serde(default)]
+    pub directives: DirectiveOptions,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct RegisteredCollection {
+    pub package: String,
+    pub collection: String,
 }
 
 impl Default for SettingsData {
@@ -37,7 +45,60 @@ impl Default for SettingsData {
             schematics_package: String::default(),
             show_private: false,
             show_hidden: false,
-            runner: Runner::Google,
+            runner: String::from("Google"),
+            merged_view: false,
+            collections: vec![],
+            directives: DirectiveOptions::default(),
+        }
+    }
+}
+
+impl SettingsData {
+    pub fn get_active_collection(&self) -> RegisteredCollection {
+        RegisteredCollection {
+            package: self.schematics_package.clone(),
+            collection: self.schematics_collection.clone(),
+        }
+    }
+
+    /// All registered collections, the active one included.
+    pub fn get_collections(&self) -> Vec<RegisteredCollection> {
+        let active = self.get_active_collection();
+
+        if self.collections.iter().any(|c| c.package == active.package) {
+            return self.collections.clone();
+        }
+        let mut result = vec![active];
+        result.extend(self.collections.clone());
+        result
+    }
+
+    pub fn get_active_index(&self) -> usize {
+        self.get_collections()
+            .iter()
+            .position(|c| c.package == self.schematics_package)
+            .unwrap_or_default()
+    }
+
+    pub fn register_active(&mut self) {
+        let active = self.get_active_collection();
+
+        match self
+            .collections
+            .iter_mut()
+            .find(|c| c.package == active.package)
+        {
+            Some(registered) => registered.collection = active.collection,
+            None => self.collections.push(active),
+        }
+    }
+
+    pub fn with_collection(&self, collection: &RegisteredCollection) -> SettingsData {
+        SettingsData {
+            schematics_package: collection.package.clone(),
+            schematics_collection: collection.collection.clone(),
+            merged_view: false,
+            ..self.clone()
         }
     }
 }
@@ -55,42 +116,35 @@ impl SettingsUtils {
 
     pub fn get_config_dir() -> PathBuf {
         let home_dir = match std::env::var_os("HOME") {
-            None => std::env::current_dir().unwrap().as_os_str().to_owned(),
+            None => std::env::current_dir()
+                .unwrap_or_default()
+                .as_os_str()
+                .to_owned(),
             Some(s) => s,
         };
         Path::new(&home_dir).join("schematics-gui").to_owned()
     }
 
-    pub fn init(&self) {
+    pub fn init(&self) -> Result<()> {
         let config_dir: PathBuf = Self::get_config_dir();
 
         if !config_dir.exists() {
-            match std::fs::create_dir(config_dir) {
-                Ok(s) => s,
-                Err(err) => panic!("Could not create settings dir! {}", err),
-            }
+            std::fs::create_dir(&config_dir).map_err(|err| AppError::io(&config_dir, err))?;
         }
+        Ok(())
     }
 
-    pub fn write(&self, model: &SettingsData) {
-        let toml = toml::to_string(&model).unwrap();
+    pub fn write(&self, model: &SettingsData) -> Result<()> {
+        let toml = toml::to_string(&model)?;
         let config_dir = Self::get_config_dir();
         let path = Path::new(&config_dir).join("./settings.toml");
-        match write(path.as_os_str(), toml) {
-            Ok(s) => s,
-            Err(err) => panic!("Could not save settings! {}", err),
-        }
+        FileUtils::write_str(&path, &toml)
     }
 
-    pub fn read(&self) -> SettingsData {
+    pub fn read(&self) -> Result<SettingsData> {
         let config_dir = Self::get_config_dir();
         let path = Path::new(&config_dir).join("./settings.toml");
-        let contents = match read_to_string(path) {
-            Ok(data) => data,
-            Err(err) => panic!("Could not read settings! {}", err),
-        };
-        let settings: SettingsData = toml::from_str(&contents).unwrap();
-        settings
+        FileUtils::read_toml(&path)
     }
 }
 
@@ -100,6 +154,60 @@ mod tests {
 
     use super::*;
 
+    fn get_registered(package: &str) -> RegisteredCollection {
+        RegisteredCollection {
+            package: package.to_string(),
+            collection: String::default(),
+        }
+    }
+
+    #[test]
+    fn get_collections_includes_active() {
+        let data = SettingsData {
+            schematics_package: String::from("foo"),
+            collections: vec![get_registered("bar")],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            data.get_collections(),
+            vec![get_registered("foo"), get_registered("bar")]
+        );
+        assert_eq!(data.get_active_index(), 0);
+    }
+
+    #[test]
+    fn register_active_keeps_order() {
+        let mut data = SettingsData {
+            schematics_package: String::from("bar"),
+            schematics_collection: String::from("collection.json"),
+            collections: vec![get_registered("foo"), get_registered("bar")],
+            ..Default::default()
+        };
+
+        data.register_active();
+
+        assert_eq!(data.collections.len(), 2);
+        assert_eq!(data.collections[1].collection, "collection.json");
+        assert_eq!(data.get_active_index(), 1);
+    }
+
+    #[test]
+    fn with_collection_switches_active() {
+        let data = SettingsData {
+            schematics_package: String::from("foo"),
+            merged_view: true,
+            collections: vec![get_registered("foo"), get_registered("bar")],
+            ..Default::default()
+        };
+
+        let switched = data.with_collection(&get_registered("bar"));
+
+        assert_eq!(switched.schematics_package, "bar");
+        assert!(!switched.merged_view);
+        assert_eq!(switched.collections, data.collections);
+    }
+
     #[test]
     fn get_config_dir_without_home() {
         remove_var("HOME");
@@ -118,12 +226,12 @@ mod tests {
     }
 
     #[test]
-    #[should_panic(expected = "Could not create settings dir!")]
     fn init_fail() {
         set_var("HOME", "/tmp/no/such/dir");
 
         let settings = SettingsUtils::default();
-        settings.init();
+        let result = settings.init();
+        assert!(matches!(result, Err(AppError::Io(_, _))));
     }
 
     #[test]
@@ -131,7 +239,7 @@ mod tests {
         set_var("HOME", temp_dir());
 
         let settings = SettingsUtils::default();
-        settings.init();
+        settings.init().unwrap();
         let cfg_dir = SettingsUtils::get_config_dir();
         assert!(cfg_dir.exists())
     }
@@ -152,21 +260,21 @@ mod tests {
 
         let settings = SettingsUtils::default();
         let data = SettingsData::default();
-        settings.init();
-        settings.write(&data);
+        settings.init().unwrap();
+        settings.write(&data).unwrap();
 
         let result = settings.exists();
         assert!(result);
     }
 
     #[test]
-    #[should_panic(expected = "Could not save settings!")]
     fn write_fail() {
         set_var("HOME", "/tmp/no/dir");
 
         let settings = SettingsUtils::default();
         let data = SettingsData::default();
-        settings.write(&data);
+        let result = settings.write(&data);
+        assert!(matches!(result, Err(AppError::Io(_, _))));
     }
 
     #[test]
@@ -175,18 +283,18 @@ mod tests {
 
         let settings = SettingsUtils::default();
         let data = SettingsData::default();
-        settings.write(&data);
+        settings.write(&data).unwrap();
         let cfg_dir = SettingsUtils::get_config_dir();
         assert!(cfg_dir.join("settings.toml").exists())
     }
 
     #[test]
-    #[should_panic(expected = "Could not read settings!")]
     fn read_fail() {
         set_var("HOME", "/tmp/no/dir");
 
         let settings = SettingsUtils::default();
-        settings.read();
+        let result = settings.read();
+        assert!(matches!(result, Err(AppError::Io(_, _))));
     }
 
     #[test]
@@ -195,9 +303,9 @@ mod tests {
 
         let settings = SettingsUtils::default();
         let data = SettingsData::default();
-        settings.init();
-        settings.write(&data);
-        let loaded = settings.read();
+        settings.init().unwrap();
+        settings.write(&data).unwrap();
+        let loaded = settings.read().unwrap();
         assert_eq!(loaded, data)
     }
 }
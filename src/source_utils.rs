@@ -0,0 +1,210 @@
+use serde_json::Value;
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::{Mutex, OnceLock};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crate::error::{AppError, Result};
+use crate::schema_parsing::{CommandSource, SourceFormat};
+
+/// Items of the command sources by cwd and command line, with the time they were read.
+type Cache = Mutex<HashMap<(PathBuf, String), (Instant, Vec<String>)>>;
+
+static CACHE: OnceLock<Cache> = OnceLock::new();
+
+pub struct SourceUtils {}
+
+impl SourceUtils {
+    /// Items printed by the command in `cwd`, reused until the cache expires or `refresh` is set.
+    pub fn get_items(source: &CommandSource, cwd: &Path, refresh: bool) -> Result<Vec<String>> {
+        if !refresh {
+            if let Some(items) = Self::get_cached(source, cwd) {
+                return Ok(items);
+            }
+        }
+
+        let key = (cwd.to_path_buf(), source.to_string());
+        let cache = CACHE.get_or_init(Default::default);
+        let output = Self::run(source, cwd)?;
+        let items = Self::parse(&output, &source.format)
+            .map_err(|reason| AppError::Command(source.to_string(), reason))?;
+
+        if source.cache > 0 {
+            cache
+                .lock()
+                .unwrap()
+                .insert(key, (Instant::now(), items.clone()));
+        }
+        Ok(items)
+    }
+
+    /// Items of an earlier run of the command in `cwd` if the cache hasn't expired yet.
+    pub fn get_cached(source: &CommandSource, cwd: &Path) -> Option<Vec<String>> {
+        let key = (cwd.to_path_buf(), source.to_string());
+        let cache = CACHE.get_or_init(Default::default).lock().unwrap();
+
+        cache
+            .get(&key)
+            .filter(|(time, _)| time.elapsed() < Duration::from_secs(source.cache))
+            .map(|(_, items)| items.clone())
+    }
+
+    /// Stdout of the command, it's killed once the timeout is over.
+    pub fn run(source: &CommandSource, cwd: &Path) -> Result<String> {
+        let error = |reason: String| AppError::Command(source.to_string(), reason);
+        let mut child = Command::new(&source.command)
+            .args(&source.args)
+            .current_dir(cwd)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|err| error(err.to_string()))?;
+
+        // Read in the background, a full pipe would block the command.
+        let stdout = Self::read(child.stdout.take());
+        let stderr = Self::read(child.stderr.take());
+        let deadline = Instant::now() + Duration::from_secs(source.timeout);
+
+        let status = loop {
+            match child.try_wait() {
+                Ok(Some(status)) => break status,
+                Ok(None) if Instant::now() >= deadline => {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    return Err(error(format!("Timed out after {}s.", source.timeout)));
+                }
+                Ok(None) => thread::sleep(Duration::from_millis(20)),
+                Err(err) => return Err(error(err.to_string())),
+            }
+        };
+
+        if !status.success() {
+            let stderr = stderr.join().unwrap_or_default();
+            return Err(error(format!("{}. {}", status, stderr.trim())));
+        }
+        Ok(stdout.join().unwrap_or_default())
+    }
+
+    pub fn parse(output: &str, format: &SourceFormat) -> std::result::Result<Vec<String>, String> {
+        match format {
+            SourceFormat::Lines => Ok(output
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .map(String::from)
+                .collect()),
+            SourceFormat::Json => match serde_json::from_str::<Value>(output) {
+                Ok(Value::Array(items)) => Ok(items
+                    .into_iter()
+                    .map(|item| match item {
+                        Value::String(s) => s,
+                        other => other.to_string(),
+                    })
+                    .collect()),
+                Ok(_) => Err(String::from("Expected a JSON array.")),
+                Err(err) => Err(format!("Invalid JSON! {}", err)),
+            },
+        }
+    }
+
+    fn read<T: Read + Send + 'static>(stream: Option<T>) -> JoinHandle<String> {
+        thread::spawn(move || {
+            let mut result = String::new();
+            if let Some(mut stream) = stream {
+                let _ = stream.read_to_string(&mut result);
+            }
+            result
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env::temp_dir;
+
+    fn get_source(script: &str) -> CommandSource {
+        CommandSource {
+            command: String::from("sh"),
+            args: vec![String::from("-c"), String::from(script)],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn parse_lines() {
+        assert_eq!(
+            SourceUtils::parse("app\n\n  lib \n", &SourceFormat::Lines),
+            Ok(vec![String::from("app"), String::from("lib")])
+        );
+    }
+
+    #[test]
+    fn parse_json() {
+        assert_eq!(
+            SourceUtils::parse(r#"["app", 1, true]"#, &SourceFormat::Json),
+            Ok(vec![
+                String::from("app"),
+                String::from("1"),
+                String::from("true")
+            ])
+        );
+        assert!(SourceUtils::parse(r#"{ "app": 1 }"#, &SourceFormat::Json).is_err());
+        assert!(SourceUtils::parse("app", &SourceFormat::Json).is_err());
+    }
+
+    #[test]
+    fn run_success() {
+        let source = get_source("echo app; echo lib");
+
+        assert_eq!(
+            SourceUtils::get_items(&source, &temp_dir(), true).unwrap(),
+            vec!["app", "lib"]
+        );
+    }
+
+    #[test]
+    fn run_failure() {
+        let source = get_source("echo oops >&2; exit 3");
+        let err = SourceUtils::run(&source, &temp_dir()).unwrap_err();
+
+        assert!(matches!(err, AppError::Command(_, _)));
+        assert!(err.to_string().contains("oops"));
+    }
+
+    #[test]
+    fn run_timeout() {
+        let source = CommandSource {
+            timeout: 0,
+            ..get_source("sleep 5")
+        };
+        let started = Instant::now();
+
+        assert!(SourceUtils::run(&source, &temp_dir())
+            .unwrap_err()
+            .to_string()
+            .contains("Timed out"));
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn get_items_cached() {
+        let cwd = temp_dir().join("schematics-gui-source");
+        let _ = std::fs::remove_dir_all(&cwd);
+        std::fs::create_dir_all(&cwd).unwrap();
+        let source = get_source("echo x >> count; wc -l < count");
+
+        assert_eq!(SourceUtils::get_cached(&source, &cwd), None);
+        assert_eq!(SourceUtils::get_items(&source, &cwd, false).unwrap(), ["1"]);
+        assert_eq!(
+            SourceUtils::get_cached(&source, &cwd),
+            Some(vec![String::from("1")])
+        );
+        assert_eq!(SourceUtils::get_items(&source, &cwd, false).unwrap(), ["1"]);
+        assert_eq!(SourceUtils::get_items(&source, &cwd, true).unwrap(), ["2"]);
+    }
+}
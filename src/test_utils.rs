@@ -0,0 +1,17 @@
+use std::env::temp_dir;
+use std::fs::{create_dir_all, remove_dir_all, write};
+use std::path::PathBuf;
+
+/// Empty directory under the temp dir with the files written in it, e.g.: `directives/nx`.
+pub fn write_tree(prefix: &str, files: &[(&str, &str)]) -> PathBuf {
+    let root = temp_dir().join("schematics-gui").join(prefix);
+    let _ = remove_dir_all(&root);
+    create_dir_all(&root).unwrap();
+
+    for (file, contents) in files {
+        let path = root.join(file);
+        create_dir_all(path.parent().unwrap()).unwrap();
+        write(path, contents).unwrap();
+    }
+    root
+}
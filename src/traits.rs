@@ -1,9 +1,12 @@
 use relm4::gtk::glib::object::Object;
+use relm4::gtk::glib::BoxedAnyObject;
 use relm4::gtk::prelude::{Cast, IsA};
-use relm4::gtk::Widget;
+use relm4::gtk::{StringObject, Widget};
 use sourceview5::prelude::BufferExt;
 use sourceview5::Buffer;
 
+use crate::string_list_item::StringListItem;
+
 pub trait Validator {
     fn print_error(&mut self, message: &str);
     fn print_success(&mut self, message: &str);
@@ -33,6 +36,16 @@ pub trait WidgetUtils {
     ) -> bool {
         widget.clone().upcast::<Widget>().downcast::<T>().is_ok()
     }
+
+    /// Value of an item of a list model, menus hold `StringObject`s or `StringListItem`s.
+    fn get_list_item_value(item: &Object) -> String {
+        if let Some(string) = item.downcast_ref::<StringObject>() {
+            return string.string().to_string();
+        }
+        item.downcast_ref::<BoxedAnyObject>()
+            .map(|wrapper| wrapper.borrow::<StringListItem>().value.clone())
+            .unwrap_or_default()
+    }
 }
 
 #[macro_export]
@@ -1,19 +1,17 @@
 use crate::command_builder::{InputType, Param};
 use crate::form_utils::FormUtils;
 use crate::schema_parsing::ColorEntryFormat;
-use crate::string_list_item::StringListItem;
 use crate::traits::WidgetUtils;
-use relm4::gtk::gio::ListModel;
-use relm4::gtk::glib::{BoxedAnyObject, GString};
+use relm4::gtk::glib::GString;
 use relm4::gtk::prelude::{
     ButtonExt, Cast, CheckButtonExt, ColorChooserExt, EntryBufferExtManual, EntryExt, ListModelExt,
     RangeExt, SelectionModelExt, TextBufferExt, TextViewExt, ToggleButtonExt, WidgetExt,
 };
 use relm4::gtk::{
     Box, Calendar, CheckButton, ColorButton, ComboBoxText, DropDown, Entry, EntryBuffer, ListView,
-    MultiSelection, Range, SpinButton, StringObject, Switch, TextView, ToggleButton, Widget,
+    MenuButton, MultiSelection, Range, SpinButton, Stack, StringObject, Switch, TextView,
+    ToggleButton, Widget,
 };
-use std::cell::Ref;
 
 pub struct ValueExtractor<'l> {
     widget: &'l Widget,
@@ -49,11 +47,7 @@ impl<'l> ValueExtractor<'l> {
                 Param::new(name, value, InputType::TextArea),
             )
         } else if self.is_a::<_, Switch>(self.widget) {
-            Some(Param {
-                name,
-                value: self.get_switch_value(),
-                kind: InputType::Switch,
-            })
+            Some(Param::new(name, self.get_switch_value(), InputType::Switch))
         } else if self.is_a::<_, ColorButton>(self.widget) {
             let value = self.get_color_button_value();
             self.get_optional_param_value(
@@ -65,46 +59,86 @@ impl<'l> ValueExtractor<'l> {
             let kind = container.css_classes();
 
             if kind.contains(&GString::from("slider_input_container")) {
-                Some(Param {
-                    name: container
-                        .first_child()
-                        .unwrap()
-                        .next_sibling()
-                        .unwrap()
-                        .widget_name()
-                        .to_string(),
-                    value: self.get_slider_value(&container),
-                    kind: InputType::Slider,
-                })
+                let scale = container.first_child().unwrap().next_sibling().unwrap();
+                let kind = match Self::is_integer_input(&scale) {
+                    true => InputType::Integer,
+                    false => InputType::Slider,
+                };
+                Some(Param::new(
+                    scale.widget_name().to_string(),
+                    self.get_slider_value(&container),
+                    kind,
+                ))
             } else if kind.contains(&GString::from("time_input_container")) {
-                Some(Param {
-                    name: container.widget_name().to_string(),
-                    value: self.get_time_input_value(&container),
-                    kind: InputType::Time,
-                })
+                Some(Param::new(
+                    container.widget_name().to_string(),
+                    self.get_time_input_value(&container),
+                    InputType::Time,
+                ))
             } else if kind.contains(&GString::from("date_time_input_container")) {
-                Some(Param {
-                    name: container.widget_name().to_string(),
-                    value: self.get_date_time_input_value(&container),
-                    kind: InputType::DateTime,
-                })
+                Some(Param::new(
+                    container.widget_name().to_string(),
+                    self.get_date_time_input_value(&container),
+                    InputType::DateTime,
+                ))
             } else if kind.contains(&GString::from("date_input_container")) {
-                Some(Param {
-                    name: container.widget_name().to_string(),
-                    value: self.get_date_input_value(&container),
-                    kind: InputType::Date,
-                })
+                Some(Param::new(
+                    container.widget_name().to_string(),
+                    self.get_date_input_value(&container),
+                    InputType::Date,
+                ))
             } else if kind.contains(&GString::from("radio_group_container"))
                 || kind.contains(&GString::from("toggle_group_container"))
             {
-                Some(Param {
-                    name: container.first_child().unwrap().widget_name().to_string(),
-                    value: self.get_group_value(&container),
-                    kind: match kind.contains(&GString::from("radio_group_container")) {
-                        true => InputType::RadioGroup,
-                        false => InputType::ToggleGroup,
-                    },
-                })
+                let group_kind = match kind.contains(&GString::from("radio_group_container")) {
+                    true => InputType::RadioGroup,
+                    false => InputType::ToggleGroup,
+                };
+                Some(Param::new(
+                    container.first_child().unwrap().widget_name().to_string(),
+                    self.get_group_value(&container),
+                    group_kind,
+                ))
+            } else if kind.contains(&GString::from("type_switch_container")) {
+                let stack = container.last_child().unwrap().downcast::<Stack>().unwrap();
+                ValueExtractor::new(&stack.visible_child()?).get_name_value()
+            } else if kind.contains(&GString::from("source_menu_container")) {
+                let menu = container.first_child()?.first_child()?;
+                let param = ValueExtractor::new(&menu).get_name_value()?;
+                self.get_optional_param_value(param.value.clone(), param)
+            } else if kind.contains(&GString::from("searchable_list_container")) {
+                let (_, list) = FormUtils::get_search_list_parts(&container)?;
+                ValueExtractor::new(list.upcast_ref()).get_name_value()
+            } else if kind.contains(&GString::from("searchable_dropdown_container")) {
+                let button = container.first_child()?.downcast::<MenuButton>().ok()?;
+                let value = button.label().unwrap_or_default().to_string();
+                self.get_optional_param_value(
+                    value.clone(),
+                    Param::new(
+                        container.widget_name().to_string(),
+                        value,
+                        InputType::DropDown,
+                    ),
+                )
+            } else if kind.contains(&GString::from("searchable_combo_container")) {
+                let value = self.get_entry_value(container.first_child());
+                Some(Param::new(
+                    container.widget_name().to_string(),
+                    value,
+                    InputType::Combobox,
+                ))
+            } else if kind.contains(&GString::from("json_input_container")) {
+                let value = self.get_json_input_value(&container)?;
+                self.get_optional_param_value(
+                    value.clone(),
+                    Param::new(container.widget_name().to_string(), value, InputType::Json),
+                )
+            } else if kind.contains(&GString::from("map_input_container")) {
+                let param = Param::map(
+                    container.widget_name().to_string(),
+                    &self.get_map_entries(&container),
+                );
+                self.get_optional_param_value(param.value.clone(), param)
             } else if kind.contains(&GString::from("file_input_container"))
                 || kind.contains(&GString::from("dir_input_container"))
                 || kind.contains(&GString::from("color_input_container"))
@@ -126,41 +160,42 @@ impl<'l> ValueExtractor<'l> {
                 Some(Param::default())
             }
         } else if self.is_a::<_, CheckButton>(self.widget) {
-            Some(Param {
+            Some(Param::new(
                 name,
-                value: self.get_check_button_value(),
-                kind: InputType::Checkbox,
-            })
+                self.get_check_button_value(),
+                InputType::Checkbox,
+            ))
         } else if self.is_a::<_, ToggleButton>(self.widget) {
-            Some(Param {
+            Some(Param::new(
                 name,
-                value: self.get_toggle_button_value(),
-                kind: InputType::Toggle,
-            })
+                self.get_toggle_button_value(),
+                InputType::Toggle,
+            ))
         } else if self.is_a::<_, SpinButton>(self.widget) {
             let value = self.get_numeric_input();
-            self.get_optional_param_value(
-                value.clone(),
-                Param::new(name, value, InputType::Numeric),
-            )
+            let kind = match Self::is_integer_input(self.widget) {
+                true => InputType::Integer,
+                false => InputType::Numeric,
+            };
+            self.get_optional_param_value(value.clone(), Param::new(name, value, kind))
         } else if self.is_a::<_, DropDown>(self.widget) {
-            Some(Param {
+            Some(Param::new(
                 name,
-                value: self.get_dropdown_value(),
-                kind: InputType::DropDown,
-            })
+                self.get_dropdown_value(),
+                InputType::DropDown,
+            ))
         } else if self.is_a::<_, ListView>(self.widget) {
-            Some(Param {
+            Some(Param::new(
                 name,
-                value: self.get_multiselect_value(),
-                kind: InputType::Multiselect,
-            })
+                self.get_multiselect_value(),
+                InputType::Multiselect,
+            ))
         } else if self.is_a::<_, ComboBoxText>(self.widget) {
-            Some(Param {
+            Some(Param::new(
                 name,
-                value: self.get_combo_box_value(),
-                kind: InputType::Combobox,
-            })
+                self.get_combo_box_value(),
+                InputType::Combobox,
+            ))
         } else {
             Some(Param::default())
         }
@@ -252,8 +287,11 @@ impl<'l> ValueExtractor<'l> {
 
     fn get_numeric_input(&self) -> String {
         let entry = self.widget.clone().downcast::<SpinButton>().unwrap();
-        // TODO format according to options
-        entry.value().to_string()
+        format!("{:.*}", entry.digits() as usize, entry.value())
+    }
+
+    fn is_integer_input(widget: &Widget) -> bool {
+        widget.has_css_class("integer_input")
     }
 
     fn get_switch_value(&self) -> String {
@@ -296,15 +334,65 @@ impl<'l> ValueExtractor<'l> {
         }
     }
 
-    fn get_dropdown_value(&self) -> String {
-        let dropdown = self.widget.clone().downcast::<DropDown>().unwrap();
-        let selected = dropdown.selected_item();
-        selected
+    /// Compact JSON, `None` when the text doesn't parse.
+    fn get_json_input_value(&self, container: &Box) -> Option<String> {
+        let text = Self::get_json_input_text(container);
+
+        match text.trim().is_empty() {
+            true => Some(String::default()),
+            false => serde_json::from_str::<serde_json::Value>(&text)
+                .ok()
+                .map(|value| value.to_string()),
+        }
+    }
+
+    /// Parse error of a JSON editor, `None` when it's empty or holds valid JSON.
+    pub fn get_json_input_error(container: &Box) -> Option<String> {
+        let text = Self::get_json_input_text(container);
+
+        match text.trim().is_empty() {
+            true => None,
+            false => serde_json::from_str::<serde_json::Value>(&text)
+                .err()
+                .map(|err| err.to_string()),
+        }
+    }
+
+    fn get_json_input_text(container: &Box) -> String {
+        let bf = container
+            .first_child()
             .unwrap()
-            .downcast::<StringObject>()
+            .downcast::<TextView>()
             .unwrap()
-            .string()
-            .to_string()
+            .buffer();
+        bf.text(&bf.start_iter(), &bf.end_iter(), false).to_string()
+    }
+
+    fn get_map_entries(&self, container: &Box) -> Vec<(String, String)> {
+        let mut result: Vec<(String, String)> = vec![];
+        let rows = container.first_child().unwrap().downcast::<Box>().unwrap();
+        let mut row = rows.first_child();
+
+        while let Some(widget) = row {
+            let key = widget.first_child().unwrap();
+            let value = key.next_sibling().unwrap();
+            result.push((
+                self.get_entry_value(Some(key)),
+                self.get_entry_value(Some(value)),
+            ));
+            row = widget.next_sibling();
+        }
+        result
+    }
+
+    fn get_dropdown_value(&self) -> String {
+        let dropdown = self.widget.clone().downcast::<DropDown>().unwrap();
+        // Empty while the items of a sourced menu are loading.
+        dropdown
+            .selected_item()
+            .and_then(|item| item.downcast::<StringObject>().ok())
+            .map(|item| item.string().to_string())
+            .unwrap_or_default()
     }
 
     fn get_multiselect_value(&self) -> String {
@@ -315,15 +403,13 @@ impl<'l> ValueExtractor<'l> {
             .unwrap()
             .downcast::<MultiSelection>()
             .unwrap();
-        let list_model = selection.model().unwrap().downcast::<ListModel>().unwrap();
+        let list_model = selection.model().unwrap();
         let items_no = list_model.n_items();
 
         for i in 0..items_no {
             if selection.is_selected(i) {
                 let item = list_model.item(i).unwrap();
-                let wrapper = item.downcast::<BoxedAnyObject>().unwrap();
-                let value: Ref<StringListItem> = wrapper.borrow();
-                result.push(value.value.to_string());
+                result.push(Self::get_list_item_value(&item));
             }
         }
 
@@ -1,21 +1,19 @@
-use relm4::gtk::gio::ListModel;
 use relm4::gtk::glib::object::Object;
-use relm4::gtk::glib::{BoxedAnyObject, DateTime, GString, TimeZone};
+use relm4::gtk::glib::{DateTime, GString, TimeZone};
 use relm4::gtk::prelude::{
-    ButtonExt, Cast, CheckButtonExt, ColorChooserExt, ComboBoxExt, EntryBufferExtManual, EntryExt,
-    ListModelExt, ListModelExtManual, RangeExt, SelectionModelExt, TextBufferExt, TextViewExt,
-    ToggleButtonExt, WidgetExt,
+    ButtonExt, Cast, CheckButtonExt, ColorChooserExt, ComboBoxExt, EditableExt,
+    EntryBufferExtManual, EntryExt, ListModelExt, ListModelExtManual, RangeExt, SelectionModelExt,
+    TextBufferExt, TextViewExt, ToggleButtonExt, WidgetExt,
 };
 use relm4::gtk::{
     Box, Calendar, CheckButton, ColorButton, ComboBoxText, DropDown, Entry, EntryBuffer, ListView,
-    MultiSelection, Range, SpinButton, StringList, StringObject, Switch, TextView, ToggleButton,
-    Widget,
+    MenuButton, MultiSelection, Range, SpinButton, Stack, StringList, StringObject, Switch,
+    TextView, ToggleButton, Widget,
 };
 
+use crate::command_builder::{InputType, Param};
 use crate::form_utils::FormUtils;
-use crate::string_list_item::StringListItem;
 use crate::traits::WidgetUtils;
-use std::cell::Ref;
 use toml::Value;
 
 pub struct ValueLoader<'l> {
@@ -58,6 +56,23 @@ impl<'l> ValueLoader<'l> {
                 || kind.contains(&GString::from("toggle_group_container"))
             {
                 self.set_group_value(value, &container);
+            } else if kind.contains(&GString::from("type_switch_container")) {
+                self.set_type_switch_value(value, &container);
+            } else if kind.contains(&GString::from("source_menu_container")) {
+                FormUtils::set_source_menu_value(&container, &Self::get_str(value));
+            } else if kind.contains(&GString::from("searchable_list_container")) {
+                if let Some((search, list)) = FormUtils::get_search_list_parts(&container) {
+                    search.set_text("");
+                    ValueLoader::new(list.upcast_ref()).set_value(value, &list.widget_name());
+                }
+            } else if kind.contains(&GString::from("searchable_dropdown_container")) {
+                self.set_searchable_dropdown_value(value, &container);
+            } else if kind.contains(&GString::from("searchable_combo_container")) {
+                self.set_entry_value(value, container.first_child());
+            } else if kind.contains(&GString::from("json_input_container")) {
+                self.set_json_input_value(value, &container);
+            } else if kind.contains(&GString::from("map_input_container")) {
+                self.set_map_value(value, &container);
             } else if kind.contains(&GString::from("file_input_container"))
                 || kind.contains(&GString::from("dir_input_container"))
                 || kind.contains(&GString::from("color_input_container"))
@@ -86,12 +101,12 @@ impl<'l> ValueLoader<'l> {
             self.widget.clone().downcast::<Entry>().unwrap().buffer()
         };
 
-        bf.set_text(value.as_str().unwrap_or_default());
+        bf.set_text(&Self::get_str(value));
     }
 
     fn set_text_view_value(&self, value: &Value) {
         let bf = self.widget.clone().downcast::<TextView>().unwrap().buffer();
-        bf.set_text(value.as_str().unwrap_or_default());
+        bf.set_text(&Self::get_str(value));
     }
 
     fn set_slider_value(&self, value: &Value, container: &Box) {
@@ -102,13 +117,7 @@ impl<'l> ValueLoader<'l> {
             .unwrap()
             .downcast::<Range>()
             .unwrap();
-        scale.set_value(
-            value
-                .as_str()
-                .unwrap_or("0.0")
-                .parse::<f64>()
-                .unwrap_or_default(),
-        );
+        scale.set_value(Self::get_number(value));
     }
 
     fn set_date_time_value(&self, value: &Value, container: &Box) {
@@ -190,51 +199,61 @@ impl<'l> ValueLoader<'l> {
 
     fn set_toggle_button_value(&self, value: &Value) {
         let toggle = self.widget.clone().downcast::<ToggleButton>().unwrap();
-        toggle.set_active(
-            value
-                .as_str()
-                .unwrap_or("false")
-                .parse::<bool>()
-                .unwrap_or_default(),
-        );
+        toggle.set_active(Self::get_bool(value));
     }
 
     fn set_check_button_value(&self, value: &Value) {
         let checkbox = self.widget.clone().downcast::<CheckButton>().unwrap();
-        checkbox.set_active(
-            value
-                .as_str()
-                .unwrap_or("false")
-                .parse::<bool>()
-                .unwrap_or_default(),
-        );
+        checkbox.set_active(Self::get_bool(value));
     }
 
     fn set_numeric_input(&self, value: &Value) {
         let entry = self.widget.clone().downcast::<SpinButton>().unwrap();
-        entry.set_value(
-            value
+        entry.set_value(Self::get_number(value));
+    }
+
+    /// Text of a value as the widgets show it, the items of an array are comma separated.
+    /// Profiles saved by older versions hold strings only.
+    fn get_str(value: &Value) -> String {
+        match value {
+            Value::String(s) => s.clone(),
+            Value::Array(items) => items
+                .iter()
+                .map(Self::get_str)
+                .collect::<Vec<String>>()
+                .join(","),
+            other => other.to_string(),
+        }
+    }
+
+    fn get_bool(value: &Value) -> bool {
+        match value {
+            Value::Boolean(b) => *b,
+            _ => Self::get_str(value).parse::<bool>().unwrap_or_default(),
+        }
+    }
+
+    /// Numbers saved by older versions are strings.
+    fn get_number(value: &Value) -> f64 {
+        match value {
+            Value::Integer(i) => *i as f64,
+            Value::Float(f) => *f,
+            _ => value
                 .as_str()
                 .unwrap_or("0.0")
                 .parse::<f64>()
                 .unwrap_or_default(),
-        );
+        }
     }
 
     fn set_switch_value(&self, value: &Value) {
         let switch = self.widget.clone().downcast::<Switch>().unwrap();
-        switch.set_active(
-            value
-                .as_str()
-                .unwrap_or("false")
-                .parse::<bool>()
-                .unwrap_or_default(),
-        );
+        switch.set_active(Self::get_bool(value));
     }
 
     fn set_combo_box_value(&self, value: &Value) {
         let combo: ComboBoxText = self.widget.clone().downcast::<ComboBoxText>().unwrap();
-        combo.set_active_id(Some(value.as_str().unwrap_or("")));
+        combo.set_active_id(Some(&Self::get_str(value)));
     }
 
     fn set_color_button_value(&self, value: &Value) {
@@ -244,6 +263,7 @@ impl<'l> ValueLoader<'l> {
     }
 
     fn set_group_value(&self, value: &Value, container: &Box) {
+        let value = Self::get_str(value);
         let mut w = container.first_child();
 
         loop {
@@ -251,13 +271,13 @@ impl<'l> ValueLoader<'l> {
             if self.is_a::<_, CheckButton>(widget) {
                 let button = widget.clone().downcast::<CheckButton>().unwrap();
                 let current_label = button.label().unwrap();
-                if current_label == value.as_str().unwrap() {
+                if current_label == value {
                     return button.set_active(true);
                 }
             } else if self.is_a::<_, ToggleButton>(widget) {
                 let button = widget.clone().downcast::<ToggleButton>().unwrap();
                 let current_label = button.label().unwrap();
-                if current_label == value.as_str().unwrap() {
+                if current_label == value {
                     return button.set_active(true);
                 }
             }
@@ -269,6 +289,79 @@ impl<'l> ValueLoader<'l> {
         }
     }
 
+    /// Whether a stored value can be shown by the widget of a JSON Schema `type`.
+    fn is_type(value: &str, r#type: &str) -> bool {
+        match r#type {
+            "boolean" => value.parse::<bool>().is_ok(),
+            "number" | "integer" => value.parse::<f64>().is_ok(),
+            _ => true,
+        }
+    }
+
+    /// Switches to the first type that fits the value, preferring anything over `string`.
+    fn set_type_switch_value(&self, value: &Value, container: &Box) {
+        let switcher = container
+            .first_child()
+            .unwrap()
+            .downcast::<DropDown>()
+            .unwrap();
+        let stack = container.last_child().unwrap().downcast::<Stack>().unwrap();
+        let text = Self::get_str(value);
+        let mut pages: Vec<(u32, String, Widget)> = vec![];
+        let mut child = stack.first_child();
+
+        while let Some(widget) = child {
+            let title = stack.page(&widget).title().unwrap_or_default().to_string();
+            pages.push((pages.len() as u32, title, widget.clone()));
+            child = widget.next_sibling();
+        }
+
+        let page = pages
+            .iter()
+            .find(|(_, t, _)| t != "string" && Self::is_type(&text, t))
+            .or(pages.iter().find(|(_, t, _)| t == "string"));
+
+        if let Some((index, _, widget)) = page {
+            switcher.set_selected(*index);
+            ValueLoader::new(widget).set_value(value, &widget.widget_name());
+        }
+    }
+
+    fn set_json_input_value(&self, value: &Value, container: &Box) {
+        let text = Self::get_str(value);
+        let pretty = match value {
+            Value::String(_) => serde_json::from_str::<serde_json::Value>(&text),
+            _ => serde_json::to_value(value),
+        }
+        .and_then(|json| serde_json::to_string_pretty(&json));
+        let bf = container
+            .first_child()
+            .unwrap()
+            .downcast::<TextView>()
+            .unwrap()
+            .buffer();
+
+        bf.set_text(&pretty.unwrap_or(text));
+    }
+
+    fn set_map_value(&self, value: &Value, container: &Box) {
+        let entries = match value {
+            Value::String(s) => {
+                Param::new(String::default(), s.clone(), InputType::Map).get_map_entries()
+            }
+            Value::Table(table) => table
+                .iter()
+                .map(|(key, value)| match value.as_str() {
+                    Some(s) => (key.clone(), s.to_string()),
+                    None => (key.clone(), value.to_string()),
+                })
+                .collect(),
+            _ => vec![],
+        };
+        let rows = container.first_child().unwrap().downcast::<Box>().unwrap();
+        FormUtils::new().set_map_entries(&rows, &entries);
+    }
+
     fn set_dropdown_value(&self, value: &Value) {
         let dropdown = self.widget.clone().downcast::<DropDown>().unwrap();
         let items = dropdown.model().unwrap().downcast::<StringList>().unwrap();
@@ -280,17 +373,45 @@ impl<'l> ValueLoader<'l> {
                     .unwrap()
                     .string()
                     .to_string();
-                return s == *value.as_str().unwrap();
+                return s == Self::get_str(value);
             }
             false
         });
 
-        dropdown.set_selected(selected.unwrap() as u32);
+        if let Some(index) = selected {
+            dropdown.set_selected(index as u32);
+        }
+    }
+
+    /// Label of the button, only if the value is one of the items.
+    fn set_searchable_dropdown_value(&self, value: &Value, container: &Box) {
+        let value = Self::get_str(value);
+        let button = container
+            .first_child()
+            .and_then(|w| w.downcast::<MenuButton>().ok());
+        let parts = button
+            .as_ref()
+            .and_then(FormUtils::get_search_button_list)
+            .and_then(|content| FormUtils::get_search_list_parts(&content));
+        let (button, (search, list)) = match (button, parts) {
+            (Some(button), Some(parts)) => (button, parts),
+            _ => return,
+        };
+        search.set_text("");
+
+        let model = list.model().unwrap();
+        let found = (0..model.n_items())
+            .filter_map(|i| model.item(i))
+            .any(|item| Self::get_list_item_value(&item) == value);
+        if found {
+            button.set_label(&value);
+        }
     }
 
     fn set_multiselect_value(&self, value: &Value) {
         let mut selected_indexes: Vec<u32> = vec![];
-        let selected_values = value.as_str().unwrap().split(',').collect::<Vec<&str>>();
+        let value = Self::get_str(value);
+        let selected_values = value.split(',').collect::<Vec<&str>>();
         let selection = self
             .widget
             .clone()
@@ -300,14 +421,12 @@ impl<'l> ValueLoader<'l> {
             .unwrap()
             .downcast::<MultiSelection>()
             .unwrap();
-        let list_model = selection.model().unwrap().downcast::<ListModel>().unwrap();
+        let list_model = selection.model().unwrap();
         let items_no = list_model.n_items();
 
         for i in 0..items_no {
             let item = list_model.item(i).unwrap();
-            let wrapper = item.downcast::<BoxedAnyObject>().unwrap();
-            let value: Ref<StringListItem> = wrapper.borrow();
-            if selected_values.contains(&value.value.as_str()) {
+            if selected_values.contains(&Self::get_list_item_value(&item).as_str()) {
                 selected_indexes.push(i);
             }
         }
@@ -0,0 +1,332 @@
+use chrono::format::{Item, StrftimeItems};
+use chrono::Local;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use toml::{map::Map, Value};
+
+use crate::schema_parsing::CommandSource;
+use crate::source_utils::SourceUtils;
+
+/// Values the placeholders of a profile are resolved with.
+#[derive(Default, Debug, Clone)]
+pub struct VariableContext {
+    pub cwd: Option<PathBuf>,
+    /// Answers to the `${prompt:<label>}` placeholders, by label.
+    pub answers: HashMap<String, String>,
+    /// Output of the `${git:<what>}` placeholders, by what. Resolved beforehand with `get_git`
+    /// as it runs git.
+    pub git: HashMap<String, String>,
+}
+
+/// Placeholders in profile values: `${cwd}`, `${cwdName}`, `${env:<name>}`, `${date:<format>}`,
+/// `${git:branch}`, `${git:commit}` and `${prompt:<label>}`. `$${` stands for a literal `${`.
+pub struct VariableUtils {}
+
+impl VariableUtils {
+    /// Labels of the prompts in the string values, in order and without duplicates.
+    pub fn get_prompts(data: &Map<String, Value>) -> Vec<String> {
+        Self::get_text_prompts(&Self::get_texts(data))
+    }
+
+    /// Labels of the prompts in the texts, e.g.: the values of the command params.
+    pub fn get_text_prompts(texts: &[String]) -> Vec<String> {
+        Self::get_args(texts, "prompt")
+    }
+
+    /// Strings of the values, nested ones included.
+    pub fn get_texts(data: &Map<String, Value>) -> Vec<String> {
+        let mut result: Vec<String> = vec![];
+
+        for value in data.values() {
+            Self::visit(value, &mut |text| result.push(text.to_string()));
+        }
+        result
+    }
+
+    pub fn has_git(texts: &[String]) -> bool {
+        !Self::get_args(texts, "git").is_empty()
+    }
+
+    /// Output of the git placeholders in the texts, for `VariableContext::git`. Runs git in
+    /// `cwd`, so it blocks until git is done.
+    pub fn get_git(texts: &[String], cwd: Option<&Path>) -> HashMap<String, String> {
+        let cwd = match cwd {
+            Some(cwd) => cwd,
+            None => return HashMap::new(),
+        };
+        Self::get_args(texts, "git")
+            .into_iter()
+            .filter_map(|what| Some((what.clone(), Self::git(cwd, &what)?)))
+            .collect()
+    }
+
+    /// Arguments of the placeholders of a kind, in order and without duplicates.
+    fn get_args(texts: &[String], kind: &str) -> Vec<String> {
+        let mut result: Vec<String> = vec![];
+
+        for name in texts.iter().flat_map(|text| Self::get_placeholders(text)) {
+            match name.split_once(':') {
+                Some((k, arg)) if k == kind && !result.iter().any(|r| r == arg) => {
+                    result.push(arg.to_string());
+                }
+                _ => {}
+            }
+        }
+        result
+    }
+
+    /// Values with the placeholders of the strings, nested ones included, resolved.
+    pub fn resolve(data: &Map<String, Value>, context: &VariableContext) -> Map<String, Value> {
+        data.iter()
+            .map(|(key, value)| (key.clone(), Self::resolve_value(value, context)))
+            .collect()
+    }
+
+    fn resolve_value(value: &Value, context: &VariableContext) -> Value {
+        match value {
+            Value::String(text) => Value::String(Self::interpolate(text, context)),
+            Value::Array(items) => Value::Array(
+                items
+                    .iter()
+                    .map(|item| Self::resolve_value(item, context))
+                    .collect(),
+            ),
+            Value::Table(table) => Value::Table(Self::resolve(table, context)),
+            other => other.clone(),
+        }
+    }
+
+    /// The text with its placeholders resolved, the ones that can't be are kept as they are.
+    pub fn interpolate(text: &str, context: &VariableContext) -> String {
+        let mut result = String::new();
+        let mut rest = text;
+
+        while let Some(start) = rest.find("${") {
+            if rest[..start].ends_with('$') {
+                result.push_str(&rest[..start - 1]);
+                result.push_str("${");
+                rest = &rest[start + 2..];
+                continue;
+            }
+            let end = match rest[start..].find('}') {
+                Some(end) => start + end,
+                None => break,
+            };
+            let name = &rest[start + 2..end];
+
+            result.push_str(&rest[..start]);
+            match Self::get_value(name, context) {
+                Some(value) => result.push_str(&value),
+                None => result.push_str(&rest[start..=end]),
+            }
+            rest = &rest[end + 1..];
+        }
+        result.push_str(rest);
+        result
+    }
+
+    /// Profile values with the placeholders put back where the form still holds the value
+    /// they were resolved to. `templates` maps the keys to the (template, resolved) values.
+    pub fn restore(
+        data: &Map<String, Value>,
+        templates: &HashMap<String, (Value, Value)>,
+    ) -> Map<String, Value> {
+        data.iter()
+            .map(|(key, value)| match templates.get(key) {
+                Some((template, resolved)) if resolved == value => (key.clone(), template.clone()),
+                _ => (key.clone(), value.clone()),
+            })
+            .collect()
+    }
+
+    fn get_value(name: &str, context: &VariableContext) -> Option<String> {
+        let cwd = context.cwd.as_ref();
+        let (kind, arg) = name.split_once(':').unwrap_or((name, ""));
+
+        match (kind, arg) {
+            ("cwd", "") => cwd.map(|cwd| cwd.to_string_lossy().to_string()),
+            ("cwdName", "") => cwd
+                .and_then(|cwd| cwd.file_name())
+                .map(|name| name.to_string_lossy().to_string()),
+            ("env", name) => std::env::var(name).ok(),
+            ("date", format) => Self::format_date(format),
+            ("git", what) => context.git.get(what).cloned(),
+            ("prompt", label) => context.answers.get(label).cloned(),
+            _ => None,
+        }
+    }
+
+    /// Current local date, `%Y-%m-%d` if no format is given. `None` for invalid formats.
+    fn format_date(format: &str) -> Option<String> {
+        let format = match format.is_empty() {
+            true => "%Y-%m-%d",
+            false => format,
+        };
+        let items: Vec<Item> = StrftimeItems::new(format).collect();
+
+        if items.iter().any(|item| matches!(item, Item::Error)) {
+            return None;
+        }
+        Some(
+            Local::now()
+                .format_with_items(items.into_iter())
+                .to_string(),
+        )
+    }
+
+    fn git(cwd: &Path, what: &str) -> Option<String> {
+        let args: &[&str] = match what {
+            "branch" => &["branch", "--show-current"],
+            "commit" => &["rev-parse", "--short", "HEAD"],
+            _ => return None,
+        };
+        let source = CommandSource {
+            command: String::from("git"),
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+            timeout: 5,
+            ..Default::default()
+        };
+        SourceUtils::run(&source, cwd)
+            .ok()
+            .map(|output| output.trim().to_string())
+            .filter(|output| !output.is_empty())
+    }
+
+    /// Names of the placeholders in a text, e.g.: `env:USER`.
+    fn get_placeholders(text: &str) -> Vec<&str> {
+        let mut result: Vec<&str> = vec![];
+        let mut offset = 0;
+
+        while let Some(start) = text[offset..].find("${").map(|start| offset + start) {
+            let end = match text[start..].find('}') {
+                Some(end) => start + end,
+                None => break,
+            };
+            if !text[..start].ends_with('$') {
+                result.push(&text[start + 2..end]);
+            }
+            offset = end + 1;
+        }
+        result
+    }
+
+    fn visit(value: &Value, action: &mut impl FnMut(&str)) {
+        match value {
+            Value::String(text) => action(text),
+            Value::Array(items) => items.iter().for_each(|item| Self::visit(item, action)),
+            Value::Table(table) => table.values().for_each(|item| Self::visit(item, action)),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env::temp_dir;
+
+    fn get_context() -> VariableContext {
+        VariableContext {
+            cwd: Some(PathBuf::from("/work/shop")),
+            answers: HashMap::from([(String::from("Feature name"), String::from("cart"))]),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn interpolate_success() {
+        let context = get_context();
+        std::env::set_var("SCHEMATICS_GUI_TEST", "tester");
+
+        assert_eq!(
+            VariableUtils::interpolate("${cwdName}-${prompt:Feature name}", &context),
+            "shop-cart"
+        );
+        assert_eq!(
+            VariableUtils::interpolate("${cwd}/src", &context),
+            "/work/shop/src"
+        );
+        assert_eq!(
+            VariableUtils::interpolate("by ${env:SCHEMATICS_GUI_TEST}", &context),
+            "by tester"
+        );
+        assert_eq!(
+            VariableUtils::interpolate("${date:%Y}", &context),
+            Local::now().format("%Y").to_string()
+        );
+    }
+
+    #[test]
+    fn interpolate_unresolved() {
+        let context = VariableContext::default();
+
+        assert_eq!(
+            VariableUtils::interpolate("${cwd} ${prompt:Name} ${date:%Q} ${nope}", &context),
+            "${cwd} ${prompt:Name} ${date:%Q} ${nope}"
+        );
+        assert_eq!(
+            VariableUtils::interpolate("$${cwd} ${", &context),
+            "${cwd} ${"
+        );
+    }
+
+    #[test]
+    fn git_branch() {
+        let cwd = temp_dir().join("schematics-gui-git");
+        let _ = std::fs::remove_dir_all(&cwd);
+        std::fs::create_dir_all(&cwd).unwrap();
+        let git = std::process::Command::new("git")
+            .args(["init", "-q", "-b", "feature/cart"])
+            .current_dir(&cwd)
+            .status();
+        if !git.is_ok_and(|status| status.success()) {
+            return;
+        }
+        let texts = vec![String::from("${git:branch}"), String::from("${git:nope}")];
+        let context = VariableContext {
+            git: VariableUtils::get_git(&texts, Some(&cwd)),
+            cwd: Some(cwd),
+            ..Default::default()
+        };
+
+        assert!(VariableUtils::has_git(&texts));
+        assert_eq!(
+            VariableUtils::interpolate("${git:branch} ${git:nope}", &context),
+            "feature/cart ${git:nope}"
+        );
+    }
+
+    #[test]
+    fn resolve_and_restore() {
+        let data: Map<String, Value> = [
+            (String::from("name"), Value::from("${prompt:Feature name}")),
+            (
+                String::from("tags"),
+                Value::from(vec!["${prompt:Tag}", "x"]),
+            ),
+            (String::from("skipTests"), Value::from(true)),
+        ]
+        .into_iter()
+        .collect();
+        let resolved = VariableUtils::resolve(&data, &get_context());
+        let templates = HashMap::from([(
+            String::from("name"),
+            (data["name"].clone(), resolved["name"].clone()),
+        )]);
+
+        assert_eq!(
+            VariableUtils::get_prompts(&data),
+            vec!["Feature name", "Tag"]
+        );
+        assert_eq!(
+            VariableUtils::get_text_prompts(&[
+                String::from("${prompt:Tag}-${prompt:Tag}"),
+                String::from("$${prompt:Name}"),
+            ]),
+            vec!["Tag"]
+        );
+        assert_eq!(resolved["name"], Value::from("cart"));
+        assert_eq!(resolved["tags"], Value::from(vec!["${prompt:Tag}", "x"]));
+        assert_eq!(VariableUtils::restore(&resolved, &templates), data);
+    }
+}
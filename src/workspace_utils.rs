@@ -0,0 +1,377 @@
+use serde_json::{Map, Value};
+use std::path::{Path, PathBuf};
+
+use crate::file_utils::FileUtils;
+
+/// Project configuration found in the cwd: `angular.json`, an Nx workspace (`workspace.json`,
+/// `nx.json` and `project.json` files) or `nest-cli.json`.
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct Workspace {
+    /// Name of the configuration file, e.g.: `angular.json`.
+    pub file: String,
+    pub default_project: Option<String>,
+    pub projects: Vec<WorkspaceProject>,
+    /// Per-schematic defaults, keyed by `<collection>:<schematic>` or nested by collection.
+    pub schematics: Map<String, Value>,
+    /// Defaults applied to every schematic, e.g.: the `generateOptions` of Nest.
+    pub generate_options: Map<String, Value>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct WorkspaceProject {
+    pub name: String,
+    pub root: String,
+    pub source_root: Option<String>,
+    pub prefix: Option<String>,
+    pub schematics: Map<String, Value>,
+}
+
+impl WorkspaceProject {
+    fn from_json(name: &str, json: &Value) -> Self {
+        WorkspaceProject {
+            name: json["name"].as_str().unwrap_or(name).to_string(),
+            root: json["root"].as_str().unwrap_or_default().to_string(),
+            source_root: json["sourceRoot"].as_str().map(String::from),
+            prefix: json["prefix"].as_str().map(String::from),
+            schematics: WorkspaceUtils::get_schematics(json),
+        }
+    }
+}
+
+impl Workspace {
+    pub fn get_project_names(&self) -> Vec<String> {
+        self.projects.iter().map(|p| p.name.clone()).collect()
+    }
+
+    pub fn get_project(&self, name: &str) -> Option<&WorkspaceProject> {
+        self.projects.iter().find(|p| p.name == name)
+    }
+
+    /// Project the CLI picks when `--project` is omitted: the `defaultProject` or the only one.
+    pub fn get_default_project(&self) -> Option<String> {
+        if self.default_project.is_some() {
+            return self.default_project.clone();
+        }
+        match self.projects.as_slice() {
+            [project] => Some(project.name.clone()),
+            _ => None,
+        }
+    }
+
+    /// Defaults of a schematic, the ones of `project` take precedence over the workspace ones.
+    pub fn get_schematic_defaults(
+        &self,
+        collection: &str,
+        schematic: &str,
+        project: Option<&str>,
+    ) -> Map<String, Value> {
+        let mut result = self.generate_options.clone();
+        let project = project.and_then(|name| self.get_project(name));
+
+        for schematics in [Some(&self.schematics), project.map(|p| &p.schematics)]
+            .into_iter()
+            .flatten()
+        {
+            let defaults = [
+                schematics.get(&format!("{}:{}", collection, schematic)),
+                schematics.get(collection).and_then(|c| c.get(schematic)),
+            ];
+            for value in defaults.into_iter().flatten() {
+                if let Some(map) = value.as_object() {
+                    result.extend(map.clone());
+                }
+            }
+        }
+        result
+    }
+}
+
+pub struct WorkspaceUtils {}
+
+impl WorkspaceUtils {
+    pub fn read(cwd: &Path) -> Option<Workspace> {
+        Self::read_angular(cwd)
+            .or_else(|| Self::read_nx(cwd))
+            .or_else(|| Self::read_nest(cwd))
+    }
+
+    pub fn get_default_project(cwd: &Path) -> Option<String> {
+        Self::read(cwd)?.get_default_project()
+    }
+
+    /// Schematic defaults of a workspace or project, Nx calls them `generators`.
+    fn get_schematics(json: &Value) -> Map<String, Value> {
+        json["schematics"]
+            .as_object()
+            .or(json["generators"].as_object())
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    fn read_json(path: &Path) -> Option<Value> {
+        FileUtils::read_json(path).ok()
+    }
+
+    /// Projects of `angular.json` and `workspace.json`, the latter may point to the directory
+    /// of a `project.json` instead of inlining it.
+    fn get_projects(cwd: &Path, json: &Value) -> Vec<WorkspaceProject> {
+        let empty = Map::new();
+        json["projects"]
+            .as_object()
+            .unwrap_or(&empty)
+            .iter()
+            .filter_map(|(name, project)| match project {
+                Value::String(root) => {
+                    let json = Self::read_json(&cwd.join(root).join("project.json"))?;
+                    Some(WorkspaceProject {
+                        root: root.clone(),
+                        ..WorkspaceProject::from_json(name, &json)
+                    })
+                }
+                Value::Object(_) => Some(WorkspaceProject::from_json(name, project)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn read_angular(cwd: &Path) -> Option<Workspace> {
+        let json = Self::read_json(&cwd.join("angular.json"))?;
+
+        Some(Workspace {
+            file: String::from("angular.json"),
+            default_project: json["defaultProject"].as_str().map(String::from),
+            projects: Self::get_projects(cwd, &json),
+            schematics: Self::get_schematics(&json),
+            ..Default::default()
+        })
+    }
+
+    fn read_nx(cwd: &Path) -> Option<Workspace> {
+        let nx = Self::read_json(&cwd.join("nx.json"));
+        let workspace = Self::read_json(&cwd.join("workspace.json"));
+        let standalone = Self::read_json(&cwd.join("project.json"));
+
+        if nx.is_none() && workspace.is_none() && standalone.is_none() {
+            return None;
+        }
+        let nx = nx.unwrap_or_default();
+        let projects = match (&workspace, &standalone) {
+            (Some(workspace), _) => Self::get_projects(cwd, workspace),
+            (None, Some(project)) => vec![WorkspaceProject::from_json(
+                &Self::get_dir_name(cwd),
+                project,
+            )],
+            (None, None) => Self::find_nx_projects(cwd, &nx),
+        };
+
+        Some(Workspace {
+            file: String::from(match workspace {
+                Some(_) => "workspace.json",
+                None => "nx.json",
+            }),
+            default_project: nx["defaultProject"].as_str().map(String::from),
+            projects,
+            schematics: Self::get_schematics(&nx),
+            ..Default::default()
+        })
+    }
+
+    /// `project.json` files in the apps and libs directories of an Nx workspace.
+    fn find_nx_projects(cwd: &Path, nx: &Value) -> Vec<WorkspaceProject> {
+        let layout = &nx["workspaceLayout"];
+        let dirs = [
+            layout["appsDir"].as_str().unwrap_or("apps"),
+            layout["libsDir"].as_str().unwrap_or("libs"),
+        ];
+        let mut result: Vec<WorkspaceProject> = vec![];
+
+        for dir in dirs {
+            let mut roots: Vec<PathBuf> =
+                FileUtils::read_fs_entries(&cwd.join(dir), true).unwrap_or_default();
+            roots.sort();
+            for root in roots {
+                if let Some(json) = Self::read_json(&root.join("project.json")) {
+                    let name = Self::get_dir_name(&root);
+                    result.push(WorkspaceProject {
+                        root: format!("{}/{}", dir, name),
+                        ..WorkspaceProject::from_json(&name, &json)
+                    });
+                }
+            }
+        }
+        result
+    }
+
+    fn read_nest(cwd: &Path) -> Option<Workspace> {
+        let json = Self::read_json(&cwd.join("nest-cli.json"))?;
+        let mut projects = Self::get_projects(cwd, &json);
+
+        if projects.is_empty() {
+            projects.push(WorkspaceProject {
+                name: Self::get_dir_name(cwd),
+                source_root: json["sourceRoot"].as_str().map(String::from),
+                ..Default::default()
+            });
+        }
+
+        Some(Workspace {
+            file: String::from("nest-cli.json"),
+            default_project: json["root"]
+                .as_str()
+                .and_then(|root| projects.iter().find(|p| p.root == root))
+                .map(|p| p.name.clone()),
+            projects,
+            generate_options: json["generateOptions"]
+                .as_object()
+                .cloned()
+                .unwrap_or_default(),
+            ..Default::default()
+        })
+    }
+
+    fn get_dir_name(path: &Path) -> String {
+        path.file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::write_tree;
+
+    #[test]
+    fn get_default_project_success() {
+        let root = write_tree(
+            "workspace/default",
+            &[(
+                "angular.json",
+                r#"{ "defaultProject": "app", "projects": { "app": {}, "lib": {} } }"#,
+            )],
+        );
+
+        assert_eq!(
+            WorkspaceUtils::get_default_project(&root),
+            Some(String::from("app"))
+        );
+    }
+
+    #[test]
+    fn get_default_project_single() {
+        let root = write_tree(
+            "workspace/single",
+            &[("angular.json", r#"{ "projects": { "lib": {} } }"#)],
+        );
+
+        assert_eq!(
+            WorkspaceUtils::get_default_project(&root),
+            Some(String::from("lib"))
+        );
+    }
+
+    #[test]
+    fn get_default_project_ambiguous() {
+        let root = write_tree(
+            "workspace/ambiguous",
+            &[(
+                "angular.json",
+                r#"{ "projects": { "app": {}, "lib": {} } }"#,
+            )],
+        );
+
+        assert_eq!(WorkspaceUtils::get_default_project(&root), None);
+        assert_eq!(
+            WorkspaceUtils::get_default_project(&root.join("missing")),
+            None
+        );
+    }
+
+    #[test]
+    fn read_angular_success() {
+        let root = write_tree(
+            "workspace/angular",
+            &[(
+                "angular.json",
+                r#"{
+                    "schematics": { "@schematics/angular:component": { "style": "scss", "flat": true } },
+                    "projects": {
+                        "app": {
+                            "root": "",
+                            "sourceRoot": "src",
+                            "prefix": "app",
+                            "schematics": { "@schematics/angular": { "component": { "flat": false } } }
+                        }
+                    }
+                }"#,
+            )],
+        );
+        let workspace = WorkspaceUtils::read(&root).unwrap();
+        let project = workspace.get_project("app").unwrap();
+
+        assert_eq!(workspace.file, "angular.json");
+        assert_eq!(project.source_root.as_deref(), Some("src"));
+        assert_eq!(project.prefix.as_deref(), Some("app"));
+        assert_eq!(
+            Value::Object(workspace.get_schematic_defaults(
+                "@schematics/angular",
+                "component",
+                Some("app")
+            )),
+            serde_json::json!({ "style": "scss", "flat": false })
+        );
+    }
+
+    #[test]
+    fn read_nx_success() {
+        let root = write_tree(
+            "workspace/nx",
+            &[
+                (
+                    "nx.json",
+                    r#"{ "generators": { "@nx/angular:component": { "style": "css" } } }"#,
+                ),
+                (
+                    "apps/shop/project.json",
+                    r#"{ "name": "shop", "sourceRoot": "apps/shop/src", "prefix": "shop" }"#,
+                ),
+                ("libs/ui/project.json", r#"{ "sourceRoot": "libs/ui/src" }"#),
+            ],
+        );
+        let workspace = WorkspaceUtils::read(&root).unwrap();
+
+        assert_eq!(workspace.file, "nx.json");
+        assert_eq!(workspace.get_project_names(), vec!["shop", "ui"]);
+        assert_eq!(workspace.projects[1].root, "libs/ui");
+        assert_eq!(
+            workspace.get_schematic_defaults("@nx/angular", "component", None)["style"],
+            "css"
+        );
+    }
+
+    #[test]
+    fn read_nest_success() {
+        let root = write_tree(
+            "workspace/nest",
+            &[(
+                "nest-cli.json",
+                r#"{
+                    "root": "apps/api",
+                    "generateOptions": { "spec": false },
+                    "projects": {
+                        "api": { "root": "apps/api", "sourceRoot": "apps/api/src" },
+                        "auth": { "root": "libs/auth", "sourceRoot": "libs/auth/src" }
+                    }
+                }"#,
+            )],
+        );
+        let workspace = WorkspaceUtils::read(&root).unwrap();
+
+        assert_eq!(workspace.get_default_project(), Some(String::from("api")));
+        assert_eq!(
+            workspace.get_schematic_defaults("@nestjs/schematics", "service", Some("auth"))["spec"],
+            false
+        );
+    }
+}
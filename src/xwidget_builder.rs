@@ -1,10 +1,13 @@
 use relm4::gtk::glib::object::Cast;
 use relm4::gtk::{Box, Orientation, Widget};
+use std::rc::Rc;
 
+use crate::directives::DirectiveRegistry;
 use crate::form_utils::FormUtils;
 use crate::schema_parsing::{
-    ChoiceEntry, ChoiceType, ColorEntry, ColorEntryType, DateEntry, DateEntryType, FsEntry,
-    MenuEntry, MenuType, NumericEntry, NumericType, SchemaProp, TextEntry, XWidget, XWidgetType,
+    ChoiceEntry, ChoiceType, ColorEntry, ColorEntryType, CommandSource, DateEntry, DateEntryType,
+    FsEntry, MenuEntry, MenuType, NumericEntry, NumericType, NumericValueType, SchemaProp,
+    TextEntry, XWidget, XWidgetType,
 };
 
 pub struct XWidgetBuilder {
@@ -13,16 +16,24 @@ pub struct XWidgetBuilder {
     utils: FormUtils,
     field: String,
     cwd: Option<String>,
+    /// Directives of the form, built once for all its widgets.
+    registry: Rc<DirectiveRegistry>,
 }
 
 impl XWidgetBuilder {
-    pub fn new(prop: &SchemaProp, field: String, cwd: Option<String>) -> Self {
+    pub fn new(
+        prop: &SchemaProp,
+        field: String,
+        cwd: Option<String>,
+        registry: Rc<DirectiveRegistry>,
+    ) -> Self {
         XWidgetBuilder {
             prop: prop.clone(),
             xwidget: prop.x_widget.clone().unwrap(),
             utils: FormUtils::new(),
             field,
             cwd,
+            registry,
         }
     }
 
@@ -49,10 +60,14 @@ impl XWidgetBuilder {
             }
             return self.get_text_input(c.clone()).upcast();
         } else if let XWidgetType::Numeric(c) = &self.xwidget.options {
+            let mut options = c.clone();
+            if self.prop.is_integer() {
+                options.value_type = NumericValueType::Int;
+            }
             if c.r#type == NumericType::Input {
-                return self.get_numeric_input(c.clone()).upcast();
+                return self.get_numeric_input(options).upcast();
             } else {
-                return self.get_slider_input(c.clone()).upcast();
+                return self.get_slider_input(options).upcast();
             }
         } else if let XWidgetType::Choice(c) = &self.xwidget.options {
             if c.r#type == ChoiceType::Switch {
@@ -62,49 +77,42 @@ impl XWidgetBuilder {
             }
             return self.get_checkbox_input(c.clone()).upcast();
         } else if let XWidgetType::Menu(c) = &self.xwidget.options {
-            if c.r#type == MenuType::Combobox {
-                return self.get_combo(c.clone()).upcast();
-            } else if c.r#type == MenuType::Radio {
-                return self.get_radio_group(c.clone()).upcast();
-            } else if c.r#type == MenuType::Toggle {
-                return self.get_toggle_group(c.clone()).upcast();
-            } else if c.r#type == MenuType::Multiselect {
-                return self.get_multiselect(c.clone()).upcast();
+            if let Some(source) = &c.source {
+                return self.get_sourced_menu(c.clone(), source).upcast();
             }
-            return self.get_menu(c.clone()).upcast();
+            return self.get_menu_widget(c.clone(), &self.get_items());
         } else {
             return Box::new(Orientation::Horizontal, 0).upcast();
         }
     }
 
-    fn get_items(&self) -> Vec<String> {
-        let empty: Vec<String> = vec![];
-        if self.prop.r#type == "string" && self.prop.r#enum.is_some() {
-            return self.prop.r#enum.as_ref().unwrap().clone();
-        }
-        if (self.prop.r#type == "array" || self.prop.r#type == "string")
-            && self.prop.x_prompt.is_some()
-            && self.prop.x_prompt.as_ref().unwrap().has_items()
-        {
-            let prompt = self.prop.x_prompt.as_ref().unwrap();
-            if self.cwd.is_some() {
-                if prompt.has_modules() {
-                    return prompt.get_modules(self.cwd.as_ref().unwrap());
-                } else if prompt.has_models() {
-                    return prompt.get_models(self.cwd.as_ref().unwrap());
-                } else if prompt.has_dirs() {
-                    return prompt.get_dirs_or_files(true, self.cwd.as_ref().unwrap());
-                } else if prompt.has_files() {
-                    return prompt.get_dirs_or_files(false, self.cwd.as_ref().unwrap());
-                }
-            }
-            if prompt.has_modules() || prompt.has_models() || prompt.has_dirs() {
-                return prompt.get_items_placeholder();
-            }
-            return prompt.get_items();
+    fn get_menu_widget(&self, options: MenuEntry, items: &[String]) -> Widget {
+        match options.r#type {
+            MenuType::Combobox => self.get_combo(options, items),
+            MenuType::Radio => self.get_radio_group(options, items),
+            MenuType::Toggle => self.get_toggle_group(options, items),
+            MenuType::Multiselect => self.get_multiselect(options, items),
+            MenuType::DropDown => self.get_menu(options, items),
         }
+    }
+
+    /// Menu whose items are printed by the command of `source`, rebuilt when they change.
+    fn get_sourced_menu(&self, options: MenuEntry, source: &CommandSource) -> Widget {
+        let builder = XWidgetBuilder::new(
+            &self.prop,
+            self.field.clone(),
+            self.cwd.clone(),
+            self.registry.clone(),
+        );
+        self.utils
+            .sourced_menu(&self.field, source, self.cwd.clone(), move |items| {
+                builder.get_menu_widget(options.clone(), items)
+            })
+            .upcast()
+    }
 
-        empty
+    fn get_items(&self) -> Vec<String> {
+        self.prop.get_items(self.cwd.as_deref(), &self.registry)
     }
 
     fn get_file_input(&self, options: FsEntry) -> Widget {
@@ -174,58 +182,51 @@ impl XWidgetBuilder {
             .upcast()
     }
 
-    fn get_menu(&self, options: MenuEntry) -> Widget {
+    fn get_menu(&self, options: MenuEntry, items: &[String]) -> Widget {
+        if options.searchable {
+            return self
+                .utils
+                .searchable_dropdown(&self.field, items, self.prop.default.clone())
+                .upcast();
+        }
         self.utils
-            .dropdown(
-                &self.field,
-                &self.get_items(),
-                Some(options),
-                self.prop.default.clone(),
-            )
+            .dropdown(&self.field, items, self.prop.default.clone())
             .upcast()
     }
 
-    fn get_multiselect(&self, options: MenuEntry) -> Widget {
+    fn get_multiselect(&self, options: MenuEntry, items: &[String]) -> Widget {
+        if options.searchable {
+            return self
+                .utils
+                .searchable_multiselect(&self.field, items, self.prop.default.clone())
+                .upcast();
+        }
         self.utils
-            .multiselect_input(
-                &self.field,
-                &self.get_items(),
-                Some(options),
-                self.prop.default.clone(),
-            )
+            .multiselect_input(&self.field, items, Some(options), self.prop.default.clone())
             .upcast()
     }
 
-    fn get_combo(&self, options: MenuEntry) -> Widget {
+    fn get_combo(&self, options: MenuEntry, items: &[String]) -> Widget {
+        if options.searchable {
+            return self
+                .utils
+                .searchable_combobox(&self.field, items, self.prop.default.clone())
+                .upcast();
+        }
         self.utils
-            .combobox_text(
-                &self.field,
-                &self.get_items(),
-                Some(options),
-                self.prop.default.clone(),
-            )
+            .combobox_text(&self.field, items, Some(options), self.prop.default.clone())
             .upcast()
     }
 
-    fn get_radio_group(&self, options: MenuEntry) -> Widget {
+    fn get_radio_group(&self, options: MenuEntry, items: &[String]) -> Widget {
         self.utils
-            .radio_group(
-                &self.field,
-                &self.get_items(),
-                Some(options),
-                self.prop.default.clone(),
-            )
+            .radio_group(&self.field, items, Some(options), self.prop.default.clone())
             .upcast()
     }
 
-    fn get_toggle_group(&self, options: MenuEntry) -> Widget {
+    fn get_toggle_group(&self, options: MenuEntry, items: &[String]) -> Widget {
         self.utils
-            .toggle_group(
-                &self.field,
-                &self.get_items(),
-                Some(options),
-                self.prop.default.clone(),
-            )
+            .toggle_group(&self.field, items, Some(options), self.prop.default.clone())
             .upcast()
     }
 
@@ -0,0 +1,305 @@
+use relm4::gtk::prelude::{
+    BoxExt, ButtonExt, Cast, CheckButtonExt, EditableExt, ListModelExt, OrientableExt, WidgetExt,
+};
+use relm4::{gtk, ComponentParts, ComponentSender, SimpleComponent};
+use serde_json::{Map, Value};
+
+use crate::form_utils::FormUtils;
+use crate::xwidget_options::{OptionKind, WidgetOption, XWidgetOptions};
+
+const NO_WIDGET: &str = "default";
+
+pub struct XWidgetEditorModel {
+    schema: Value,
+    properties: gtk::StringList,
+    widget_types: gtk::StringList,
+    property: Option<String>,
+    widget: Option<String>,
+    loaded: Value,
+    inputs: Vec<(WidgetOption, gtk::Widget)>,
+    form: gtk::Box,
+    error: Option<String>,
+}
+
+impl XWidgetEditorModel {
+    fn get_list_items(list: &gtk::StringList) -> Vec<String> {
+        (0..list.n_items())
+            .filter_map(|i| list.string(i))
+            .map(|s| s.to_string())
+            .collect()
+    }
+
+    fn get_property_index(&self) -> u32 {
+        Self::get_list_items(&self.properties)
+            .iter()
+            .position(|p| Some(p) == self.property.as_ref())
+            .unwrap_or_default() as u32
+    }
+
+    fn get_widget_index(&self) -> u32 {
+        Self::get_list_items(&self.widget_types)
+            .iter()
+            .position(|w| Some(w) == self.widget.as_ref())
+            .unwrap_or_default() as u32
+    }
+
+    fn get_prop(&self) -> &Value {
+        match &self.property {
+            Some(property) => &self.schema["properties"][property],
+            None => &Value::Null,
+        }
+    }
+
+    fn get_input(&self, option: &WidgetOption, value: &Value) -> gtk::Widget {
+        match &option.kind {
+            OptionKind::Bool => {
+                let check = gtk::CheckButton::new();
+                check.set_active(value.as_bool().unwrap_or_default());
+                check.upcast()
+            }
+            OptionKind::Choice(choices) => {
+                let items: Vec<&str> = choices.iter().map(|c| c.as_str()).collect();
+                let dropdown = gtk::DropDown::from_strings(&items);
+                let selected = choices
+                    .iter()
+                    .position(|c| Some(c.as_str()) == value.as_str())
+                    .unwrap_or_default();
+                dropdown.set_selected(selected as u32);
+                dropdown.upcast()
+            }
+            _ => {
+                let entry = gtk::Entry::new();
+                entry.set_text(&WidgetOption::format_value(value));
+                entry.upcast()
+            }
+        }
+    }
+
+    fn get_value(option: &WidgetOption, input: &gtk::Widget) -> Result<Value, String> {
+        match &option.kind {
+            OptionKind::Bool => Ok(Value::Bool(
+                input
+                    .downcast_ref::<gtk::CheckButton>()
+                    .unwrap()
+                    .is_active(),
+            )),
+            OptionKind::Choice(choices) => {
+                let selected = input.downcast_ref::<gtk::DropDown>().unwrap().selected();
+                Ok(Value::String(
+                    choices.get(selected as usize).cloned().unwrap_or_default(),
+                ))
+            }
+            _ => option.parse_value(&input.downcast_ref::<gtk::Entry>().unwrap().text()),
+        }
+    }
+
+    /// Rebuilds the option inputs of the selected widget, pre-filled from the schema.
+    fn build_form(&mut self) {
+        while let Some(child) = self.form.first_child() {
+            self.form.remove(&child);
+        }
+        self.inputs.clear();
+        self.error = None;
+        self.loaded = self.get_prop()["x-widget"].clone();
+
+        let widget = match &self.widget {
+            Some(widget) => widget.clone(),
+            None => return,
+        };
+        let current = XWidgetOptions::get_xwidget(self.get_prop())
+            .filter(|(w, _)| w == &widget)
+            .map(|(_, options)| options)
+            .unwrap_or_default();
+        let utils = FormUtils::new();
+
+        for option in XWidgetOptions::get_options(&widget) {
+            let value = current.get(&option.name).unwrap_or(&option.default);
+            let input = self.get_input(&option, value);
+            self.form
+                .append(&utils.label(&option.name, &option.name, None, None));
+            self.form.append(&input);
+            self.inputs.push((option, input));
+        }
+    }
+
+    fn select_property(&mut self, property: Option<String>) {
+        self.property = property;
+        self.widget = XWidgetOptions::get_xwidget(self.get_prop()).map(|(w, _)| w);
+        self.build_form();
+    }
+
+    fn load(&mut self, schema: Value) {
+        self.schema = schema;
+        let names: Vec<String> = self.schema["properties"]
+            .as_object()
+            .map(|props| props.keys().cloned().collect())
+            .unwrap_or_default();
+
+        if names != Self::get_list_items(&self.properties) {
+            let items: Vec<&str> = names.iter().map(|n| n.as_str()).collect();
+            self.properties.splice(0, self.properties.n_items(), &items);
+        }
+
+        match &self.property {
+            Some(property) if names.contains(property) => {
+                if self.get_prop()["x-widget"] != self.loaded {
+                    self.select_property(Some(property.clone()));
+                }
+            }
+            _ => self.select_property(names.first().cloned()),
+        }
+    }
+
+    fn apply(&mut self) -> Option<Value> {
+        let property = self.property.clone()?;
+        let mut options = Map::new();
+
+        for (option, input) in &self.inputs {
+            match Self::get_value(option, input) {
+                Ok(value) => {
+                    options.insert(option.name.clone(), value);
+                }
+                Err(err) => {
+                    self.error = Some(err);
+                    return None;
+                }
+            }
+        }
+
+        let mut schema = self.schema.clone();
+        XWidgetOptions::set_xwidget(&mut schema, &property, self.widget.as_deref(), &options);
+        self.error = None;
+        Some(schema)
+    }
+}
+
+#[derive(Debug)]
+pub enum XWidgetEditorInput {
+    Load(Value),
+    PropertyPicked(u32),
+    WidgetPicked(u32),
+    Apply,
+}
+
+#[derive(Debug)]
+pub enum XWidgetEditorOutput {
+    Changed(Value),
+}
+
+#[relm4::component(pub)]
+impl SimpleComponent for XWidgetEditorModel {
+    type Input = XWidgetEditorInput;
+    type Output = XWidgetEditorOutput;
+    type Init = ();
+
+    view! {
+        #[root]
+        gtk::Box {
+          set_orientation: gtk::Orientation::Vertical,
+          set_spacing: 5,
+          set_css_classes: &["xwidget_editor"],
+          gtk::Label {
+            set_xalign: 0.0,
+            set_css_classes: &["label"],
+            set_label: "Property",
+          },
+          gtk::DropDown {
+            set_model: Some(&model.properties),
+            set_css_classes: &["dropdown"],
+            #[watch]
+            set_selected: model.get_property_index(),
+            connect_selected_notify[sender] => move |dropdown| {
+              sender.input(XWidgetEditorInput::PropertyPicked(dropdown.selected()));
+            }
+          },
+          gtk::Label {
+            set_xalign: 0.0,
+            set_css_classes: &["label"],
+            set_label: "Widget",
+          },
+          gtk::DropDown {
+            set_model: Some(&model.widget_types),
+            set_css_classes: &["dropdown"],
+            #[watch]
+            set_selected: model.get_widget_index(),
+            connect_selected_notify[sender] => move |dropdown| {
+              sender.input(XWidgetEditorInput::WidgetPicked(dropdown.selected()));
+            }
+          },
+          append: &model.form,
+          gtk::Label {
+            set_xalign: 0.0,
+            set_wrap: true,
+            set_css_classes: &["label", "error"],
+            #[watch]
+            set_visible: model.error.is_some(),
+            #[watch]
+            set_label: model.error.as_deref().unwrap_or_default(),
+          },
+          gtk::Button {
+            set_label: "Apply",
+            set_halign: gtk::Align::End,
+            #[watch]
+            set_sensitive: model.property.is_some(),
+            connect_clicked[sender] => move |_| {
+              sender.input(XWidgetEditorInput::Apply);
+            }
+          }
+        }
+    }
+
+    fn init(
+        _init: Self::Init,
+        root: &Self::Root,
+        sender: ComponentSender<Self>,
+    ) -> ComponentParts<Self> {
+        let mut widget_types = vec![String::from(NO_WIDGET)];
+        widget_types.extend(XWidgetOptions::get_widget_types());
+        let items: Vec<&str> = widget_types.iter().map(|w| w.as_str()).collect();
+
+        let model = XWidgetEditorModel {
+            schema: Value::Null,
+            properties: gtk::StringList::new(&[]),
+            widget_types: gtk::StringList::new(&items),
+            property: None,
+            widget: None,
+            loaded: Value::Null,
+            inputs: vec![],
+            form: gtk::Box::new(gtk::Orientation::Vertical, 5),
+            error: None,
+        };
+
+        let widgets = view_output!();
+        ComponentParts { model, widgets }
+    }
+
+    fn update(&mut self, message: Self::Input, sender: ComponentSender<Self>) {
+        match message {
+            XWidgetEditorInput::Load(schema) => {
+                self.load(schema);
+            }
+            XWidgetEditorInput::PropertyPicked(index) => {
+                let property = self.properties.string(index).map(|p| p.to_string());
+                if property.is_some() && property != self.property {
+                    self.select_property(property);
+                }
+            }
+            XWidgetEditorInput::WidgetPicked(index) => {
+                let widget = self
+                    .widget_types
+                    .string(index)
+                    .map(|w| w.to_string())
+                    .filter(|w| w != NO_WIDGET);
+                if widget != self.widget {
+                    self.widget = widget;
+                    self.build_form();
+                }
+            }
+            XWidgetEditorInput::Apply => {
+                if let Some(schema) = self.apply() {
+                    let _ = sender.output(XWidgetEditorOutput::Changed(schema));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,476 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_aux::prelude::serde_introspect;
+use serde_json::{json, Map, Value};
+
+use crate::schema_parsing::{
+    ChoiceType, ColorEntryFormat, ColorEntryType, CommandSource, CurrentValuePosType,
+    DateEntryType, HintType, IconPositionType, JustificationType, MarkData, MenuType, NumericType,
+    NumericValueType, OrientationType, PurposeType, SourceFormat, TextAlignmentType, XWidgetType,
+};
+
+pub const JSON_SCHEMA_FILE_NAME: &str = "x-widget.schema.json";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum OptionKind {
+    Bool,
+    Number,
+    Text,
+    Choice(Vec<String>),
+    Json,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct WidgetOption {
+    pub name: String,
+    pub kind: OptionKind,
+    pub default: Value,
+}
+
+impl WidgetOption {
+    /// Parses the text typed into an input of this option.
+    pub fn parse_value(&self, text: &str) -> Result<Value, String> {
+        match self.kind {
+            _ if text.is_empty() && self.default.is_null() => Ok(Value::Null),
+            OptionKind::Number => text
+                .parse::<i64>()
+                .map(Value::from)
+                .or_else(|_| text.parse::<f64>().map(Value::from))
+                .map_err(|_| format!("`{}` must be a number", self.name)),
+            OptionKind::Json => serde_json::from_str(text)
+                .map_err(|err| format!("`{}` must be valid JSON! {}", self.name, err)),
+            _ => Ok(Value::String(text.to_string())),
+        }
+    }
+
+    pub fn format_value(value: &Value) -> String {
+        match value {
+            Value::String(s) => s.clone(),
+            Value::Null => String::default(),
+            other => other.to_string(),
+        }
+    }
+
+    fn is_default(&self, value: &Value) -> bool {
+        match (value, &self.default) {
+            (Value::Number(a), Value::Number(b)) => a.as_f64() == b.as_f64(),
+            (value, default) => value == default,
+        }
+    }
+}
+
+/// Describes the options of the `x-widget` types, derived from the option structs of `schema_parsing`.
+pub struct XWidgetOptions {}
+
+impl XWidgetOptions {
+    fn get_defaults() -> Vec<(String, Map<String, Value>)> {
+        XWidgetType::defaults()
+            .into_iter()
+            .filter_map(|w| match serde_json::to_value(w) {
+                Ok(Value::Object(map)) => map.into_iter().next(),
+                _ => None,
+            })
+            .filter_map(|(name, options)| match options {
+                Value::Object(options) => Some((name, options)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    pub fn get_widget_types() -> Vec<String> {
+        Self::get_defaults()
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect()
+    }
+
+    fn get_choices(widget: &str, option: &str) -> Option<&'static [&'static str]> {
+        let choices = match (widget, option) {
+            ("numeric", "type") => serde_introspect::<NumericType>(),
+            ("numeric", "value_type") => serde_introspect::<NumericValueType>(),
+            ("numeric", "show_current") => serde_introspect::<CurrentValuePosType>(),
+            ("date", "type") => serde_introspect::<DateEntryType>(),
+            ("color", "type") => serde_introspect::<ColorEntryType>(),
+            ("color", "format") => serde_introspect::<ColorEntryFormat>(),
+            ("choice", "type") => serde_introspect::<ChoiceType>(),
+            ("menu", "type") => serde_introspect::<MenuType>(),
+            ("text", "icon_position") => serde_introspect::<IconPositionType>(),
+            ("text", "direction") => serde_introspect::<TextAlignmentType>(),
+            ("text", "purpose") => serde_introspect::<PurposeType>(),
+            ("text", "hint") => serde_introspect::<HintType>(),
+            ("text", "justify") => serde_introspect::<JustificationType>(),
+            ("menu", "source.format") => serde_introspect::<SourceFormat>(),
+            (_, "orientation") => serde_introspect::<OrientationType>(),
+            _ => return None,
+        };
+        Some(choices)
+    }
+
+    pub fn get_options(widget: &str) -> Vec<WidgetOption> {
+        let defaults = Self::get_defaults()
+            .into_iter()
+            .find(|(name, _)| name == widget)
+            .map(|(_, options)| options)
+            .unwrap_or_default();
+
+        defaults
+            .into_iter()
+            .map(|(name, default)| {
+                let kind = match Self::get_choices(widget, &name) {
+                    Some(choices) => {
+                        OptionKind::Choice(choices.iter().map(|c| c.to_string()).collect())
+                    }
+                    None => match default {
+                        Value::Bool(_) => OptionKind::Bool,
+                        Value::Number(_) => OptionKind::Number,
+                        Value::Array(_) | Value::Object(_) => OptionKind::Json,
+                        Value::Null if Self::get_struct_schema(widget, &name).is_some() => {
+                            OptionKind::Json
+                        }
+                        _ => OptionKind::Text,
+                    },
+                };
+                WidgetOption {
+                    name,
+                    kind,
+                    default,
+                }
+            })
+            .collect()
+    }
+
+    /// JSON Schema of a value shaped like `default`, `null` stands for the optional strings.
+    fn get_value_schema(default: &Value) -> Value {
+        match default {
+            Value::Bool(_) => json!({ "type": "boolean" }),
+            Value::Number(n) if n.is_f64() => json!({ "type": "number" }),
+            Value::Number(_) => json!({ "type": "integer" }),
+            Value::Array(_) => json!({ "type": "array" }),
+            Value::Object(map) => json!({
+                "type": "object",
+                "properties": map
+                    .iter()
+                    .map(|(key, value)| (key.clone(), Self::get_value_schema(value)))
+                    .collect::<Map<String, Value>>(),
+            }),
+            Value::Null => json!({ "type": ["string", "null"] }),
+            _ => json!({ "type": "string" }),
+        }
+    }
+
+    /// JSON Schema of a struct, derived from a sample of it. The fields it deserializes without
+    /// aren't required, the `None` ones accept `null` and the first type of the probes that
+    /// deserializes. Enum fields take their choices from `<option>.<field>`.
+    fn derive_schema<T: Serialize + DeserializeOwned>(
+        widget: &str,
+        option: &str,
+        sample: &T,
+    ) -> Option<Value> {
+        let fields = match serde_json::to_value(sample).ok()? {
+            Value::Object(fields) => fields,
+            _ => return None,
+        };
+        let accepts = |key: &str, value: Option<Value>| {
+            let mut object = fields.clone();
+            match value {
+                Some(value) => object.insert(key.to_string(), value),
+                None => object.remove(key),
+            };
+            serde_json::from_value::<T>(Value::Object(object)).is_ok()
+        };
+        let probes = [
+            ("string", json!("")),
+            ("number", json!(0.5)),
+            ("boolean", json!(false)),
+            ("array", json!([])),
+            ("object", json!({})),
+        ];
+
+        let properties: Map<String, Value> = fields
+            .iter()
+            .map(|(key, value)| {
+                let path = format!("{}.{}", option, key);
+                let schema = match (Self::get_choices(widget, &path), value) {
+                    (Some(choices), _) => json!({ "enum": choices }),
+                    (None, Value::Null) => {
+                        let kind = probes
+                            .iter()
+                            .find(|(_, probe)| accepts(key, Some(probe.clone())))
+                            .map(|(kind, _)| *kind)
+                            .unwrap_or("string");
+                        json!({ "type": [kind, "null"] })
+                    }
+                    (None, value) => Self::get_value_schema(value),
+                };
+                (key.clone(), schema)
+            })
+            .collect();
+        let required: Vec<&String> = fields.keys().filter(|key| !accepts(key, None)).collect();
+
+        let mut schema = json!({
+            "type": "object",
+            "properties": properties,
+            "additionalProperties": false,
+        });
+        if !required.is_empty() {
+            schema["required"] = json!(required);
+        }
+        Some(schema)
+    }
+
+    /// Schema of the items of the options holding a list of structs.
+    fn get_items_schema(widget: &str, option: &str) -> Option<Value> {
+        match (widget, option) {
+            ("numeric", "marks") => Self::derive_schema(
+                widget,
+                option,
+                &MarkData {
+                    value: 0.0,
+                    text: None,
+                },
+            ),
+            _ => None,
+        }
+    }
+
+    /// Schema of the options holding a struct, they are `null` by default.
+    fn get_struct_schema(widget: &str, option: &str) -> Option<Value> {
+        match (widget, option) {
+            ("menu", "source") => Self::derive_schema(widget, option, &CommandSource::default()),
+            _ => None,
+        }
+    }
+
+    fn get_option_schema(widget: &str, option: &WidgetOption) -> Value {
+        let mut schema = match (&option.kind, Self::get_struct_schema(widget, &option.name)) {
+            (OptionKind::Choice(choices), _) => json!({ "enum": choices }),
+            (_, Some(schema)) => schema,
+            _ => Self::get_value_schema(&option.default),
+        };
+        if let Some(items) = Self::get_items_schema(widget, &option.name) {
+            schema["items"] = items;
+        }
+        if !option.default.is_null() {
+            schema["default"] = option.default.clone();
+        }
+        schema
+    }
+
+    /// JSON Schema of the `x-widget` blocks, so editors can complete and validate them.
+    pub fn get_json_schema() -> Value {
+        let widgets: Map<String, Value> = Self::get_widget_types()
+            .into_iter()
+            .map(|widget| {
+                let options: Map<String, Value> = Self::get_options(&widget)
+                    .iter()
+                    .map(|option| {
+                        (
+                            option.name.clone(),
+                            Self::get_option_schema(&widget, option),
+                        )
+                    })
+                    .collect();
+                let schema = json!({
+                    "type": "object",
+                    "properties": options,
+                    "additionalProperties": false,
+                });
+                (widget, schema)
+            })
+            .collect();
+
+        json!({
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "title": "x-widget",
+            "description": "Customizes the widget schematics-gui renders for a schema property.",
+            "type": "object",
+            "properties": widgets,
+            "additionalProperties": false,
+            "minProperties": 1,
+            "maxProperties": 1,
+        })
+    }
+
+    /// The widget type and options of a schema property, if it has an `x-widget`.
+    pub fn get_xwidget(prop: &Value) -> Option<(String, Map<String, Value>)> {
+        let (widget, options) = prop["x-widget"].as_object()?.iter().next()?;
+        Some((
+            widget.clone(),
+            options.as_object().cloned().unwrap_or_default(),
+        ))
+    }
+
+    /// Writes the `x-widget` of a property, leaving out the options that match the defaults.
+    /// Passing no widget type removes the `x-widget`.
+    pub fn set_xwidget(
+        schema: &mut Value,
+        property: &str,
+        widget: Option<&str>,
+        options: &Map<String, Value>,
+    ) {
+        let prop = match schema["properties"][property].as_object_mut() {
+            Some(prop) => prop,
+            None => return,
+        };
+
+        let widget = match widget {
+            Some(widget) => widget,
+            None => {
+                prop.remove("x-widget");
+                return;
+            }
+        };
+
+        let values: Map<String, Value> = Self::get_options(widget)
+            .iter()
+            .filter_map(|option| {
+                let value = options.get(&option.name)?;
+                match option.is_default(value) {
+                    true => None,
+                    false => Some((option.name.clone(), value.clone())),
+                }
+            })
+            .collect();
+
+        let mut xwidget = Map::new();
+        xwidget.insert(widget.to_string(), Value::Object(values));
+        prop.insert(String::from("x-widget"), Value::Object(xwidget));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json_validator::JsonValidator;
+
+    #[test]
+    fn get_widget_types_success() {
+        assert_eq!(
+            XWidgetOptions::get_widget_types(),
+            vec!["text", "numeric", "date", "color", "file", "dir", "choice", "menu"]
+        );
+    }
+
+    #[test]
+    fn get_options_kinds() {
+        let options = XWidgetOptions::get_options("menu");
+        let get_kind = |name: &str| {
+            options
+                .iter()
+                .find(|o| o.name == name)
+                .map(|o| o.kind.clone())
+        };
+
+        assert_eq!(
+            get_kind("type"),
+            Some(OptionKind::Choice(vec![
+                String::from("dropdown"),
+                String::from("combobox"),
+                String::from("multiselect"),
+                String::from("radio"),
+                String::from("toggle"),
+            ]))
+        );
+        assert_eq!(get_kind("searchable"), Some(OptionKind::Bool));
+        assert_eq!(get_kind("source"), Some(OptionKind::Json));
+        assert_eq!(
+            XWidgetOptions::get_options("numeric")
+                .iter()
+                .find(|o| o.name == "marks")
+                .map(|o| o.kind.clone()),
+            Some(OptionKind::Json)
+        );
+    }
+
+    #[test]
+    fn parse_value_success() {
+        let option = WidgetOption {
+            name: String::from("max"),
+            kind: OptionKind::Number,
+            default: json!(1.0),
+        };
+
+        assert_eq!(option.parse_value("10"), Ok(json!(10)));
+        assert_eq!(option.parse_value("1.5"), Ok(json!(1.5)));
+        assert!(option.parse_value("foo").is_err());
+    }
+
+    #[test]
+    fn set_xwidget_skips_defaults() {
+        let mut schema = json!({ "properties": { "foo": { "type": "number" } } });
+        let mut options = Map::new();
+        options.insert(String::from("type"), json!("slider"));
+        options.insert(String::from("stepping"), json!(1));
+
+        XWidgetOptions::set_xwidget(&mut schema, "foo", Some("numeric"), &options);
+
+        assert_eq!(
+            schema["properties"]["foo"]["x-widget"],
+            json!({ "numeric": { "type": "slider" } })
+        );
+        assert_eq!(
+            XWidgetOptions::get_xwidget(&schema["properties"]["foo"]).map(|(w, _)| w),
+            Some(String::from("numeric"))
+        );
+    }
+
+    #[test]
+    fn get_json_schema_success() {
+        let schema = XWidgetOptions::get_json_schema();
+        let widgets = schema["properties"].as_object().unwrap();
+        let mut types = XWidgetOptions::get_widget_types();
+        types.sort();
+
+        assert_eq!(widgets.keys().cloned().collect::<Vec<String>>(), types);
+        assert_eq!(
+            widgets["date"]["properties"]["type"]["enum"],
+            json!(["date", "time", "date_time"])
+        );
+        assert_eq!(
+            widgets["numeric"]["properties"]["slider_height"],
+            json!({ "type": "integer", "default": 100 })
+        );
+        assert_eq!(
+            widgets["numeric"]["properties"]["marks"]["items"]["properties"],
+            json!({ "value": { "type": "number" }, "text": { "type": ["string", "null"] } })
+        );
+        assert_eq!(
+            widgets["numeric"]["properties"]["marks"]["items"]["required"],
+            json!(["value"])
+        );
+        assert_eq!(widgets["menu"]["additionalProperties"], json!(false));
+        assert_eq!(
+            widgets["menu"]["properties"]["source"]["required"],
+            Value::Null
+        );
+        assert_eq!(
+            widgets["menu"]["properties"]["source"]["properties"]["format"]["enum"],
+            json!(["lines", "json"])
+        );
+    }
+
+    #[test]
+    fn get_json_schema_optional_fields() {
+        let schema = XWidgetOptions::get_json_schema();
+        let marks = &schema["properties"]["numeric"]["properties"]["marks"];
+
+        assert!(JsonValidator::is_valid(
+            marks,
+            &json!([{ "value": 1 }, { "value": 2.5, "text": null }, { "value": 3, "text": "max" }])
+        ));
+        assert!(!JsonValidator::is_valid(marks, &json!([{ "text": "min" }])));
+        assert!(JsonValidator::is_valid(
+            &schema["properties"]["menu"]["properties"]["source"],
+            &json!({ "command": "git", "format": "json" })
+        ));
+    }
+
+    #[test]
+    fn set_xwidget_removes_widget() {
+        let mut schema = json!({ "properties": { "foo": { "x-widget": { "text": {} } } } });
+
+        XWidgetOptions::set_xwidget(&mut schema, "foo", None, &Map::new());
+
+        assert_eq!(schema["properties"]["foo"], json!({}));
+    }
+}